@@ -0,0 +1,357 @@
+//! 数组操作挂件
+//!
+//! 对数组数据按顺序执行过滤、排序、截取、去重、分组和聚合操作
+
+use crate::chainware::config::ChainwareConfig;
+use crate::chainware::core::Chainware;
+use crate::core::{ChainStatus, ChainRequest, ChainResponse};
+use crate::types::{error_codes, ErrorResponse};
+use crate::utils::condition;
+use crate::utils::json_path::{JsonPathTemplate, PathContext};
+use serde_json::{json, Value};
+use std::cmp::Ordering;
+
+/// 数组操作挂件
+///
+/// 配置格式：
+/// {
+///   "operations": [
+///     {"op": "filter", "condition": "$item.price > 10"},
+///     {"op": "sort", "by": "$item.price", "order": "desc"},
+///     {"op": "limit", "count": 10, "offset": 0},
+///     {"op": "dedupe", "by": "$item.id"},
+///     {"op": "group", "by": "$item.category"},
+///     {"op": "aggregate", "field": "$item.price", "functions": ["sum", "avg", "min", "max", "count"]}
+///   ]
+/// }
+pub struct ArrayOpsChainware {
+    name: String,
+}
+
+impl Default for ArrayOpsChainware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ArrayOpsChainware {
+    pub fn new() -> Self {
+        Self {
+            name: "array_ops".to_string(),
+        }
+    }
+
+    /// 依次执行所有数组操作
+    fn process_array_ops(&self, input: &Value, operations: &[Value], context: &PathContext<'_>) -> Result<Value, String> {
+        let mut current = input.clone();
+
+        for (index, operation) in operations.iter().enumerate() {
+            let op = operation
+                .get("op")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| format!("第{}个操作缺少op字段", index + 1))?;
+
+            current = match (op, current) {
+                // 分组后的聚合：对每个分组分别聚合
+                ("aggregate", Value::Object(groups)) => {
+                    let mut result = serde_json::Map::new();
+                    for (key, group) in groups {
+                        let items = group
+                            .as_array()
+                            .ok_or_else(|| format!("分组 '{}' 不是数组", key))?;
+                        result.insert(key, self.aggregate(items, operation, context)?);
+                    }
+                    Value::Object(result)
+                }
+                (_, Value::Array(items)) => match op {
+                    "filter" => Value::Array(self.filter(items, operation, context)?),
+                    "sort" => Value::Array(self.sort(items, operation, context)?),
+                    "limit" => Value::Array(self.limit(items, operation)?),
+                    "dedupe" => Value::Array(self.dedupe(items, operation, context)?),
+                    "group" => self.group(items, operation, context)?,
+                    "aggregate" => self.aggregate(&items, operation, context)?,
+                    other => return Err(format!("第{}个操作类型不支持: {}", index + 1, other)),
+                },
+                (_, other) => {
+                    return Err(format!(
+                        "第{}个操作 '{}' 需要数组输入，实际为: {}",
+                        index + 1,
+                        op,
+                        other
+                    ))
+                }
+            };
+        }
+
+        Ok(current)
+    }
+
+    /// 获取操作中的路径参数，不以`$`开头的简单字段名视为`$item.字段名`
+    fn item_path(operation: &Value, key: &str) -> Option<String> {
+        operation.get(key).and_then(|v| v.as_str()).map(|path| {
+            if path.starts_with('$') {
+                path.to_string()
+            } else {
+                format!("$item.{}", path)
+            }
+        })
+    }
+
+    /// 获取数组元素上指定路径的值
+    fn item_value(item: &Value, path: &str, context: &PathContext<'_>) -> Result<Option<Value>, String> {
+        let item_context = JsonPathTemplate::build_item_path_context(item, context);
+        JsonPathTemplate::get_path_value(&item_context, path)
+    }
+
+    /// 过滤：保留满足条件表达式的元素
+    fn filter(&self, items: Vec<Value>, operation: &Value, context: &PathContext<'_>) -> Result<Vec<Value>, String> {
+        let condition = operation
+            .get("condition")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| "filter操作缺少condition字段".to_string())?;
+
+        let mut result = Vec::new();
+        for item in items {
+            let item_context = JsonPathTemplate::build_item_path_context(&item, context);
+            if condition::evaluate(condition, &item_context)? {
+                result.push(item);
+            }
+        }
+        Ok(result)
+    }
+
+    /// 排序：按指定字段升序或降序排序，缺失值排在最后
+    fn sort(&self, items: Vec<Value>, operation: &Value, context: &PathContext<'_>) -> Result<Vec<Value>, String> {
+        let path = Self::item_path(operation, "by").unwrap_or_else(|| "$item".to_string());
+        let descending = operation.get("order").and_then(|v| v.as_str()) == Some("desc");
+
+        let mut keyed = Vec::with_capacity(items.len());
+        for item in items {
+            let key = Self::item_value(&item, &path, context)?;
+            keyed.push((key, item));
+        }
+
+        keyed.sort_by(|(a, _), (b, _)| match (a, b) {
+            (Some(a), Some(b)) if descending => Self::compare(b, a),
+            (Some(a), Some(b)) => Self::compare(a, b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        });
+
+        Ok(keyed.into_iter().map(|(_, item)| item).collect())
+    }
+
+    /// 截取：跳过offset个元素后取count个元素
+    fn limit(&self, items: Vec<Value>, operation: &Value) -> Result<Vec<Value>, String> {
+        let count = operation
+            .get("count")
+            .and_then(|v| v.as_u64())
+            .ok_or_else(|| "limit操作缺少count字段或不是非负整数".to_string())?;
+        let offset = operation.get("offset").and_then(|v| v.as_u64()).unwrap_or(0);
+
+        Ok(items
+            .into_iter()
+            .skip(offset as usize)
+            .take(count as usize)
+            .collect())
+    }
+
+    /// 去重：按指定字段（默认整个元素）去重，保留首次出现的元素
+    fn dedupe(&self, items: Vec<Value>, operation: &Value, context: &PathContext<'_>) -> Result<Vec<Value>, String> {
+        let path = Self::item_path(operation, "by").unwrap_or_else(|| "$item".to_string());
+
+        let mut seen: Vec<Option<Value>> = Vec::new();
+        let mut result = Vec::new();
+        for item in items {
+            let key = Self::item_value(&item, &path, context)?;
+            if !seen.contains(&key) {
+                seen.push(key);
+                result.push(item);
+            }
+        }
+        Ok(result)
+    }
+
+    /// 分组：按指定字段分组，返回 {分组值: [元素]} 对象
+    fn group(&self, items: Vec<Value>, operation: &Value, context: &PathContext<'_>) -> Result<Value, String> {
+        let path = Self::item_path(operation, "by").ok_or_else(|| "group操作缺少by字段".to_string())?;
+
+        let mut groups = serde_json::Map::new();
+        for item in items {
+            let key = match Self::item_value(&item, &path, context)? {
+                Some(Value::String(s)) => s,
+                Some(Value::Null) | None => "null".to_string(),
+                Some(other) => other.to_string(),
+            };
+            groups
+                .entry(key)
+                .or_insert_with(|| Value::Array(Vec::new()))
+                .as_array_mut()
+                .unwrap()
+                .push(item);
+        }
+        Ok(Value::Object(groups))
+    }
+
+    /// 聚合：计算sum/avg/min/max/count
+    fn aggregate(&self, items: &[Value], operation: &Value, context: &PathContext<'_>) -> Result<Value, String> {
+        let path = Self::item_path(operation, "field").unwrap_or_else(|| "$item".to_string());
+        let functions: Vec<&str> = match operation.get("functions") {
+            Some(Value::Array(funcs)) => funcs.iter().filter_map(|f| f.as_str()).collect(),
+            Some(Value::String(func)) => vec![func.as_str()],
+            None => vec!["count", "sum", "avg", "min", "max"],
+            Some(_) => return Err("aggregate操作的functions必须是字符串或数组".to_string()),
+        };
+
+        // 只统计数值，字符串数字按数值处理
+        let mut numbers = Vec::new();
+        for item in items {
+            match Self::item_value(item, &path, context)? {
+                Some(Value::Number(n)) => numbers.extend(n.as_f64()),
+                Some(Value::String(s)) => numbers.extend(s.trim().parse::<f64>().ok()),
+                _ => {}
+            }
+        }
+
+        let mut result = serde_json::Map::new();
+        for func in functions {
+            let value = match func {
+                "count" => json!(items.len()),
+                "sum" => Self::number(numbers.iter().sum()),
+                "avg" if numbers.is_empty() => Value::Null,
+                "avg" => Self::number(numbers.iter().sum::<f64>() / numbers.len() as f64),
+                "min" => numbers.iter().copied().reduce(f64::min).map(Self::number).unwrap_or(Value::Null),
+                "max" => numbers.iter().copied().reduce(f64::max).map(Self::number).unwrap_or(Value::Null),
+                other => return Err(format!("不支持的聚合函数: {}", other)),
+            };
+            result.insert(func.to_string(), value);
+        }
+        Ok(Value::Object(result))
+    }
+
+    /// 数值转换为JSON，整数值保持整数形式
+    fn number(value: f64) -> Value {
+        if value.fract() == 0.0 && value.abs() < i64::MAX as f64 {
+            json!(value as i64)
+        } else {
+            json!(value)
+        }
+    }
+
+    /// 比较两个值的大小：数字按数值比较，字符串按字典序比较
+    fn compare(a: &Value, b: &Value) -> Ordering {
+        match (a, b) {
+            (Value::Number(a), Value::Number(b)) => a
+                .as_f64()
+                .partial_cmp(&b.as_f64())
+                .unwrap_or(Ordering::Equal),
+            (Value::String(a), Value::String(b)) => a.cmp(b),
+            (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+            (Value::Null, Value::Null) => Ordering::Equal,
+            (Value::Null, _) => Ordering::Greater,
+            (_, Value::Null) => Ordering::Less,
+            _ => a.to_string().cmp(&b.to_string()),
+        }
+    }
+}
+
+impl Chainware for ArrayOpsChainware {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        "对数组按顺序执行过滤、排序、截取、去重、分组和聚合"
+    }
+
+    fn config_schema(&self) -> Option<Value> {
+        Some(json!({
+            "type": "object",
+            "properties": {
+                "operations": {
+                    "type": "array",
+                    "description": "操作列表，按顺序执行，每个操作的输出作为下一个操作的输入",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "op": {"type": "string", "enum": ["filter", "sort", "limit", "dedupe", "group", "aggregate"]},
+                            "condition": {"type": "string", "description": "filter的条件表达式，当前元素通过$item访问"},
+                            "by": {"type": "string", "description": "sort/dedupe/group的字段"},
+                            "order": {"type": "string", "enum": ["asc", "desc"], "default": "asc"},
+                            "count": {"type": "integer", "minimum": 0},
+                            "offset": {"type": "integer", "minimum": 0, "default": 0},
+                            "field": {"type": "string", "description": "aggregate的字段"},
+                            "functions": {
+                                "type": ["string", "array"],
+                                "items": {"type": "string", "enum": ["count", "sum", "avg", "min", "max"]}
+                            }
+                        },
+                        "required": ["op"]
+                    }
+                }
+            },
+            "additionalProperties": false
+        }))
+    }
+
+    fn examples(&self) -> Vec<Value> {
+        vec![json!({
+            "operations": [
+                {"op": "filter", "condition": "$item.status == \"paid\""},
+                {"op": "group", "by": "$item.channel"},
+                {"op": "aggregate", "field": "amount", "functions": ["count", "sum"]}
+            ]
+        })]
+    }
+
+    fn process(
+        &self,
+        request: &ChainRequest,
+        response: &mut ChainResponse,
+        data: Option<serde_json::Value>,
+        config: Option<&ChainwareConfig>,
+    ) -> Option<serde_json::Value> {
+        let input = data.unwrap_or_default();
+
+        // 获取操作列表
+        let operations = match config.and_then(|cfg| cfg.config.get("operations")) {
+            Some(Value::Array(ops)) => ops,
+            Some(_) => {
+                response.status = ChainStatus::Error;
+                response.data = Some(
+                    ErrorResponse::new(
+                        error_codes::CONFIG_ERROR,
+                        "operations配置必须是数组类型".to_string(),
+                        None,
+                    )
+                    .to_json(),
+                );
+                return None;
+            }
+            None => {
+                // 如果没有operations配置，返回原数据
+                return Some(input);
+            }
+        };
+
+        // 构建完整的上下文对象
+        let context = JsonPathTemplate::build_path_context(&input, request, response);
+
+        match self.process_array_ops(&input, operations, &context) {
+            Ok(result) => Some(result),
+            Err(err) => {
+                response.status = ChainStatus::Error;
+                response.data = Some(
+                    ErrorResponse::new(
+                        error_codes::INTERNAL_ERROR,
+                        format!("数组操作失败: {}", err),
+                        None,
+                    )
+                    .to_json(),
+                );
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,396 @@
+//! 类型转换挂件
+//!
+//! 将指定路径的字段转换为目标类型
+
+use crate::chainware::config::ChainwareConfig;
+use crate::chainware::core::Chainware;
+use crate::core::{ChainStatus, ChainRequest, ChainResponse};
+use crate::types::{error_codes, ErrorResponse};
+use crate::utils::datetime;
+use crate::utils::json_path::JsonPathTemplate;
+use serde_json::{json, Value};
+
+/// 目标类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TargetType {
+    Int,
+    Float,
+    Bool,
+    String,
+    /// 秒级时间戳
+    Timestamp,
+    /// 毫秒级时间戳
+    TimestampMs,
+    /// ISO 8601 UTC日期时间字符串
+    Datetime,
+    /// ISO 8601 日期字符串
+    Date,
+}
+
+impl TargetType {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "int" | "integer" => Some(Self::Int),
+            "float" | "number" => Some(Self::Float),
+            "bool" | "boolean" => Some(Self::Bool),
+            "string" => Some(Self::String),
+            "timestamp" => Some(Self::Timestamp),
+            "timestamp_ms" => Some(Self::TimestampMs),
+            "datetime" => Some(Self::Datetime),
+            "date" => Some(Self::Date),
+            _ => None,
+        }
+    }
+}
+
+/// 单个字段的转换规则
+struct FieldRule {
+    target: TargetType,
+    strict: bool,
+    default: Option<Value>,
+}
+
+/// 类型转换挂件
+///
+/// 配置格式：
+/// {
+///   "fields": {
+///     "$.age": "int",
+///     "$.items[*].price": "float",
+///     "$.active": {"type": "bool", "default": false},
+///     "$.created_at": {"type": "datetime", "mode": "strict"}
+///   },
+///   "mode": "lenient"
+/// }
+pub struct ConvertChainware {
+    name: String,
+}
+
+impl Default for ConvertChainware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConvertChainware {
+    pub fn new() -> Self {
+        Self {
+            name: "convert".to_string(),
+        }
+    }
+
+    /// 解析字段转换规则
+    fn parse_rule(&self, path: &str, rule: &Value, strict: bool) -> Result<FieldRule, String> {
+        let (type_name, strict, default) = match rule {
+            Value::String(type_name) => (type_name.as_str(), strict, None),
+            Value::Object(obj) => {
+                let type_name = obj
+                    .get("type")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| format!("字段 '{}' 缺少type配置", path))?;
+                let strict = match obj.get("mode").and_then(|v| v.as_str()) {
+                    Some(mode) => mode == "strict",
+                    None => strict,
+                };
+                (type_name, strict, obj.get("default").cloned())
+            }
+            _ => return Err(format!("字段 '{}' 的转换规则必须是字符串或对象", path)),
+        };
+
+        let target = TargetType::parse(type_name)
+            .ok_or_else(|| format!("字段 '{}' 不支持的目标类型: {}", path, type_name))?;
+        Ok(FieldRule { target, strict, default })
+    }
+
+    /// 将值转换为目标类型
+    fn convert_value(&self, value: &Value, target: TargetType, strict: bool) -> Result<Value, String> {
+        if value.is_null() {
+            return if strict {
+                Err("值为null".to_string())
+            } else {
+                Ok(Value::Null)
+            };
+        }
+
+        match target {
+            TargetType::Int => self.to_int(value, strict).map(Value::from),
+            TargetType::Float => self
+                .to_float(value, strict)
+                .and_then(|f| serde_json::Number::from_f64(f).map(Value::Number).ok_or_else(|| "无效的浮点数".to_string())),
+            TargetType::Bool => self.to_bool(value, strict).map(Value::Bool),
+            TargetType::String => match value {
+                Value::String(s) => Ok(Value::String(s.clone())),
+                Value::Number(n) => Ok(Value::String(n.to_string())),
+                Value::Bool(b) => Ok(Value::String(b.to_string())),
+                _ if strict => Err("对象和数组不能转换为字符串".to_string()),
+                _ => Ok(Value::String(value.to_string())),
+            },
+            TargetType::Timestamp => self.to_timestamp_ms(value, strict).map(|ms| Value::from(ms.div_euclid(1000))),
+            TargetType::TimestampMs => self.to_timestamp_ms(value, strict).map(Value::from),
+            TargetType::Datetime => self
+                .to_timestamp_ms(value, strict)
+                .map(|ms| Value::String(datetime::format_iso_datetime(ms))),
+            TargetType::Date => self
+                .to_timestamp_ms(value, strict)
+                .map(|ms| Value::String(datetime::format_iso_date(ms))),
+        }
+    }
+
+    /// 转换为整数
+    fn to_int(&self, value: &Value, strict: bool) -> Result<i64, String> {
+        match value {
+            Value::Number(n) => {
+                if let Some(i) = n.as_i64() {
+                    return Ok(i);
+                }
+                let f = n.as_f64().unwrap_or(f64::NAN);
+                if f.fract() == 0.0 || !strict {
+                    Ok(f.trunc() as i64)
+                } else {
+                    Err(format!("{} 不是整数", n))
+                }
+            }
+            Value::String(s) => {
+                let s = s.trim();
+                if let Ok(i) = s.parse::<i64>() {
+                    return Ok(i);
+                }
+                if strict {
+                    return Err(format!("'{}' 不是整数", s));
+                }
+                s.replace([',', '_'], "")
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|f| f.is_finite())
+                    .map(|f| f.trunc() as i64)
+                    .ok_or_else(|| format!("'{}' 无法转换为整数", s))
+            }
+            Value::Bool(b) if !strict => Ok(*b as i64),
+            _ => Err(format!("{} 无法转换为整数", value)),
+        }
+    }
+
+    /// 转换为浮点数
+    fn to_float(&self, value: &Value, strict: bool) -> Result<f64, String> {
+        match value {
+            Value::Number(n) => n.as_f64().ok_or_else(|| format!("{} 无法转换为浮点数", n)),
+            Value::String(s) => {
+                let s = s.trim();
+                let text = if strict { s.to_string() } else { s.replace([',', '_'], "") };
+                text.parse::<f64>()
+                    .ok()
+                    .filter(|f| f.is_finite())
+                    .ok_or_else(|| format!("'{}' 无法转换为浮点数", s))
+            }
+            Value::Bool(b) if !strict => Ok(if *b { 1.0 } else { 0.0 }),
+            _ => Err(format!("{} 无法转换为浮点数", value)),
+        }
+    }
+
+    /// 转换为布尔值
+    fn to_bool(&self, value: &Value, strict: bool) -> Result<bool, String> {
+        match value {
+            Value::Bool(b) => Ok(*b),
+            Value::String(s) => {
+                let lower = s.trim().to_lowercase();
+                match lower.as_str() {
+                    "true" => Ok(true),
+                    "false" => Ok(false),
+                    "yes" | "y" | "1" | "on" if !strict => Ok(true),
+                    "no" | "n" | "0" | "off" | "" if !strict => Ok(false),
+                    _ => Err(format!("'{}' 无法转换为布尔值", s)),
+                }
+            }
+            Value::Number(n) if !strict => match n.as_f64() {
+                Some(1.0) => Ok(true),
+                Some(0.0) => Ok(false),
+                _ => Err(format!("{} 无法转换为布尔值", n)),
+            },
+            _ => Err(format!("{} 无法转换为布尔值", value)),
+        }
+    }
+
+    /// 转换为毫秒时间戳
+    ///
+    /// 数字按秒级时间戳处理，宽松模式下超过1e12的数字按毫秒处理
+    fn to_timestamp_ms(&self, value: &Value, strict: bool) -> Result<i64, String> {
+        let from_number = |n: f64| -> i64 {
+            if !strict && n.abs() >= 1e12 {
+                n as i64
+            } else {
+                (n * 1000.0) as i64
+            }
+        };
+
+        match value {
+            Value::Number(n) => n
+                .as_f64()
+                .map(from_number)
+                .ok_or_else(|| format!("{} 无法转换为时间", n)),
+            Value::String(s) => {
+                if let Ok(n) = s.trim().parse::<f64>() {
+                    return Ok(from_number(n));
+                }
+                datetime::parse_datetime_ms(s)
+            }
+            _ => Err(format!("{} 无法转换为时间", value)),
+        }
+    }
+
+    /// 按规则转换所有字段，返回转换结果和错误列表
+    fn process_convert(
+        &self,
+        input: &Value,
+        fields: &serde_json::Map<String, Value>,
+        strict: bool,
+    ) -> Result<(Value, Vec<Value>), String> {
+        let mut result = input.clone();
+        let mut errors = Vec::new();
+
+        for (path, rule) in fields {
+            let rule = self.parse_rule(path, rule, strict)?;
+
+            for location in JsonPathTemplate::query_paths(input, path)? {
+                let Some(slot) = JsonPathTemplate::value_at_mut(&mut result, &location) else {
+                    continue;
+                };
+                match self.convert_value(slot, rule.target, rule.strict) {
+                    Ok(converted) => *slot = converted,
+                    Err(err) => match &rule.default {
+                        Some(default) => *slot = default.clone(),
+                        None => errors.push(json!({
+                            "path": location,
+                            "value": slot.clone(),
+                            "error": err,
+                        })),
+                    },
+                }
+            }
+        }
+
+        Ok((result, errors))
+    }
+}
+
+impl Chainware for ConvertChainware {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        "将指定字段转换为目标类型"
+    }
+
+    fn config_schema(&self) -> Option<Value> {
+        let target_type = json!({
+            "type": "string",
+            "enum": ["int", "float", "bool", "string", "timestamp", "timestamp_ms", "datetime", "date"]
+        });
+        let mode = json!({"type": "string", "enum": ["lenient", "strict"]});
+
+        Some(json!({
+            "type": "object",
+            "properties": {
+                "fields": {
+                    "type": "object",
+                    "description": "key为字段路径，value为目标类型或规则对象",
+                    "additionalProperties": {
+                        "anyOf": [
+                            target_type.clone(),
+                            {
+                                "type": "object",
+                                "properties": {
+                                    "type": target_type,
+                                    "default": {"description": "转换失败时的默认值"},
+                                    "mode": mode.clone()
+                                },
+                                "required": ["type"]
+                            }
+                        ]
+                    }
+                },
+                "mode": {
+                    "type": "string",
+                    "enum": mode["enum"].clone(),
+                    "default": "lenient",
+                    "description": "解析模式，lenient宽松解析，strict严格解析"
+                }
+            },
+            "additionalProperties": false
+        }))
+    }
+
+    fn examples(&self) -> Vec<Value> {
+        vec![json!({
+            "fields": {
+                "$.age": "int",
+                "$.active": {"type": "bool", "default": false},
+                "$.items[*].price": "float"
+            }
+        })]
+    }
+
+    fn process(
+        &self,
+        _request: &ChainRequest,
+        response: &mut ChainResponse,
+        data: Option<serde_json::Value>,
+        config: Option<&ChainwareConfig>,
+    ) -> Option<serde_json::Value> {
+        let input = data.unwrap_or_default();
+
+        // 获取字段转换配置
+        let fields = match config.and_then(|cfg| cfg.config.get("fields")) {
+            Some(Value::Object(fields)) => fields,
+            Some(_) => {
+                response.status = ChainStatus::Error;
+                response.data = Some(
+                    ErrorResponse::new(
+                        error_codes::CONFIG_ERROR,
+                        "fields配置必须是对象类型".to_string(),
+                        None,
+                    )
+                    .to_json(),
+                );
+                return None;
+            }
+            None => {
+                // 如果没有fields配置，返回原数据
+                return Some(input);
+            }
+        };
+
+        let strict = config
+            .and_then(|cfg| cfg.config.get("mode"))
+            .and_then(|v| v.as_str())
+            .is_some_and(|mode| mode == "strict");
+
+        match self.process_convert(&input, fields, strict) {
+            Ok((result, errors)) if errors.is_empty() => Some(result),
+            Ok((_, errors)) => {
+                response.status = ChainStatus::Error;
+                response.data = Some(
+                    ErrorResponse::new(
+                        error_codes::VALIDATION_FAILED,
+                        format!("类型转换失败: {}个字段无法转换", errors.len()),
+                        Some(Value::Array(errors)),
+                    )
+                    .to_json(),
+                );
+                None
+            }
+            Err(err) => {
+                response.status = ChainStatus::Error;
+                response.data = Some(
+                    ErrorResponse::new(
+                        error_codes::CONFIG_ERROR,
+                        format!("类型转换配置错误: {}", err),
+                        None,
+                    )
+                    .to_json(),
+                );
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,308 @@
+//! 代码块提取挂件
+//!
+//! 从Markdown文本中提取指定语言的代码块，支持SQL语句拆分
+
+use regex::Regex;
+use serde_json::{json, Value};
+use std::sync::OnceLock;
+
+use crate::chainware::core::Chainware;
+use crate::chainware::config::ChainwareConfig;
+use crate::core::{ChainStatus, ChainRequest, ChainResponse};
+use crate::types::{error_codes, ErrorResponse};
+
+// 全局正则表达式模式
+static FENCE_PATTERN: OnceLock<Regex> = OnceLock::new();
+static SQL_STATEMENT: OnceLock<Regex> = OnceLock::new();
+
+/// 代码块
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeBlock {
+    /// 语言标签（已规范化，无标签时为None）
+    pub language: Option<String>,
+    /// 代码内容
+    pub code: String,
+}
+
+/// 规范化语言标签，统一常见别名
+pub fn normalize_language(language: &str) -> String {
+    let lower = language.trim().to_lowercase();
+    match lower.as_str() {
+        "py" | "python3" => "python".to_string(),
+        "sh" | "bash" | "zsh" | "shell" | "console" => "shell".to_string(),
+        "yml" => "yaml".to_string(),
+        "js" | "node" => "javascript".to_string(),
+        "ts" => "typescript".to_string(),
+        "rs" => "rust".to_string(),
+        "psql" | "mysql" | "postgresql" | "sqlite" => "sql".to_string(),
+        _ => lower,
+    }
+}
+
+/// 按出现顺序解析文本中所有的代码块（支持 ``` 和 ~~~ 围栏）
+pub fn parse_code_blocks(text: &str) -> Vec<CodeBlock> {
+    let fence_pattern = FENCE_PATTERN.get_or_init(|| {
+        Regex::new(r"```[ \t]*([\w+#.\-]*)[ \t]*\n?([\s\S]*?)```|~~~[ \t]*([\w+#.\-]*)[ \t]*\n?([\s\S]*?)~~~").unwrap()
+    });
+
+    fence_pattern
+        .captures_iter(text)
+        .map(|cap| {
+            let language = cap.get(1).or_else(|| cap.get(3)).map(|m| m.as_str()).unwrap_or("");
+            let code = cap.get(2).or_else(|| cap.get(4)).map(|m| m.as_str()).unwrap_or("");
+            CodeBlock {
+                language: if language.is_empty() { None } else { Some(normalize_language(language)) },
+                code: code.trim().to_string(),
+            }
+        })
+        .filter(|block| !block.code.is_empty())
+        .collect()
+}
+
+/// 拆分SQL文本为多条语句
+///
+/// 按引号外和注释（`--`单行注释、`/* */`块注释）外的`;`拆分，注释不保留在语句中，每条语句保留结尾的`;`
+pub fn split_sql_statements(sql: &str) -> Vec<String> {
+    let mut statements = Vec::new();
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+    let mut chars = sql.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match quote {
+            Some(q) => {
+                current.push(c);
+                if c == q {
+                    quote = None;
+                }
+            }
+            None => match c {
+                '\'' | '"' | '`' => {
+                    quote = Some(c);
+                    current.push(c);
+                }
+                '-' if chars.peek() == Some(&'-') => {
+                    // 跳过单行注释
+                    for next in chars.by_ref() {
+                        if next == '\n' {
+                            current.push('\n');
+                            break;
+                        }
+                    }
+                }
+                '/' if chars.peek() == Some(&'*') => {
+                    // 跳过块注释，保留一个空格分隔前后内容
+                    chars.next();
+                    let mut prev = '\0';
+                    for next in chars.by_ref() {
+                        if prev == '*' && next == '/' {
+                            break;
+                        }
+                        prev = next;
+                    }
+                    current.push(' ');
+                }
+                ';' => {
+                    current.push(';');
+                    let statement = current.trim();
+                    if statement != ";" {
+                        statements.push(statement.to_string());
+                    }
+                    current.clear();
+                }
+                _ => current.push(c),
+            },
+        }
+    }
+
+    let rest = current.trim();
+    if !rest.is_empty() {
+        statements.push(rest.to_string());
+    }
+    statements
+}
+
+/// 从非代码块文本中直接匹配SQL语句
+///
+/// `WITH`只在后面是`<名称> AS (`形式的公用表表达式时匹配，避免把"start with the following;"等普通文本当作SQL
+fn find_bare_sql_statements(text: &str) -> Vec<String> {
+    let sql_statement = SQL_STATEMENT.get_or_init(|| {
+        Regex::new(
+            r"(?i)\b(?:(?:SELECT|INSERT|UPDATE|DELETE|CREATE|ALTER|DROP|TRUNCATE|GRANT|REVOKE|COMMIT|ROLLBACK)\s+|WITH\s+(?:RECURSIVE\s+)?\w+(?:\s*\([^)]*\))?\s+AS\s*\()[\s\S]+?;",
+        )
+        .unwrap()
+    });
+    sql_statement
+        .find_iter(text)
+        .map(|m| m.as_str().trim().to_string())
+        .collect()
+}
+
+/// 代码块提取挂件
+///
+/// 配置格式：
+/// {
+///   "language": ["python", "sql"],   // 语言过滤，缺省时提取所有语言
+///   "mode": "all",                   // first（默认）或 all
+///   "include_unlabeled": true,       // 是否包含无语言标签的代码块
+///   "split_statements": true         // SQL代码块是否拆分为多条语句
+/// }
+pub struct ExtractCodeChainware {
+    name: String,
+}
+
+impl Default for ExtractCodeChainware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExtractCodeChainware {
+    pub fn new() -> Self {
+        Self {
+            name: "extract_code".to_string(),
+        }
+    }
+
+    /// 解析语言过滤配置
+    fn parse_languages(&self, config: Option<&ChainwareConfig>) -> Result<Vec<String>, String> {
+        match config.and_then(|cfg| cfg.config.get("language")) {
+            None => Ok(Vec::new()),
+            Some(Value::String(language)) => Ok(vec![normalize_language(language)]),
+            Some(Value::Array(languages)) => languages
+                .iter()
+                .map(|l| {
+                    l.as_str()
+                        .map(normalize_language)
+                        .ok_or_else(|| "language数组中的元素必须是字符串类型".to_string())
+                })
+                .collect(),
+            Some(_) => Err("language配置必须是字符串或数组类型".to_string()),
+        }
+    }
+
+    /// 提取代码块
+    fn process_extract_code(
+        &self,
+        text: &str,
+        languages: &[String],
+        include_unlabeled: bool,
+        all: bool,
+        split_statements: bool,
+    ) -> Value {
+        let mut blocks: Vec<CodeBlock> = parse_code_blocks(text)
+            .into_iter()
+            .filter(|block| match &block.language {
+                Some(language) => languages.is_empty() || languages.contains(language),
+                None => include_unlabeled,
+            })
+            .collect();
+
+        // 需要SQL且没有代码块时，尝试直接匹配SQL语句
+        if blocks.is_empty() && languages.iter().any(|l| l == "sql") {
+            let statements = find_bare_sql_statements(text);
+            if !statements.is_empty() {
+                blocks.push(CodeBlock {
+                    language: Some("sql".to_string()),
+                    code: statements.join("\n"),
+                });
+            }
+        }
+
+        let to_json = |block: &CodeBlock| -> Value {
+            let mut value = json!({
+                "language": block.language,
+                "code": block.code,
+            });
+            if split_statements && block.language.as_deref() == Some("sql") {
+                value["statements"] = json!(split_sql_statements(&block.code));
+            }
+            value
+        };
+
+        if all {
+            Value::Array(blocks.iter().map(to_json).collect())
+        } else {
+            blocks.first().map(to_json).unwrap_or(Value::Null)
+        }
+    }
+}
+
+impl Chainware for ExtractCodeChainware {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        "从Markdown文本中提取指定语言的代码块"
+    }
+
+    fn config_schema(&self) -> Option<Value> {
+        Some(json!({
+            "type": "object",
+            "properties": {
+                "language": {
+                    "type": ["string", "array"],
+                    "items": {"type": "string"},
+                    "description": "语言过滤，缺省时提取所有语言，支持py、sh、yml等常用别名"
+                },
+                "mode": {
+                    "type": "string",
+                    "enum": ["first", "all"],
+                    "default": "first",
+                    "description": "返回第一个代码块或所有代码块"
+                },
+                "include_unlabeled": {
+                    "type": "boolean",
+                    "description": "是否包含无语言标签的代码块，未指定language时默认true，否则默认false"
+                },
+                "split_statements": {
+                    "type": "boolean",
+                    "default": false,
+                    "description": "SQL代码块是否额外输出statements语句数组"
+                }
+            },
+            "additionalProperties": false
+        }))
+    }
+
+    fn examples(&self) -> Vec<Value> {
+        vec![json!({"language": ["sql"], "mode": "all", "split_statements": true})]
+    }
+
+    fn process(
+        &self,
+        _request: &ChainRequest,
+        response: &mut ChainResponse,
+        data: Option<serde_json::Value>,
+        config: Option<&ChainwareConfig>,
+    ) -> Option<serde_json::Value> {
+        let input = data.unwrap_or(Value::Null);
+
+        let languages = match self.parse_languages(config) {
+            Ok(languages) => languages,
+            Err(err) => {
+                response.status = ChainStatus::Error;
+                response.data = Some(ErrorResponse::new(error_codes::CONFIG_ERROR, err, None).to_json());
+                return None;
+            }
+        };
+
+        let get_bool = |key: &str| config.and_then(|cfg| cfg.config.get(key)).and_then(|v| v.as_bool());
+        // 未指定语言时默认包含无标签代码块
+        let include_unlabeled = get_bool("include_unlabeled").unwrap_or(languages.is_empty());
+        let split_statements = get_bool("split_statements").unwrap_or(false);
+        let all = config
+            .and_then(|cfg| cfg.config.get("mode"))
+            .and_then(|v| v.as_str())
+            .is_some_and(|mode| mode == "all");
+
+        // 获取文本内容 - 只支持字符串类型，非字符串返回 Null
+        let text = match &input {
+            Value::String(s) => s.as_str(),
+            _ => return Some(Value::Null),
+        };
+
+        Some(self.process_extract_code(text, &languages, include_unlabeled, all, split_statements))
+    }
+}
@@ -4,11 +4,19 @@
 
 use crate::chainware::core::Chainware;
 use crate::chainware::config::ChainwareConfig;
-use crate::core::{ChainRequest, ChainResponse};
+use crate::core::{ChainStatus, ChainRequest, ChainResponse};
 use crate::types::{error_codes, ErrorResponse};
-use serde_json::Value;
+use crate::utils::{json_repair, json_schema};
+use serde_json::{json, Value};
 
 /// JSON提取挂件
+///
+/// 配置格式（均为可选）：
+/// {
+///   "repair": true,                    // 修复LLM输出中常见的JSON格式缺陷
+///   "mode": "first",                   // first（默认）、all（返回所有）、largest（返回最大的）
+///   "schema": {"required": ["sql"]}    // 只返回匹配该Schema的候选值
+/// }
 pub struct ExtractJsonChainware {
     name: String,
 }
@@ -27,7 +35,7 @@ impl ExtractJsonChainware {
     }
 
     /// 提取JSON：从文本中提取JSON对象或数组
-    fn process_extract_json(&self, input: &Value) -> Result<Value, String> {
+    fn process_extract_json(&self, input: &Value, options: &ExtractJsonOptions) -> Result<Value, String> {
         // 获取文本内容
         let text = match input {
             Value::String(s) => s.as_str(),
@@ -35,85 +43,133 @@ impl ExtractJsonChainware {
             _ => return Err("提取JSON需要字符串输入".to_string()),
         };
 
-        // 按照文本中出现的顺序处理 JSON 对象和数组
-        for (i, c) in text.char_indices() {
-            if c == '{' {
-                // 尝试解析JSON对象
-                if let Ok(json_value) = self.try_parse_json_object(text, i) {
-                    return Ok(json_value);
-                }
-            } else if c == '[' {
-                // 尝试解析JSON数组
-                if let Ok(json_value) = self.try_parse_json_array(text, i) {
-                    return Ok(json_value);
-                }
-            }
+        // 只需第一个结果时找到即停止
+        let stop_at_first = options.mode == "first" && options.schema.is_none();
+        let mut candidates = self.find_candidates(text, options.repair, stop_at_first);
+
+        // 按Schema过滤候选值
+        if let Some(schema) = &options.schema {
+            candidates.retain(|candidate| json_schema::is_valid(candidate, schema));
         }
 
-        // 没有找到有效的JSON
-        Ok(Value::Null)
+        match options.mode.as_str() {
+            "all" => Ok(Value::Array(candidates)),
+            "largest" => Ok(candidates
+                .into_iter()
+                .max_by_key(|candidate| candidate.to_string().len())
+                .unwrap_or(Value::Null)),
+            // 没有找到有效的JSON时返回Null
+            _ => Ok(candidates.into_iter().next().unwrap_or(Value::Null)),
+        }
     }
 
-    /// 尝试从指定位置解析JSON对象
-    fn try_parse_json_object(&self, text: &str, start_pos: usize) -> Result<Value, String> {
-        let mut brace_count = 0;
-        let mut end_pos = None;
-
-        // 从开始位置查找匹配的结束位置
-        for (i, c) in text[start_pos..].char_indices() {
-            let actual_pos = start_pos + i;
-            if c == '{' {
-                brace_count += 1;
-            } else if c == '}' {
-                brace_count -= 1;
-                if brace_count == 0 {
-                    end_pos = Some(actual_pos);
+    /// 按照文本中出现的顺序查找所有JSON对象和数组
+    ///
+    /// 找到的值所在范围内不再查找嵌套的候选值
+    fn find_candidates(&self, text: &str, repair: bool, stop_at_first: bool) -> Vec<Value> {
+        let mut candidates = Vec::new();
+        let mut next_pos = 0;
+
+        for (i, c) in text.char_indices() {
+            if i < next_pos || (c != '{' && c != '[') {
+                continue;
+            }
+
+            if let Some((value, end)) = self.try_parse_json(text, i, repair) {
+                candidates.push(value);
+                if stop_at_first {
                     break;
                 }
+                next_pos = end;
             }
         }
 
-        // 如果找到完整的对象，尝试解析
-        if let Some(end) = end_pos {
-            let json_str = &text[start_pos..=end];
-            match serde_json::from_str::<Value>(json_str) {
-                Ok(json_value) => Ok(json_value),
-                Err(_) => Err("JSON对象解析失败".to_string()),
-            }
-        } else {
-            Err("未找到完整的JSON对象".to_string())
-        }
+        candidates
     }
 
-    /// 尝试从指定位置解析JSON数组
-    fn try_parse_json_array(&self, text: &str, start_pos: usize) -> Result<Value, String> {
-        let mut bracket_count = 0;
-        let mut end_pos = None;
-
-        // 从开始位置查找匹配的结束位置
-        for (i, c) in text[start_pos..].char_indices() {
-            let actual_pos = start_pos + i;
-            if c == '[' {
-                bracket_count += 1;
-            } else if c == ']' {
-                bracket_count -= 1;
-                if bracket_count == 0 {
-                    end_pos = Some(actual_pos);
-                    break;
+    /// 流式输入时查找第一个完整的JSON值
+    ///
+    /// 按与完整文本相同的顺序检查候选位置，遇到尚未闭合的候选时等待更多输入，
+    /// 保证提前给出的结果与完整文本的结果一致。之前的候选在文本追加后结果不变，
+    /// `scan_from`记录第一个尚未闭合的候选位置，下次从该位置继续
+    fn find_first_complete(&self, text: &str, scan_from: &mut usize, options: &ExtractJsonOptions) -> Option<Value> {
+        let start = (*scan_from).min(text.len());
+        let mut next_pos = start;
+
+        for (offset, c) in text[start..].char_indices() {
+            let i = start + offset;
+            if i < next_pos || (c != '{' && c != '[') {
+                continue;
+            }
+
+            if json_repair::find_json_end(text, i).is_none() {
+                *scan_from = i;
+                return None;
+            }
+            if let Some((value, end)) = self.try_parse_json(text, i, options.repair) {
+                if options.schema.as_ref().is_none_or(|schema| json_schema::is_valid(&value, schema)) {
+                    return Some(value);
                 }
+                next_pos = end;
             }
         }
 
-        // 如果找到完整的数组，尝试解析
-        if let Some(end) = end_pos {
-            let json_str = &text[start_pos..=end];
-            match serde_json::from_str::<Value>(json_str) {
-                Ok(json_value) => Ok(json_value),
-                Err(_) => Err("JSON数组解析失败".to_string()),
+        *scan_from = text.len().max(next_pos);
+        None
+    }
+
+    /// 尝试从指定位置解析JSON对象或数组，返回解析结果和结束位置（不含）
+    fn try_parse_json(&self, text: &str, start_pos: usize, repair: bool) -> Option<(Value, usize)> {
+        // 优先按标准JSON解析
+        if let Some(end) = json_repair::find_json_end(text, start_pos) {
+            if let Ok(json_value) = serde_json::from_str::<Value>(&text[start_pos..=end]) {
+                return Some((json_value, end + 1));
             }
-        } else {
-            Err("未找到完整的JSON数组".to_string())
         }
+
+        // 修复模式下宽松解析，忽略空的对象和数组
+        if repair {
+            return json_repair::parse_lenient(text, start_pos).filter(|(value, _)| match value {
+                Value::Object(obj) => !obj.is_empty(),
+                Value::Array(arr) => !arr.is_empty(),
+                _ => false,
+            });
+        }
+
+        None
+    }
+}
+
+/// JSON提取选项
+struct ExtractJsonOptions {
+    /// 是否启用宽松修复解析
+    repair: bool,
+    /// 返回模式：first、all、largest
+    mode: String,
+    /// 候选值需要匹配的Schema
+    schema: Option<Value>,
+}
+
+impl ExtractJsonOptions {
+    fn from_config(config: Option<&ChainwareConfig>) -> Result<Self, String> {
+        let get = |key: &str| config.and_then(|cfg| cfg.config.get(key));
+
+        let mode = match get("mode") {
+            None => "first".to_string(),
+            Some(Value::String(mode)) if ["first", "all", "largest"].contains(&mode.as_str()) => mode.clone(),
+            Some(other) => return Err(format!("不支持的mode配置: {}", other)),
+        };
+        let schema = match get("schema") {
+            None => None,
+            Some(schema @ Value::Object(_)) => Some(schema.clone()),
+            Some(_) => return Err("schema配置必须是对象类型".to_string()),
+        };
+
+        Ok(Self {
+            repair: get("repair").and_then(|v| v.as_bool()).unwrap_or(false),
+            mode,
+            schema,
+        })
     }
 }
 
@@ -122,16 +178,68 @@ impl Chainware for ExtractJsonChainware {
         &self.name
     }
 
+    fn description(&self) -> &str {
+        "从文本中提取JSON值，可修复LLM输出中的常见缺陷"
+    }
+
+    fn config_schema(&self) -> Option<Value> {
+        Some(json!({
+            "type": "object",
+            "properties": {
+                "repair": {
+                    "type": "boolean",
+                    "default": false,
+                    "description": "修复结尾逗号、单引号、未加引号的键、注释和截断等缺陷"
+                },
+                "mode": {
+                    "type": "string",
+                    "enum": ["first", "all", "largest"],
+                    "default": "first",
+                    "description": "返回第一个、所有或最大的JSON值"
+                },
+                "schema": {
+                    "type": "object",
+                    "description": "JSON Schema，只返回匹配的候选值"
+                }
+            },
+            "additionalProperties": false
+        }))
+    }
+
+    fn examples(&self) -> Vec<Value> {
+        vec![json!({"repair": true, "schema": {"required": ["sql"]}})]
+    }
+
+    /// 只有`first`模式支持流式输入；修复模式下单引号字符串中的括号无法判断是否闭合，不提前给出结果
+    fn supports_streaming(&self, config: Option<&ChainwareConfig>) -> bool {
+        ExtractJsonOptions::from_config(config).is_ok_and(|options| options.mode == "first" && !options.repair)
+    }
+
+    /// 从上次停止处的第一个尚未闭合的候选位置继续查找
+    fn process_partial(&self, text: &str, scan_from: &mut usize, config: Option<&ChainwareConfig>) -> Option<Value> {
+        let options = ExtractJsonOptions::from_config(config).ok()?;
+        self.find_first_complete(text, scan_from, &options)
+    }
+
     fn process(
         &self,
         _request: &ChainRequest,
         response: &mut ChainResponse,
         data: Option<serde_json::Value>,
-        _config: Option<&ChainwareConfig>,
+        config: Option<&ChainwareConfig>,
     ) -> Option<serde_json::Value> {
         let input = data.unwrap_or_default();
 
-        match self.process_extract_json(&input) {
+        let options = match ExtractJsonOptions::from_config(config) {
+            Ok(options) => options,
+            Err(err) => {
+                response.status = ChainStatus::Error;
+                response.data = Some(ErrorResponse::new(error_codes::CONFIG_ERROR, err, None).to_json());
+                return None;
+            }
+        };
+
+        match self.process_extract_json(&input, &options) {
             Ok(result) => Some(result),
             Err(err) => {
                 response.data = Some(
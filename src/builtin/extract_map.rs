@@ -6,8 +6,8 @@ use crate::chainware::core::Chainware;
 use crate::chainware::config::ChainwareConfig;
 use crate::core::{ChainStatus, ChainRequest, ChainResponse};
 use crate::types::{error_codes, ErrorResponse};
-use crate::utils::json_path::JsonPathTemplate;
-use serde_json::Value;
+use crate::utils::json_path::{JsonPathTemplate, PathContext};
+use serde_json::{json, Value};
 
 /// 提取映射挂件
 /// 
@@ -47,7 +47,7 @@ impl ExtractMapChainware {
     /// # 返回
     /// - 成功：包含提取值的新对象
     /// - 失败：错误信息
-    fn process_extract_map(&self, _input: &Value, mappings: &Value, context: &Value) -> Result<Value, String> {
+    fn process_extract_map(&self, _input: &Value, mappings: &Value, context: &PathContext<'_>) -> Result<Value, String> {
         // 验证映射配置必须是对象
         let mapping_obj = match mappings.as_object() {
             Some(obj) => obj,
@@ -86,12 +86,12 @@ impl ExtractMapChainware {
     /// - JSONPath: `$.path.to.value` 或 `$input.field` 或 `$params.key`
     /// - 模板字符串: `"Hello ${.name}!"` 
     /// - 字面量: `"literal_string"`
-    fn extract_value_from_template(&self, template: &str, context: &Value) -> Result<Value, String> {
+    fn extract_value_from_template(&self, template: &str, context: &PathContext<'_>) -> Result<Value, String> {
         let template = template.trim();
 
         // 情况1：以$开头的JSONPath路径 或 包含${}变量的模板字符串
         if template.starts_with('$') || template.contains("${") {
-            match JsonPathTemplate::get_value(context, template) {
+            match JsonPathTemplate::get_path_value(context, template) {
                 Ok(Some(value)) => Ok(value),
                 Ok(None) => Ok(Value::String(template.to_string())),
                 Err(e) => Err(e),
@@ -126,6 +126,34 @@ impl Chainware for ExtractMapChainware {
         &self.name
     }
 
+    fn description(&self) -> &str {
+        "从输入数据中提取指定字段组成新对象"
+    }
+
+    fn config_schema(&self) -> Option<Value> {
+        Some(json!({
+            "type": "object",
+            "properties": {
+                "mapping": {
+                    "type": "object",
+                    "description": "提取映射，key为新字段名，value为源路径、模板或字面量"
+                }
+            },
+            "required": ["mapping"],
+            "additionalProperties": false
+        }))
+    }
+
+    fn examples(&self) -> Vec<Value> {
+        vec![json!({
+            "mapping": {
+                "username": "$.user.profile.name",
+                "full_address": "${$.address.street} ${$.address.city}",
+                "role": "default_user"
+            }
+        })]
+    }
+
     fn process(
         &self,
         request: &ChainRequest,
@@ -169,7 +197,7 @@ impl Chainware for ExtractMapChainware {
         };
 
         // 构建完整的上下文对象
-        let context = JsonPathTemplate::build_context(&input, request);
+        let context = JsonPathTemplate::build_path_context(&input, request, response);
 
         // 执行提取映射
         match self.process_extract_map(&input, &mappings, &context) {
@@ -1,5 +1,5 @@
 use regex::Regex;
-use serde_json::Value;
+use serde_json::{json, Value};
 use std::sync::OnceLock;
 
 use crate::chainware::core::Chainware;
@@ -72,6 +72,18 @@ impl Chainware for ExtractSqlChainware {
         &self.name
     }
 
+    fn description(&self) -> &str {
+        "从文本中提取SQL语句，支持Markdown代码块和直接SQL语句"
+    }
+
+    fn config_schema(&self) -> Option<Value> {
+        Some(json!({
+            "type": "object",
+            "properties": {},
+            "additionalProperties": false
+        }))
+    }
+
     fn process(
         &self,
         _request: &ChainRequest,
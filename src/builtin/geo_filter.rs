@@ -0,0 +1,444 @@
+//! 地理位置过滤挂件
+//!
+//! 通过本地MaxMind格式（MMDB）数据库查询客户端IP所属国家和ASN，按国家和ASN名单放行或拒绝
+
+use crate::chainware::config::ChainwareConfig;
+use crate::chainware::core::Chainware;
+use crate::core::{ChainStatus, ChainRequest, ChainResponse};
+use crate::types::{error_codes, ErrorResponse};
+use crate::utils::ip_utils;
+use maxminddb::{MaxMindDBError, Reader};
+use serde_json::{json, Value};
+use std::net::IpAddr;
+use std::sync::OnceLock;
+
+/// 地理位置查询结果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GeoInfo {
+    /// 国家ISO代码（大写）
+    pub country: Option<String>,
+    /// 大洲代码
+    pub continent: Option<String>,
+    /// 自治系统号
+    pub asn: Option<u32>,
+    /// 自治系统所属组织
+    pub as_org: Option<String>,
+}
+
+impl GeoInfo {
+    /// 是否未查询到任何信息
+    pub fn is_unknown(&self) -> bool {
+        self.country.is_none() && self.asn.is_none()
+    }
+
+    /// 合并数据库记录，已有字段不覆盖
+    fn merge_record(&mut self, record: &Value) {
+        let text = |path: &[&str]| {
+            path.iter()
+                .try_fold(record, |value, key| value.get(key))
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+        };
+
+        if self.country.is_none() {
+            self.country = text(&["country", "iso_code"])
+                .or_else(|| text(&["registered_country", "iso_code"]))
+                .map(|c| c.to_uppercase());
+        }
+        if self.continent.is_none() {
+            self.continent = text(&["continent", "code"]);
+        }
+        if self.asn.is_none() {
+            self.asn = record
+                .get("autonomous_system_number")
+                .and_then(|v| v.as_u64())
+                .and_then(|n| u32::try_from(n).ok());
+        }
+        if self.as_org.is_none() {
+            self.as_org = text(&["autonomous_system_organization"]);
+        }
+    }
+
+    fn to_json(&self, ip: &IpAddr) -> Value {
+        json!({
+            "ip": ip.to_string(),
+            "country": self.country,
+            "continent": self.continent,
+            "asn": self.asn,
+            "as_org": self.as_org,
+        })
+    }
+}
+
+/// 解析字符串列表配置，支持逗号分隔的字符串或字符串数组，忽略空项
+fn string_list(key: &str, value: &Value) -> Result<Vec<String>, String> {
+    let items: Vec<&str> = match value {
+        Value::String(text) => text.split(',').collect(),
+        Value::Array(values) => values
+            .iter()
+            .map(|v| v.as_str().ok_or_else(|| format!("{}数组中的元素必须是字符串类型", key)))
+            .collect::<Result<_, _>>()?,
+        _ => return Err(format!("{}配置必须是字符串或数组类型", key)),
+    };
+    Ok(items
+        .into_iter()
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// 解析ASN：32位无符号整数，或带一个`AS`前缀（不区分大小写）的字符串，超出范围时返回None
+fn parse_asn(value: &Value) -> Option<u32> {
+    match value {
+        Value::Number(n) => n.as_u64().and_then(|n| u32::try_from(n).ok()),
+        Value::String(s) => {
+            let s = s.trim();
+            let digits = match s.get(..2) {
+                Some(prefix) if prefix.eq_ignore_ascii_case("AS") => &s[2..],
+                _ => s,
+            };
+            // 只接受数字，拒绝符号和重复的前缀
+            if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            digits.parse().ok()
+        }
+        _ => None,
+    }
+}
+
+/// 地理位置过滤规则（每个节点只加载一次数据库）
+struct GeoRules {
+    readers: Vec<Reader<Vec<u8>>>,
+    allow_countries: Vec<String>,
+    deny_countries: Vec<String>,
+    allow_asns: Vec<u32>,
+    deny_asns: Vec<u32>,
+    /// 未查询到地理信息时是否放行
+    allow_unknown: bool,
+    /// 附加地理信息的字段名，None表示不附加
+    attach_key: Option<String>,
+    client_ip: ip_utils::ClientIpOptions,
+}
+
+impl GeoRules {
+    fn from_config(config: Option<&ChainwareConfig>) -> Result<Self, String> {
+        let get = |key: &str| config.and_then(|cfg| cfg.config.get(key));
+
+        let paths: Vec<String> = match get("database") {
+            Some(Value::String(path)) => vec![path.clone()],
+            Some(Value::Array(paths)) => paths
+                .iter()
+                .map(|p| {
+                    p.as_str()
+                        .map(|s| s.to_string())
+                        .ok_or_else(|| "database数组中的元素必须是字符串类型".to_string())
+                })
+                .collect::<Result<_, _>>()?,
+            Some(_) => return Err("database配置必须是字符串或数组类型".to_string()),
+            None => return Err("缺少database配置".to_string()),
+        };
+        let readers = paths
+            .iter()
+            .map(|path| {
+                Reader::open_readfile(path).map_err(|e| format!("加载地理数据库失败: {}, {}", path, e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let countries = |key: &str| -> Result<Vec<String>, String> {
+            match get(key) {
+                None => Ok(Vec::new()),
+                Some(value) => string_list(key, value)
+                    .map(|list| list.into_iter().map(|c| c.to_uppercase()).collect()),
+            }
+        };
+        let asns = |key: &str| -> Result<Vec<u32>, String> {
+            match get(key) {
+                None => Ok(Vec::new()),
+                Some(Value::Array(values)) => values
+                    .iter()
+                    .map(|v| parse_asn(v).ok_or_else(|| format!("{}中的无效ASN: {}", key, v)))
+                    .collect(),
+                Some(value) => parse_asn(value)
+                    .map(|asn| vec![asn])
+                    .ok_or_else(|| format!("{}中的无效ASN: {}", key, value)),
+            }
+        };
+
+        let allow_countries = countries("allow_countries")?;
+        let allow_asns = asns("allow_asns")?;
+        // 默认：配置了允许名单时拒绝未知IP，否则放行
+        let allow_unknown = match get("on_unknown").and_then(|v| v.as_str()) {
+            Some("allow") => true,
+            Some("deny") => false,
+            Some(other) => return Err(format!("不支持的on_unknown配置: {}", other)),
+            None => allow_countries.is_empty() && allow_asns.is_empty(),
+        };
+        let attach_key = match get("attach") {
+            None | Some(Value::Bool(false)) => None,
+            Some(Value::Bool(true)) => Some("geo".to_string()),
+            Some(Value::String(key)) => Some(key.clone()),
+            Some(_) => return Err("attach配置必须是布尔值或字符串类型".to_string()),
+        };
+
+        Ok(Self {
+            readers,
+            allow_countries,
+            deny_countries: countries("deny_countries")?,
+            allow_asns,
+            deny_asns: asns("deny_asns")?,
+            allow_unknown,
+            attach_key,
+            client_ip: ip_utils::ClientIpOptions::from_config(config)?,
+        })
+    }
+
+    /// 在所有数据库中查询IP的地理信息
+    fn lookup(&self, ip: IpAddr) -> Result<GeoInfo, String> {
+        let mut info = GeoInfo::default();
+        for reader in &self.readers {
+            match reader.lookup::<Value>(ip) {
+                Ok(record) => info.merge_record(&record),
+                Err(MaxMindDBError::AddressNotFoundError(_)) => {}
+                // IPv4数据库中查询IPv6地址
+                Err(MaxMindDBError::InvalidNetworkError(_)) => {}
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+        Ok(info)
+    }
+
+    /// 检查地理信息是否允许访问，拒绝时返回原因
+    fn check(&self, info: &GeoInfo) -> Option<String> {
+        if info.is_unknown() {
+            return (!self.allow_unknown).then(|| "无法确定地理位置".to_string());
+        }
+
+        if let Some(country) = &info.country {
+            if self.deny_countries.contains(country) {
+                return Some(format!("所属国家 {} 在拒绝名单中", country));
+            }
+        }
+        if let Some(asn) = info.asn {
+            if self.deny_asns.contains(&asn) {
+                return Some(format!("所属ASN {} 在拒绝名单中", asn));
+            }
+        }
+        if !self.allow_countries.is_empty()
+            && !info.country.as_ref().is_some_and(|c| self.allow_countries.contains(c))
+        {
+            return Some(format!(
+                "所属国家 {} 不在允许名单中",
+                info.country.as_deref().unwrap_or("未知")
+            ));
+        }
+        if !self.allow_asns.is_empty() && !info.asn.is_some_and(|asn| self.allow_asns.contains(&asn)) {
+            return Some(format!(
+                "所属ASN {} 不在允许名单中",
+                info.asn.map(|asn| asn.to_string()).unwrap_or_else(|| "未知".to_string())
+            ));
+        }
+        None
+    }
+}
+
+/// 地理位置过滤挂件（需要启用`geo`特性）
+///
+/// 配置格式：
+/// {
+///   "database": ["GeoLite2-Country.mmdb", "GeoLite2-ASN.mmdb"],
+///   "allow_countries": ["CN", "HK"],
+///   "deny_countries": ["KP"],
+///   "allow_asns": [4134],
+///   "deny_asns": ["AS15169"],
+///   "on_unknown": "deny",            // allow 或 deny，默认：配置了允许名单时为deny
+///   "attach": "geo",                 // 附加地理信息到数据的字段名，true等同于"geo"
+///   "ip_key": "ip_address",          // 客户端IP解析与ip_whitelist相同
+///   "trusted_proxies": ["10.0.0.0/8"]
+/// }
+pub struct GeoFilterChainware {
+    name: String,
+    /// 加载后的数据库和规则，首次执行时根据节点配置构建，构建失败时不缓存，下次执行时重试
+    rules: OnceLock<GeoRules>,
+}
+
+impl Default for GeoFilterChainware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GeoFilterChainware {
+    pub fn new() -> Self {
+        Self {
+            name: "geo_filter".to_string(),
+            rules: OnceLock::new(),
+        }
+    }
+
+    /// 获取地理过滤规则，节点配置不变，加载成功后不再重复加载数据库
+    fn rules(&self, config: Option<&ChainwareConfig>) -> Result<&GeoRules, String> {
+        if let Some(rules) = self.rules.get() {
+            return Ok(rules);
+        }
+        let rules = GeoRules::from_config(config)?;
+        Ok(self.rules.get_or_init(|| rules))
+    }
+}
+
+impl Chainware for GeoFilterChainware {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        "通过本地MMDB数据库查询客户端IP所属国家和ASN，按名单放行或拒绝"
+    }
+
+    fn config_schema(&self) -> Option<Value> {
+        let countries = json!({
+            "type": ["string", "array"],
+            "items": {"type": "string"},
+            "description": "国家ISO代码，不区分大小写"
+        });
+        let asns = json!({
+            "type": "array",
+            "items": {"type": ["integer", "string"]},
+            "description": "ASN，支持数字或\"AS15169\"格式"
+        });
+
+        let mut properties = ip_utils::ClientIpOptions::schema_properties();
+        let own = json!({
+            "database": {
+                "type": ["string", "array"],
+                "items": {"type": "string"},
+                "description": "MMDB数据库文件路径，查询结果按顺序合并"
+            },
+            "allow_countries": countries,
+            "deny_countries": countries,
+            "allow_asns": asns,
+            "deny_asns": asns,
+            "on_unknown": {
+                "type": "string",
+                "enum": ["allow", "deny"],
+                "description": "数据库中查不到IP时的处理方式，默认配置了允许名单时为deny，否则为allow"
+            },
+            "attach": {
+                "type": ["boolean", "string"],
+                "description": "将地理信息附加到对象数据的字段，true等同于\"geo\""
+            }
+        });
+        if let Value::Object(own) = own {
+            properties.extend(own);
+        }
+
+        Some(json!({
+            "type": "object",
+            "properties": properties,
+            "required": ["database"],
+            "additionalProperties": false
+        }))
+    }
+
+    fn examples(&self) -> Vec<Value> {
+        vec![json!({
+            "database": ["/data/GeoLite2-Country.mmdb", "/data/GeoLite2-ASN.mmdb"],
+            "allow_countries": ["CN", "HK", "SG"],
+            "deny_asns": ["AS14061"],
+            "attach": true
+        })]
+    }
+
+    fn process(
+        &self,
+        request: &ChainRequest,
+        response: &mut ChainResponse,
+        data: Option<serde_json::Value>,
+        config: Option<&ChainwareConfig>,
+    ) -> Option<serde_json::Value> {
+        let input = data.unwrap_or_default();
+
+        // 获取地理过滤规则
+        let rules = match self.rules(config) {
+            Ok(rules) => rules,
+            Err(err) => {
+                response.status = ChainStatus::Error;
+                response.data = Some(ErrorResponse::new(error_codes::CONFIG_ERROR, err, None).to_json());
+                return Some(input); // 数据透传
+            }
+        };
+
+        // 从配置中获取IP地址字段名，默认为"ip_address"
+        let ip_key = config
+            .and_then(|cfg| cfg.config.get("ip_key"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("ip_address");
+
+        // 从meta中获取IP地址
+        let ip_address = match request.meta.get(ip_key) {
+            Some(Value::String(ip)) => ip,
+            Some(_) => {
+                response.status = ChainStatus::Reject;
+                response.data = Some(
+                    ErrorResponse::new(
+                        error_codes::FORBIDDEN,
+                        format!("meta中的{}必须是字符串类型", ip_key),
+                        None,
+                    )
+                    .to_json(),
+                );
+                return None;
+            }
+            None => {
+                response.status = ChainStatus::Reject;
+                response.data = Some(
+                    ErrorResponse::new(error_codes::FORBIDDEN, format!("meta中缺少{}", ip_key), None).to_json(),
+                );
+                return None;
+            }
+        };
+
+        // 解析真实客户端IP并查询地理信息
+        let lookup = ip_utils::resolve_client_ip(ip_address, &request.meta, &rules.client_ip)
+            .and_then(|ip| rules.lookup(ip).map(|info| (ip, info)));
+        let (client_ip, info) = match lookup {
+            Ok(result) => result,
+            Err(err) => {
+                response.status = ChainStatus::Error;
+                response.data = Some(
+                    ErrorResponse::new(
+                        error_codes::INTERNAL_ERROR,
+                        format!("地理位置检查失败: {}", err),
+                        None,
+                    )
+                    .to_json(),
+                );
+                return None;
+            }
+        };
+        response.set_meta(rules.client_ip.client_ip_key.clone(), Value::String(client_ip.to_string()));
+
+        if let Some(reason) = rules.check(&info) {
+            response.status = ChainStatus::Reject;
+            response.data = Some(
+                ErrorResponse::new(
+                    error_codes::FORBIDDEN,
+                    format!("IP地址 {} {}", client_ip, reason),
+                    Some(info.to_json(&client_ip)),
+                )
+                .to_json(),
+            );
+            return None;
+        }
+
+        // 附加地理信息到对象数据
+        match (&rules.attach_key, input) {
+            (Some(key), Value::Object(mut obj)) => {
+                obj.insert(key.clone(), info.to_json(&client_ip));
+                Some(Value::Object(obj))
+            }
+            (_, input) => Some(input),
+        }
+    }
+}
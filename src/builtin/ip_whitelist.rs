@@ -7,12 +7,26 @@ use crate::chainware::core::Chainware;
 use crate::core::{ChainStatus, ChainRequest, ChainResponse};
 use crate::types::{error_codes, ErrorResponse};
 use crate::utils::ip_utils;
-use serde_json::Value;
-use std::net::IpAddr;
+use serde_json::{json, Value};
+use std::sync::OnceLock;
 
 /// IP白名单过滤挂件
+///
+/// 配置格式：
+/// {
+///   "ip_list": ["192.168.1.100", "10.0.0.0/8", "10.1.0.1-10.1.0.99"],
+///   "ip_file": "/etc/linkchain/ip_list.txt",    // 名单文件，每行一个条目，支持#注释
+///   "ip_key": "ip_address",                     // meta中直连IP的字段名
+///   "trusted_proxies": ["10.0.0.0/8"],          // 可信代理，配置后从转发头解析真实客户端IP
+///   "forwarded_header": "x-forwarded-for",      // 信任的转发头：x-forwarded-for或forwarded
+///   "forwarded_key": "forwarded",               // meta中RFC 7239 Forwarded头的字段名
+///   "forwarded_for_key": "x-forwarded-for",     // meta中X-Forwarded-For头的字段名
+///   "client_ip_key": "client_ip"                // 解析结果写入响应meta的字段名
+/// }
 pub struct IpWhitelistChainware {
     name: String,
+    /// 编译后的过滤规则，首次执行时根据节点配置构建，构建失败时不缓存，下次执行时重试
+    rules: OnceLock<ip_utils::IpFilterRules>,
 }
 
 impl Default for IpWhitelistChainware {
@@ -25,91 +39,17 @@ impl IpWhitelistChainware {
     pub fn new() -> Self {
         Self {
             name: "ip_whitelist".to_string(),
+            rules: OnceLock::new(),
         }
     }
 
-    /// 检查IP是否在白名单中
-    fn is_ip_in_whitelist(&self, ip: &str, whitelist: &[String]) -> Result<bool, String> {
-        // 解析IP地址
-        let target_ip: IpAddr = ip.parse().map_err(|_| format!("无效的IP地址: {}", ip))?;
-
-        // 检查是否在白名单中
-        for whitelist_ip in whitelist {
-            // 支持单个IP和CIDR格式
-            if whitelist_ip.contains('/') {
-                // CIDR格式处理
-                if self.ip_in_cidr(&target_ip, whitelist_ip)? {
-                    return Ok(true);
-                }
-            } else {
-                // 单个IP地址比较
-                let white_ip: IpAddr = whitelist_ip
-                    .parse()
-                    .map_err(|_| format!("白名单中的无效IP地址: {}", whitelist_ip))?;
-                if target_ip == white_ip {
-                    return Ok(true);
-                }
-            }
-        }
-
-        Ok(false)
-    }
-
-    /// 检查IP是否在CIDR网段中
-    fn ip_in_cidr(&self, ip: &IpAddr, cidr: &str) -> Result<bool, String> {
-        let parts: Vec<&str> = cidr.split('/').collect();
-        if parts.len() != 2 {
-            return Err(format!("无效的CIDR格式: {}", cidr));
-        }
-
-        let network_ip: IpAddr = parts[0]
-            .parse()
-            .map_err(|_| format!("CIDR中的无效IP地址: {}", parts[0]))?;
-        let prefix_len: u8 = parts[1]
-            .parse()
-            .map_err(|_| format!("CIDR中的无效前缀长度: {}", parts[1]))?;
-
-        match (ip, network_ip) {
-            (IpAddr::V4(ip4), IpAddr::V4(net4)) => {
-                if prefix_len > 32 {
-                    return Err("IPv4前缀长度不能超过32".to_string());
-                }
-                let mask = if prefix_len == 0 {
-                    0
-                } else {
-                    !0u32 << (32 - prefix_len)
-                };
-                Ok((u32::from(*ip4) & mask) == (u32::from(net4) & mask))
-            }
-            (IpAddr::V6(ip6), IpAddr::V6(net6)) => {
-                if prefix_len > 128 {
-                    return Err("IPv6前缀长度不能超过128".to_string());
-                }
-                let ip_bytes = ip6.octets();
-                let net_bytes = net6.octets();
-                let full_bytes = prefix_len / 8;
-                let remaining_bits = prefix_len % 8;
-
-                // 比较完整字节
-                for i in 0..full_bytes as usize {
-                    if ip_bytes[i] != net_bytes[i] {
-                        return Ok(false);
-                    }
-                }
-
-                // 比较剩余位
-                if remaining_bits > 0 {
-                    let mask = 0xFF << (8 - remaining_bits);
-                    let idx = full_bytes as usize;
-                    if idx < 16 && (ip_bytes[idx] & mask) != (net_bytes[idx] & mask) {
-                        return Ok(false);
-                    }
-                }
-
-                Ok(true)
-            }
-            _ => Err("IP地址类型不匹配".to_string()),
+    /// 获取编译后的白名单规则，节点配置不变，构建成功后不再重复构建
+    fn rules(&self, config: Option<&ChainwareConfig>) -> Result<&ip_utils::IpFilterRules, String> {
+        if let Some(rules) = self.rules.get() {
+            return Ok(rules);
         }
+        let rules = ip_utils::IpFilterRules::from_config(config)?;
+        Ok(self.rules.get_or_init(|| rules))
     }
 }
 
@@ -118,6 +58,18 @@ impl Chainware for IpWhitelistChainware {
         &self.name
     }
 
+    fn description(&self) -> &str {
+        "只允许白名单中的客户端IP通过"
+    }
+
+    fn config_schema(&self) -> Option<Value> {
+        Some(ip_utils::IpFilterRules::config_schema("白名单列表"))
+    }
+
+    fn examples(&self) -> Vec<Value> {
+        vec![json!({"ip_list": "127.0.0.1,::1,192.168.0.0/16,10.0.0.0/8"})]
+    }
+
     fn process(
         &self,
         request: &ChainRequest,
@@ -127,9 +79,9 @@ impl Chainware for IpWhitelistChainware {
     ) -> Option<serde_json::Value> {
         let input = data.unwrap_or_default();
 
-        // 从配置中获取IP白名单
-        let ip_list = match ip_utils::extract_ip_list(config.and_then(|cfg| cfg.config.get("ip_list"))) {
-            Ok(list) => list,
+        // 获取IP白名单及可信代理配置
+        let rules = match self.rules(config) {
+            Ok(rules) => rules,
             Err(err) => {
                 response.status = ChainStatus::Error;
                 response.data = Some(
@@ -179,25 +131,9 @@ impl Chainware for IpWhitelistChainware {
             }
         };
 
-        // 检查IP是否在白名单中
-        match self.is_ip_in_whitelist(ip_address, &ip_list) {
-            Ok(true) => {
-                // IP在白名单中，继续执行
-                Some(input)
-            }
-            Ok(false) => {
-                // IP不在白名单中，拒绝执行
-                response.status = ChainStatus::Reject;
-                response.data = Some(
-                    ErrorResponse::new(
-                        error_codes::FORBIDDEN,
-                        format!("IP地址 {} 不在白名单中", ip_address),
-                        None,
-                    )
-                    .to_json(),
-                );
-                None
-            }
+        // 解析真实客户端IP，并写入响应meta供后续节点使用
+        let client_ip = match ip_utils::resolve_client_ip(ip_address, &request.meta, &rules.client_ip) {
+            Ok(ip) => ip,
             Err(err) => {
                 response.status = ChainStatus::Error;
                 response.data = Some(
@@ -208,8 +144,28 @@ impl Chainware for IpWhitelistChainware {
                     )
                     .to_json(),
                 );
-                None
+                return None;
             }
+        };
+        let ip_address = client_ip.to_string();
+        response.set_meta(rules.client_ip.client_ip_key.clone(), Value::String(ip_address.clone()));
+
+        // 检查IP是否在白名单中
+        if rules.ip_set.contains(&client_ip) {
+            // IP在白名单中，继续执行
+            Some(input)
+        } else {
+            // IP不在白名单中，拒绝执行
+            response.status = ChainStatus::Reject;
+            response.data = Some(
+                ErrorResponse::new(
+                    error_codes::FORBIDDEN,
+                    format!("IP地址 {} 不在白名单中", ip_address),
+                    None,
+                )
+                .to_json(),
+            );
+            None
         }
     }
 }
@@ -6,8 +6,8 @@ use crate::chainware::config::ChainwareConfig;
 use crate::chainware::core::Chainware;
 use crate::core::{ChainRequest, ChainResponse};
 use crate::types::{error_codes, ErrorResponse};
-use crate::utils::json_path::JsonPathTemplate;
-use serde_json::Value;
+use crate::utils::json_path::{JsonPathTemplate, PathContext};
+use serde_json::{json, Value};
 
 /// 数据提取挂件
 pub struct JsonExtractChainware {
@@ -32,7 +32,7 @@ impl JsonExtractChainware {
         &self,
         input: &Value,
         pattern: Option<&Value>,
-        context: &Value,
+        context: &PathContext<'_>,
     ) -> Result<Option<Value>, String> {
         // 如果pattern为空，返回原data
         let pattern_str = match pattern {
@@ -41,7 +41,7 @@ impl JsonExtractChainware {
         };
 
         // 使用统一的JsonPathTemplate工具获取值
-        match JsonPathTemplate::get_value(context, pattern_str) {
+        match JsonPathTemplate::get_path_value(context, pattern_str) {
             Ok(Some(value)) => Ok(Some(value)),
             Ok(None) => Ok(None),
             Err(e) => Err(e),
@@ -54,6 +54,27 @@ impl Chainware for JsonExtractChainware {
         &self.name
     }
 
+    fn description(&self) -> &str {
+        "使用JSONPath从数据中提取值"
+    }
+
+    fn config_schema(&self) -> Option<Value> {
+        Some(json!({
+            "type": "object",
+            "properties": {
+                "pattern": {
+                    "type": "string",
+                    "description": "JSONPath路径表达式，未配置时返回原数据"
+                }
+            },
+            "additionalProperties": false
+        }))
+    }
+
+    fn examples(&self) -> Vec<Value> {
+        vec![json!({"pattern": "$.orders[?(@.status == 'completed')].amount"})]
+    }
+
     fn process(
         &self,
         request: &ChainRequest,
@@ -64,7 +85,7 @@ impl Chainware for JsonExtractChainware {
         let input = data.unwrap_or_default();
 
         // 构建完整的上下文对象
-        let context = JsonPathTemplate::build_context(&input, request);
+        let context = JsonPathTemplate::build_path_context(&input, request, response);
 
         // 获取提取参数
         let pattern = config.and_then(|cfg| cfg.config.get("pattern"));
@@ -6,8 +6,8 @@ use crate::chainware::config::ChainwareConfig;
 use crate::chainware::core::Chainware;
 use crate::core::{ChainRequest, ChainResponse};
 use crate::types::{error_codes, ErrorResponse};
-use crate::utils::json_path::JsonPathTemplate;
-use serde_json::Value;
+use crate::utils::json_path::{JsonPathTemplate, PathContext};
+use serde_json::{json, Value};
 
 /// 日志记录挂件
 pub struct LoggerChainware {
@@ -28,8 +28,8 @@ impl LoggerChainware {
     }
 
     /// 记录日志信息
-    fn log_info(&self, context: &Value, template: &str) -> Result<(), String> {
-        let message = JsonPathTemplate::get_value(context, template)?;
+    fn log_info(&self, context: &PathContext<'_>, template: &str) -> Result<(), String> {
+        let message = JsonPathTemplate::get_path_value(context, template)?;
         if message.is_none() {
             return Ok(());
         }
@@ -43,6 +43,28 @@ impl Chainware for LoggerChainware {
         &self.name
     }
 
+    fn description(&self) -> &str {
+        "按模板输出日志，数据原样透传"
+    }
+
+    fn config_schema(&self) -> Option<Value> {
+        Some(json!({
+            "type": "object",
+            "properties": {
+                "template": {
+                    "type": "string",
+                    "default": "打印日志: ${ data }",
+                    "description": "日志模板，支持${...}变量替换"
+                }
+            },
+            "additionalProperties": false
+        }))
+    }
+
+    fn examples(&self) -> Vec<Value> {
+        vec![json!({"template": "用户 ${$.username} 登录，IP=${$meta.ip_address}"})]
+    }
+
     fn process(
         &self,
         request: &ChainRequest,
@@ -51,7 +73,7 @@ impl Chainware for LoggerChainware {
         config: Option<&ChainwareConfig>,
     ) -> Option<serde_json::Value> {
         let input = data.unwrap_or_default();
-        let context = JsonPathTemplate::build_context(&input, request);
+        let context = JsonPathTemplate::build_path_context(&input, request, response);
 
         // 获取日志模板
         let template = match config.and_then(|cfg| cfg.config.get("template")) {
@@ -0,0 +1,285 @@
+//! 循环挂件
+//!
+//! 重复执行子链直到条件满足，用于LLM输出修复、重试等场景
+
+use crate::chain::executor::ChainExecutor;
+use crate::chainware::config::ChainwareConfig;
+use crate::chainware::core::Chainware;
+use crate::core::{ChainStatus, ChainRequest, ChainResponse};
+use crate::types::{error_codes, ErrorResponse};
+use crate::utils::condition;
+use crate::utils::json_path::JsonPathTemplate;
+use serde_json::{json, Value};
+use std::sync::OnceLock;
+
+/// 默认最大迭代次数
+const DEFAULT_MAX_ITERATIONS: u64 = 10;
+/// 默认迭代序号变量名
+const DEFAULT_INDEX_VAR: &str = "loop_index";
+/// 默认累积结果变量名
+const DEFAULT_ACCUMULATOR_VAR: &str = "loop_results";
+
+/// 编译后的循环规则
+struct LoopRules {
+    body: ChainExecutor,
+    while_expr: Option<String>,
+    until_expr: Option<String>,
+    max_iterations: u64,
+    index_var: String,
+    /// 每次迭代后从输出中取值的表达式
+    accumulate: Option<String>,
+    accumulator_var: String,
+}
+
+impl LoopRules {
+    fn from_config(config: Option<&ChainwareConfig>) -> Result<Self, String> {
+        let config = config.ok_or_else(|| "loop挂件需要chain配置".to_string())?;
+
+        let body = match config.get_param("chain") {
+            Some(chain) => ChainExecutor::new()
+                .add_chainwares(chain.clone())
+                .map_err(|e| format!("循环子链配置错误: {}", e.msg))?,
+            None => return Err("loop挂件需要chain配置".to_string()),
+        };
+
+        let string_param = |key: &str| match config.get_param(key) {
+            Some(Value::String(value)) => Ok(Some(value.clone())),
+            Some(_) => Err(format!("{}配置必须是字符串类型", key)),
+            None => Ok(None),
+        };
+
+        let max_iterations = match config.get_param("max_iterations") {
+            None => DEFAULT_MAX_ITERATIONS,
+            Some(value) => value
+                .as_u64()
+                .filter(|n| *n > 0)
+                .ok_or_else(|| "max_iterations配置必须是正整数".to_string())?,
+        };
+
+        Ok(Self {
+            body,
+            while_expr: string_param("while")?,
+            until_expr: string_param("until")?,
+            max_iterations,
+            index_var: string_param("index_var")?.unwrap_or_else(|| DEFAULT_INDEX_VAR.to_string()),
+            accumulate: string_param("accumulate")?,
+            accumulator_var: string_param("accumulator_var")?
+                .unwrap_or_else(|| DEFAULT_ACCUMULATOR_VAR.to_string()),
+        })
+    }
+
+    /// 基于当前数据求值循环条件
+    fn check(
+        name: &str,
+        expression: &str,
+        data: &Value,
+        request: &ChainRequest,
+        response: &ChainResponse,
+    ) -> Result<bool, String> {
+        let context = JsonPathTemplate::build_path_context(data, request, response);
+        condition::evaluate(expression, &context)
+            .map_err(|e| format!("{}条件 '{}' 求值失败: {}", name, expression, e))
+    }
+
+    /// 达到最大迭代次数时的错误信息，未配置条件时按固定次数执行，不视为错误
+    fn limit_message(&self) -> Option<String> {
+        let condition = match (&self.while_expr, &self.until_expr) {
+            (_, Some(until)) => format!("until条件 '{}' 仍未满足", until),
+            (Some(while_expr), None) => format!("while条件 '{}' 仍然满足", while_expr),
+            (None, None) => return None,
+        };
+        Some(format!("循环达到最大迭代次数{}，{}", self.max_iterations, condition))
+    }
+}
+
+/// 循环挂件
+///
+/// 配置格式：
+/// {
+///   "chain": [{"name": "...", "config": {...}}],   // 循环体子链，每次迭代以上一次的输出为输入
+///   "while": "$vars.loop_index < 3",              // 每次迭代前检查，不满足时结束循环
+///   "until": "Chain.isObject($.answer)",          // 每次迭代后检查，满足时结束循环
+///   "max_iterations": 10,                         // 最大迭代次数，默认10
+///   "index_var": "loop_index",                    // 迭代序号（从0开始）写入的链变量
+///   "accumulate": "$.error",                      // 每次迭代后从输出中取值，追加到累积结果
+///   "accumulator_var": "loop_results"             // 累积结果（数组）写入的链变量
+/// }
+///
+/// 达到最大迭代次数时条件仍未满足则返回Error；未配置while和until时按最大迭代次数执行
+pub struct LoopChainware {
+    name: String,
+    /// 编译后的循环规则，首次执行时根据节点配置构建
+    rules: OnceLock<Result<LoopRules, String>>,
+}
+
+impl Default for LoopChainware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LoopChainware {
+    pub fn new() -> Self {
+        Self {
+            name: "loop".to_string(),
+            rules: OnceLock::new(),
+        }
+    }
+
+    /// 获取编译后的循环规则，节点配置不变，只在首次执行时构建
+    fn rules(&self, config: Option<&ChainwareConfig>) -> &Result<LoopRules, String> {
+        self.rules.get_or_init(|| LoopRules::from_config(config))
+    }
+
+    /// 执行循环，返回最后一次迭代的输出；子链未正常执行完时返回None，状态和数据已写入响应
+    fn run(
+        rules: &LoopRules,
+        request: &ChainRequest,
+        response: &mut ChainResponse,
+        input: Value,
+    ) -> Result<Option<Value>, ErrorResponse> {
+        let condition_error = |msg| ErrorResponse::new(error_codes::INTERNAL_ERROR, msg, None);
+
+        let mut data = input;
+        let mut accumulated = Vec::new();
+        if rules.accumulate.is_some() {
+            response.set_var(rules.accumulator_var.clone(), json!([]));
+        }
+
+        for index in 0..=rules.max_iterations {
+            response.set_var(rules.index_var.clone(), json!(index));
+
+            if let Some(expression) = &rules.while_expr {
+                if !LoopRules::check("while", expression, &data, request, response).map_err(condition_error)? {
+                    return Ok(Some(data));
+                }
+            }
+
+            if index == rules.max_iterations {
+                break;
+            }
+
+            let sub_response = rules.body.run_nested(request, response, data);
+            if sub_response.status != ChainStatus::Continue {
+                response.status = sub_response.status;
+                response.data = sub_response.data;
+                return Ok(None);
+            }
+            data = sub_response.data.unwrap_or_default();
+
+            if let Some(template) = &rules.accumulate {
+                let context = JsonPathTemplate::build_path_context(&data, request, response);
+                let value = JsonPathTemplate::get_path_value(&context, template)
+                    .map_err(|e| condition_error(format!("accumulate表达式 '{}' 求值失败: {}", template, e)))?
+                    .unwrap_or(Value::Null);
+                accumulated.push(value);
+                response.set_var(rules.accumulator_var.clone(), Value::Array(accumulated.clone()));
+            }
+
+            if let Some(expression) = &rules.until_expr {
+                if LoopRules::check("until", expression, &data, request, response).map_err(condition_error)? {
+                    return Ok(Some(data));
+                }
+            }
+        }
+
+        match rules.limit_message() {
+            Some(msg) => Err(ErrorResponse::new(
+                error_codes::ITERATION_LIMIT,
+                msg,
+                Some(json!({"iterations": rules.max_iterations, "data": data})),
+            )),
+            None => Ok(Some(data)),
+        }
+    }
+}
+
+impl Chainware for LoopChainware {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        "重复执行子链直到条件满足，超过最大迭代次数时返回错误"
+    }
+
+    fn config_schema(&self) -> Option<Value> {
+        Some(json!({
+            "type": "object",
+            "properties": {
+                "chain": {
+                    "type": "array",
+                    "description": "循环体子链，每次迭代以上一次的输出为输入"
+                },
+                "while": {
+                    "type": "string",
+                    "description": "每次迭代前检查的条件表达式，不满足时结束循环"
+                },
+                "until": {
+                    "type": "string",
+                    "description": "每次迭代后检查的条件表达式，满足时结束循环"
+                },
+                "max_iterations": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "default": DEFAULT_MAX_ITERATIONS,
+                    "description": "最大迭代次数，达到后条件仍未满足则返回错误"
+                },
+                "index_var": {
+                    "type": "string",
+                    "default": DEFAULT_INDEX_VAR,
+                    "description": "迭代序号（从0开始）写入的链变量名"
+                },
+                "accumulate": {
+                    "type": "string",
+                    "description": "每次迭代后从输出中取值的路径或模板，结果追加到累积数组"
+                },
+                "accumulator_var": {
+                    "type": "string",
+                    "default": DEFAULT_ACCUMULATOR_VAR,
+                    "description": "累积数组写入的链变量名"
+                }
+            },
+            "required": ["chain"],
+            "additionalProperties": false
+        }))
+    }
+
+    fn examples(&self) -> Vec<Value> {
+        vec![json!({
+            "chain": [
+                {"name": "extract_json", "config": {"input_path": "$.llm_output", "output_path": "$.answer"}}
+            ],
+            "until": "Chain.isObject($.answer)",
+            "max_iterations": 3
+        })]
+    }
+
+    fn process(
+        &self,
+        request: &ChainRequest,
+        response: &mut ChainResponse,
+        data: Option<serde_json::Value>,
+        config: Option<&ChainwareConfig>,
+    ) -> Option<serde_json::Value> {
+        let input = data.unwrap_or_default();
+
+        let rules = match self.rules(config) {
+            Ok(rules) => rules,
+            Err(err) => {
+                response.status = ChainStatus::Error;
+                response.data = Some(ErrorResponse::new(error_codes::CONFIG_ERROR, err.clone(), None).to_json());
+                return None;
+            }
+        };
+
+        match Self::run(rules, request, response, input) {
+            Ok(output) => output,
+            Err(err) => {
+                response.status = ChainStatus::Error;
+                response.data = Some(err.to_json());
+                None
+            }
+        }
+    }
+}
@@ -6,8 +6,8 @@ use crate::chainware::core::Chainware;
 use crate::chainware::config::ChainwareConfig;
 use crate::core::{ChainStatus, ChainRequest, ChainResponse};
 use crate::types::{error_codes, ErrorResponse};
-use crate::utils::json_path::JsonPathTemplate;
-use serde_json::Value;
+use crate::utils::json_path::{JsonPathTemplate, PathContext};
+use serde_json::{json, Value};
 
 /// 字段映射挂件
 pub struct MapFieldsChainware {
@@ -32,7 +32,7 @@ impl MapFieldsChainware {
     /// 业务逻辑：
     /// - overwrite=true: 在原对象基础上添加映射字段，容错性强
     /// - overwrite=false: 创建新对象只包含映射字段，严格模式
-    fn process_map_fields(&self, input: &Value, mappings: &Value, context: &Value, overwrite: bool) -> Result<Value, String> {
+    fn process_map_fields(&self, input: &Value, mappings: &Value, context: &PathContext<'_>, overwrite: bool) -> Result<Value, String> {
         // 第一步：验证映射配置
         let mapping_obj = match mappings.as_object() {
             Some(obj) => obj,
@@ -58,7 +58,7 @@ impl MapFieldsChainware {
 
     /// overwrite=true模式：在原对象/数组基础上添加映射字段
     /// 适用场景：数据增强、字段别名、保持原数据完整性
-    fn process_overwrite_mode(&self, input: &Value, mapping_obj: &serde_json::Map<String, Value>, context: &Value) -> Result<Value, String> {
+    fn process_overwrite_mode(&self, input: &Value, mapping_obj: &serde_json::Map<String, Value>, context: &PathContext<'_>) -> Result<Value, String> {
         match input {
             // 处理对象：复制原对象，添加映射字段
             Value::Object(input_obj) => {
@@ -67,7 +67,7 @@ impl MapFieldsChainware {
                 // 应用字段映射
                 for (new_field, source_path) in mapping_obj {
                     if let Some(path_str) = source_path.as_str() {
-                        match JsonPathTemplate::get_value(context, path_str) {
+                        match JsonPathTemplate::get_path_value(context, path_str) {
                             Ok(Some(value)) => {
                                 // 添加或覆盖字段
                                 result.insert(new_field.to_string(), value);
@@ -99,13 +99,9 @@ impl MapFieldsChainware {
                         for (new_field, source_path) in mapping_obj {
                             if let Some(path_str) = source_path.as_str() {
                                 // 为数组元素构建独立的context，将当前元素作为$.input
-                                let item_context = serde_json::json!({
-                                    "__input": item,
-                                    "__params": context["__params"],
-                                    "__meta": context["__meta"]
-                                });
-                                
-                                match JsonPathTemplate::get_value(&item_context, path_str) {
+                                let item_context = PathContext { input: item, ..*context };
+
+                                match JsonPathTemplate::get_path_value(&item_context, path_str) {
                                     Ok(Some(value)) => {
                                         result_item.insert(new_field.to_string(), value);
                                     }
@@ -133,14 +129,14 @@ impl MapFieldsChainware {
 
     /// overwrite=false模式：创建新对象，只包含映射的字段
     /// 适用场景：数据提取、格式转换、清理无关数据
-    fn process_strict_mode(&self, _input: &Value, mapping_obj: &serde_json::Map<String, Value>, context: &Value) -> Result<Value, String> {
+    fn process_strict_mode(&self, _input: &Value, mapping_obj: &serde_json::Map<String, Value>, context: &PathContext<'_>) -> Result<Value, String> {
         // 建立新对象，根据map设置字段，返回新对象
         let mut result = serde_json::Map::new();
 
         // 只处理映射的字段
         for (new_field, source_path) in mapping_obj {
             if let Some(path_str) = source_path.as_str() {
-                match JsonPathTemplate::get_value(context, path_str) {
+                match JsonPathTemplate::get_path_value(context, path_str) {
                     Ok(Some(value)) => {
                         // 只添加成功映射的字段
                         result.insert(new_field.to_string(), value);
@@ -166,6 +162,36 @@ impl Chainware for MapFieldsChainware {
         &self.name
     }
 
+    fn description(&self) -> &str {
+        "字段重命名和转换，数组输入时对每个元素进行映射"
+    }
+
+    fn config_schema(&self) -> Option<Value> {
+        Some(json!({
+            "type": "object",
+            "properties": {
+                "mapping": {
+                    "type": "object",
+                    "additionalProperties": {"type": "string"},
+                    "description": "字段映射，key为新字段名，value为源路径或模板"
+                },
+                "overwrite": {
+                    "type": "boolean",
+                    "default": true,
+                    "description": "true时保留原字段并覆盖映射字段，false时只输出映射字段"
+                }
+            },
+            "additionalProperties": false
+        }))
+    }
+
+    fn examples(&self) -> Vec<Value> {
+        vec![json!({
+            "mapping": {"username": "$.name", "display": "${$.name}（${$.age}岁）"},
+            "overwrite": false
+        })]
+    }
+
     fn process(
         &self,
         request: &ChainRequest,
@@ -197,7 +223,7 @@ impl Chainware for MapFieldsChainware {
         };
 
         // 构建完整的上下文对象
-        let context = JsonPathTemplate::build_context(&input, request);
+        let context = JsonPathTemplate::build_path_context(&input, request, response);
 
         match self.process_map_fields(&input, mappings, &context, overwrite) {
             Ok(result) => Some(result),
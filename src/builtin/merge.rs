@@ -6,8 +6,105 @@ use crate::chainware::config::ChainwareConfig;
 use crate::chainware::core::Chainware;
 use crate::core::{ChainStatus, ChainRequest, ChainResponse};
 use crate::types::{error_codes, ErrorResponse};
-use crate::utils::json_path::JsonPathTemplate;
-use serde_json::Value;
+use crate::utils::json_path::{JsonPathTemplate, PathContext};
+use serde_json::{json, Value};
+
+/// 合并策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// 浅合并：只合并第一层字段
+    Shallow,
+    /// 深合并：递归合并嵌套对象
+    Deep,
+    /// RFC 7396 JSON Merge Patch：null表示删除字段
+    MergePatch,
+}
+
+/// 数组合并方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrayMergeMode {
+    /// 追加合并数据中的所有元素
+    Concat,
+    /// 追加合并数据中尚不存在的元素
+    Union,
+    /// 按优先方整体替换
+    Replace,
+}
+
+/// 合并选项
+#[derive(Debug, Clone)]
+pub struct MergeOptions {
+    /// 合并策略
+    pub strategy: MergeStrategy,
+    /// 数组合并方式
+    pub array_mode: ArrayMergeMode,
+    /// 数组按该字段去重合并（元素为对象时生效）
+    pub array_key: Option<String>,
+    /// 字段冲突时是否以输入数据为准（默认以合并数据为准）
+    pub input_wins: bool,
+    /// 类型冲突时是否报错（默认忽略）
+    pub error_on_conflict: bool,
+}
+
+impl Default for MergeOptions {
+    fn default() -> Self {
+        Self {
+            strategy: MergeStrategy::Shallow,
+            array_mode: ArrayMergeMode::Concat,
+            array_key: None,
+            input_wins: false,
+            error_on_conflict: false,
+        }
+    }
+}
+
+impl MergeOptions {
+    /// 从挂件配置中解析合并选项
+    fn from_config(config: &ChainwareConfig) -> Result<Self, String> {
+        let mut options = Self::default();
+
+        if let Some(strategy) = config.get_param("strategy") {
+            options.strategy = match strategy.as_str() {
+                Some("shallow") => MergeStrategy::Shallow,
+                Some("deep") => MergeStrategy::Deep,
+                Some("merge_patch") => MergeStrategy::MergePatch,
+                _ => return Err(format!("不支持的strategy配置: {}", strategy)),
+            };
+        }
+
+        if let Some(mode) = config.get_param("array_mode") {
+            options.array_mode = match mode.as_str() {
+                Some("concat") => ArrayMergeMode::Concat,
+                Some("union") => ArrayMergeMode::Union,
+                Some("replace") => ArrayMergeMode::Replace,
+                _ => return Err(format!("不支持的array_mode配置: {}", mode)),
+            };
+        }
+
+        options.array_key = match config.get_param("array_key") {
+            Some(Value::String(key)) => Some(key.clone()),
+            Some(_) => return Err("array_key配置必须是字符串类型".to_string()),
+            None => None,
+        };
+
+        if let Some(prefer) = config.get_param("prefer") {
+            options.input_wins = match prefer.as_str() {
+                Some("input") => true,
+                Some("merge") => false,
+                _ => return Err(format!("不支持的prefer配置: {}", prefer)),
+            };
+        }
+
+        options.error_on_conflict = match config.get_param("on_conflict") {
+            Some(Value::String(mode)) if mode == "error" => true,
+            Some(Value::String(mode)) if mode == "ignore" => false,
+            Some(other) => return Err(format!("不支持的on_conflict配置: {}", other)),
+            None => false,
+        };
+
+        Ok(options)
+    }
+}
 
 /// 数据合并挂件
 pub struct MergeChainware {
@@ -32,15 +129,16 @@ impl MergeChainware {
     /// 业务逻辑：
     /// - 对象 + 对象 = 合并对象
     /// - 数组 + 数组 = 合并数组
-    /// - 其他情况 = 返回原数据
+    /// - 其他情况 = 返回原数据（on_conflict=error时报错）
     fn process_merge(
         &self,
         input: &Value,
         data_path: &str,
-        context: &Value,
+        context: &PathContext<'_>,
+        options: &MergeOptions,
     ) -> Result<Value, String> {
         // 使用JsonPathTemplate从context获取合并数据
-        let merge_data = match JsonPathTemplate::get_value(context, data_path) {
+        let merge_data = match JsonPathTemplate::get_path_value(context, data_path) {
             Ok(Some(data)) => data,
             Ok(None) => return Ok(input.clone()),
             Err(err) => {
@@ -48,33 +146,169 @@ impl MergeChainware {
             }
         };
 
+        if options.strategy == MergeStrategy::MergePatch {
+            return Ok(Self::merge_patch(input, &merge_data));
+        }
+
         match (input, &merge_data) {
-            // 对象合并对象
+            (Value::Object(_), Value::Object(_)) | (Value::Array(_), Value::Array(_)) => {
+                self.merge_values(input, &merge_data, options, "$")
+            }
+            // 其他情况：类型不匹配
+            _ if options.error_on_conflict => Err(format!(
+                "类型冲突: 输入数据为{}，路径 '{}' 的数据为{}",
+                Self::type_name(input),
+                data_path,
+                Self::type_name(&merge_data)
+            )),
+            _ => Ok(input.clone()),
+        }
+    }
+
+    /// 按策略合并两个值
+    fn merge_values(
+        &self,
+        input: &Value,
+        merge: &Value,
+        options: &MergeOptions,
+        path: &str,
+    ) -> Result<Value, String> {
+        match (input, merge) {
             (Value::Object(input_obj), Value::Object(merge_obj)) => {
                 let mut result = input_obj.clone();
 
-                // 将merge_obj的所有字段合并到result中
                 for (key, value) in merge_obj {
-                    result.insert(key.clone(), value.clone());
+                    let merged = match input_obj.get(key) {
+                        None => value.clone(),
+                        Some(existing) if options.strategy == MergeStrategy::Deep
+                            && ((existing.is_object() && value.is_object())
+                                || (existing.is_array() && value.is_array())) =>
+                        {
+                            self.merge_values(existing, value, options, &format!("{}.{}", path, key))?
+                        }
+                        Some(existing) => {
+                            self.resolve_conflict(existing, value, options, &format!("{}.{}", path, key))?
+                        }
+                    };
+                    result.insert(key.clone(), merged);
                 }
 
                 Ok(Value::Object(result))
             }
-            // 数组合并数组
             (Value::Array(input_array), Value::Array(merge_array)) => {
-                let mut result = input_array.clone();
+                Ok(Value::Array(self.merge_arrays(input_array, merge_array, options)?))
+            }
+            _ => self.resolve_conflict(input, merge, options, path),
+        }
+    }
 
-                // 将merge_array的所有元素追加到result中
-                for item in merge_array {
-                    result.push(item.clone());
+    /// 合并数组
+    fn merge_arrays(
+        &self,
+        input: &[Value],
+        merge: &[Value],
+        options: &MergeOptions,
+    ) -> Result<Vec<Value>, String> {
+        // 按key去重合并：key相同的对象元素进行合并，其余追加
+        if let Some(key) = &options.array_key {
+            let mut result = input.to_vec();
+            for item in merge {
+                let position = item.get(key).and_then(|id| {
+                    result.iter().position(|existing| existing.get(key) == Some(id))
+                });
+                match position {
+                    Some(index) => {
+                        let merged = self.merge_values(&result[index], item, options, &format!("$[{}]", index))?;
+                        result[index] = merged;
+                    }
+                    None => result.push(item.clone()),
                 }
+            }
+            return Ok(result);
+        }
 
-                Ok(Value::Array(result))
+        Ok(match options.array_mode {
+            ArrayMergeMode::Concat => input.iter().chain(merge.iter()).cloned().collect(),
+            ArrayMergeMode::Union => {
+                let mut result = input.to_vec();
+                for item in merge {
+                    if !result.contains(item) {
+                        result.push(item.clone());
+                    }
+                }
+                result
             }
-            // 其他情况：类型不匹配，返回原数据
-            _ => Ok(input.clone()),
+            ArrayMergeMode::Replace if options.input_wins => input.to_vec(),
+            ArrayMergeMode::Replace => merge.to_vec(),
+        })
+    }
+
+    /// 处理同一字段上的冲突值
+    fn resolve_conflict(
+        &self,
+        input: &Value,
+        merge: &Value,
+        options: &MergeOptions,
+        path: &str,
+    ) -> Result<Value, String> {
+        if options.error_on_conflict
+            && !input.is_null()
+            && !merge.is_null()
+            && Self::type_name(input) != Self::type_name(merge)
+        {
+            return Err(format!(
+                "字段 '{}' 类型冲突: {} 与 {}",
+                path,
+                Self::type_name(input),
+                Self::type_name(merge)
+            ));
+        }
+
+        if options.input_wins {
+            Ok(input.clone())
+        } else {
+            Ok(merge.clone())
+        }
+    }
+
+    /// RFC 7396 JSON Merge Patch
+    fn merge_patch(target: &Value, patch: &Value) -> Value {
+        let Value::Object(patch_obj) = patch else {
+            return patch.clone();
+        };
+
+        let mut result = match target {
+            Value::Object(obj) => obj.clone(),
+            _ => serde_json::Map::new(),
+        };
+        for (key, value) in patch_obj {
+            if value.is_null() {
+                result.remove(key);
+            } else {
+                let merged = Self::merge_patch(result.get(key).unwrap_or(&Value::Null), value);
+                result.insert(key.clone(), merged);
+            }
+        }
+        Value::Object(result)
+    }
+
+    /// 获取值的类型名称
+    fn type_name(value: &Value) -> &'static str {
+        match value {
+            Value::Null => "null",
+            Value::Bool(_) => "boolean",
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Array(_) => "array",
+            Value::Object(_) => "object",
         }
     }
+
+    /// 生成配置错误响应
+    fn config_error(response: &mut ChainResponse, msg: String) {
+        response.status = ChainStatus::Error;
+        response.data = Some(ErrorResponse::new(error_codes::CONFIG_ERROR, msg, None).to_json());
+    }
 }
 
 impl Chainware for MergeChainware {
@@ -82,6 +316,64 @@ impl Chainware for MergeChainware {
         &self.name
     }
 
+    fn description(&self) -> &str {
+        "将其他路径的数据合并到当前数据中，支持浅合并、深合并和JSON Merge Patch"
+    }
+
+    fn config_schema(&self) -> Option<Value> {
+        Some(json!({
+            "type": "object",
+            "properties": {
+                "data_path": {
+                    "type": ["string", "array"],
+                    "items": {"type": "string"},
+                    "description": "要合并的数据路径，数组形式时按顺序依次合并"
+                },
+                "strategy": {
+                    "type": "string",
+                    "enum": ["shallow", "deep", "merge_patch"],
+                    "default": "shallow",
+                    "description": "合并策略"
+                },
+                "array_mode": {
+                    "type": "string",
+                    "enum": ["concat", "union", "replace"],
+                    "default": "concat",
+                    "description": "数组合并方式"
+                },
+                "array_key": {
+                    "type": "string",
+                    "description": "数组元素按该字段去重，key相同的对象元素进行合并"
+                },
+                "prefer": {
+                    "type": "string",
+                    "enum": ["merge", "input"],
+                    "default": "merge",
+                    "description": "字段冲突时的优先方"
+                },
+                "on_conflict": {
+                    "type": "string",
+                    "enum": ["ignore", "error"],
+                    "default": "ignore",
+                    "description": "类型冲突处理方式"
+                }
+            },
+            "additionalProperties": false
+        }))
+    }
+
+    fn examples(&self) -> Vec<Value> {
+        vec![
+            json!({"data_path": "$params.extra_info"}),
+            json!({
+                "data_path": ["$params.defaults", "$meta.overrides"],
+                "strategy": "deep",
+                "array_key": "id",
+                "on_conflict": "error"
+            }),
+        ]
+    }
+
     fn process(
         &self,
         request: &ChainRequest,
@@ -91,19 +383,19 @@ impl Chainware for MergeChainware {
     ) -> Option<serde_json::Value> {
         let input = data.unwrap_or_default();
 
-        // 获取data_path配置
-        let data_path = match config.and_then(|cfg| cfg.config.get("data_path")) {
-            Some(Value::String(path)) => path,
+        let Some(cfg) = config else {
+            // 如果没有配置，返回原数据
+            return Some(input);
+        };
+
+        // 获取data_path配置，支持单个路径或路径数组
+        let data_paths: Vec<&str> = match cfg.config.get("data_path") {
+            Some(Value::String(path)) => vec![path.as_str()],
+            Some(Value::Array(paths)) if paths.iter().all(|p| p.is_string()) => {
+                paths.iter().filter_map(|p| p.as_str()).collect()
+            }
             Some(_) => {
-                response.status = ChainStatus::Error;
-                response.data = Some(
-                    ErrorResponse::new(
-                        error_codes::CONFIG_ERROR,
-                        "data_path配置必须是字符串类型".to_string(),
-                        None,
-                    )
-                    .to_json(),
-                );
+                Self::config_error(response, "data_path配置必须是字符串或字符串数组类型".to_string());
                 return Some(input);
             }
             None => {
@@ -113,36 +405,47 @@ impl Chainware for MergeChainware {
         };
 
         // 检查是否存在自引用
-        if data_path.starts_with("$input") || data_path.starts_with("$data") || (data_path == "$") {
-            response.status = ChainStatus::Error;
-            response.data = Some(
-                ErrorResponse::new(
-                    error_codes::CONFIG_ERROR,
+        for data_path in &data_paths {
+            if data_path.starts_with("$input") || data_path.starts_with("$data") || (*data_path == "$") {
+                Self::config_error(
+                    response,
                     format!("data_path不能自引用输入数据，禁止使用路径: {}", data_path),
-                    None,
-                )
-                .to_json(),
-            );
-            return Some(input);
+                );
+                return Some(input);
+            }
         }
 
+        let options = match MergeOptions::from_config(cfg) {
+            Ok(options) => options,
+            Err(err) => {
+                Self::config_error(response, err);
+                return Some(input);
+            }
+        };
+
         // 构建完整的上下文对象
-        let context = JsonPathTemplate::build_context(&input, request);
+        let context = JsonPathTemplate::build_path_context(&input, request, response);
 
-        match self.process_merge(&input, data_path, &context) {
-            Ok(result) => Some(result),
-            Err(err) => {
-                response.status = ChainStatus::Error;
-                response.data = Some(
-                    ErrorResponse::new(
-                        error_codes::INTERNAL_ERROR,
-                        format!("合并操作失败: {}", err),
-                        None,
-                    )
-                    .to_json(),
-                );
-                Some(input) // 返回原数据
+        // 按顺序依次合并每个数据源
+        let mut result = input.clone();
+        for data_path in data_paths {
+            match self.process_merge(&result, data_path, &context, &options) {
+                Ok(merged) => result = merged,
+                Err(err) => {
+                    response.status = ChainStatus::Error;
+                    response.data = Some(
+                        ErrorResponse::new(
+                            error_codes::INTERNAL_ERROR,
+                            format!("合并操作失败: {}", err),
+                            None,
+                        )
+                        .to_json(),
+                    );
+                    return Some(input); // 返回原数据
+                }
             }
         }
+
+        Some(result)
     }
 }
@@ -11,6 +11,15 @@ pub mod regexp_condition;
 pub mod registry;
 pub mod ip_blacklist;
 pub mod ip_whitelist;
+pub mod patch;
+pub mod convert;
+pub mod array_ops;
+pub mod extract_code;
+#[cfg(feature = "geo")]
+pub mod geo_filter;
+pub mod set_var;
+pub mod switch;
+pub mod loop_chain;
 
 pub use condition::*;
 pub use logger::*;
@@ -24,4 +33,13 @@ pub use regexp_extract::*;
 pub use regexp_condition::*;
 pub use registry::*;
 pub use ip_blacklist::*;
-pub use ip_whitelist::*;
\ No newline at end of file
+pub use ip_whitelist::*;
+pub use patch::*;
+pub use convert::*;
+pub use array_ops::*;
+pub use extract_code::*;
+#[cfg(feature = "geo")]
+pub use geo_filter::*;
+pub use set_var::*;
+pub use switch::*;
+pub use loop_chain::*;
@@ -0,0 +1,279 @@
+//! JSON Patch挂件
+//!
+//! 按RFC 6902 JSON Patch操作对数据进行有序的增删改
+
+use crate::chainware::config::ChainwareConfig;
+use crate::chainware::core::Chainware;
+use crate::core::{ChainStatus, ChainRequest, ChainResponse};
+use crate::types::{error_codes, ErrorResponse};
+use crate::utils::json_path::{JsonPathTemplate, PathContext};
+use crate::utils::json_pointer;
+use serde_json::{json, Number, Value};
+
+/// Patch操作失败原因
+enum PatchError {
+    /// test操作未通过
+    TestFailed(String),
+    /// 操作执行失败
+    Failed(String),
+}
+
+/// JSON Patch挂件
+///
+/// 配置格式：
+/// {
+///   "operations": [
+///     {"op": "add", "path": "/user/role", "value": "admin"},
+///     {"op": "remove", "path": "/password"},
+///     {"op": "replace", "path": "/name", "value": "$params.name"},
+///     {"op": "move", "from": "/old", "path": "/new"},
+///     {"op": "copy", "from": "/a", "path": "/b"},
+///     {"op": "test", "path": "/version", "value": 2},
+///     {"op": "add", "path": "/price", "value": {"literal": "$5.00"}}
+///   ],
+///   "create_path": true
+/// }
+///
+/// 字符串值以`$`开头或包含`${`时按模板求值，`{"literal": 值}`形式的值原样使用，不求值
+pub struct PatchChainware {
+    name: String,
+}
+
+impl Default for PatchChainware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PatchChainware {
+    pub fn new() -> Self {
+        Self {
+            name: "patch".to_string(),
+        }
+    }
+
+    /// 解析操作中的值，字符串值可以是JsonPathTemplate表达式，`{"literal": 值}`为不求值的字面量
+    fn resolve_value(&self, value: &Value, context: &PathContext<'_>) -> Result<Value, String> {
+        match value {
+            Value::Object(obj) if obj.len() == 1 && obj.contains_key("literal") => Ok(obj["literal"].clone()),
+            Value::String(template) if template.trim().starts_with('$') || template.contains("${") => {
+                Ok(JsonPathTemplate::get_path_value(context, template)?.unwrap_or(Value::Null))
+            }
+            _ => Ok(value.clone()),
+        }
+    }
+
+    /// 依次应用所有Patch操作
+    fn process_patch(
+        &self,
+        input: &Value,
+        operations: &[Value],
+        context: &PathContext<'_>,
+        create_path: bool,
+    ) -> Result<Value, PatchError> {
+        let mut result = input.clone();
+
+        for (index, operation) in operations.iter().enumerate() {
+            let fail = |msg: String| PatchError::Failed(format!("第{}个操作失败: {}", index + 1, msg));
+
+            let op = operation
+                .get("op")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| fail("缺少op字段".to_string()))?;
+            let path = operation
+                .get("path")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| fail("缺少path字段".to_string()))?;
+            let tokens = json_pointer::parse_pointer(path).map_err(fail)?;
+
+            let value = || -> Result<Value, PatchError> {
+                let raw = operation
+                    .get("value")
+                    .ok_or_else(|| fail(format!("{}操作缺少value字段", op)))?;
+                self.resolve_value(raw, context).map_err(fail)
+            };
+            let from = || -> Result<Vec<String>, PatchError> {
+                let from = operation
+                    .get("from")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| fail(format!("{}操作缺少from字段", op)))?;
+                json_pointer::parse_pointer(from).map_err(fail)
+            };
+
+            match op {
+                "add" => json_pointer::add(&mut result, &tokens, value()?, create_path).map_err(fail)?,
+                "remove" => {
+                    json_pointer::remove(&mut result, &tokens).map_err(fail)?;
+                }
+                "replace" => json_pointer::replace(&mut result, &tokens, value()?).map_err(fail)?,
+                "move" => {
+                    let from = from()?;
+                    if tokens.len() > from.len() && tokens[..from.len()] == from[..] {
+                        return Err(fail("不能将值移动到其自身的子路径".to_string()));
+                    }
+                    let moved = json_pointer::remove(&mut result, &from).map_err(fail)?;
+                    json_pointer::add(&mut result, &tokens, moved, create_path).map_err(fail)?;
+                }
+                "copy" => {
+                    let copied = json_pointer::get(&result, &from()?)
+                        .cloned()
+                        .ok_or_else(|| fail("from路径不存在".to_string()))?;
+                    json_pointer::add(&mut result, &tokens, copied, create_path).map_err(fail)?;
+                }
+                "test" => {
+                    let expected = value()?;
+                    let actual = json_pointer::get(&result, &tokens);
+                    if !actual.is_some_and(|actual| json_equal(actual, &expected)) {
+                        return Err(PatchError::TestFailed(format!(
+                            "路径 '{}' 的值 {} 与期望值 {} 不一致",
+                            path,
+                            actual.map(|v| v.to_string()).unwrap_or_else(|| "undefined".to_string()),
+                            expected
+                        )));
+                    }
+                }
+                other => return Err(fail(format!("不支持的操作类型: {}", other))),
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+/// 按RFC 6902比较两个JSON值：数字按数值比较（`1`与`1.0`相等），数组和对象逐元素比较
+fn json_equal(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => number_equal(a, b),
+        (Value::Array(a), Value::Array(b)) => a.len() == b.len() && a.iter().zip(b).all(|(a, b)| json_equal(a, b)),
+        (Value::Object(a), Value::Object(b)) => {
+            a.len() == b.len() && a.iter().all(|(key, a)| b.get(key).is_some_and(|b| json_equal(a, b)))
+        }
+        _ => a == b,
+    }
+}
+
+/// 整数之间精确比较，其余按浮点数值比较
+fn number_equal(a: &Number, b: &Number) -> bool {
+    if let (Some(a), Some(b)) = (a.as_i64(), b.as_i64()) {
+        return a == b;
+    }
+    if let (Some(a), Some(b)) = (a.as_u64(), b.as_u64()) {
+        return a == b;
+    }
+    a.as_f64() == b.as_f64()
+}
+
+impl Chainware for PatchChainware {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        "按RFC 6902 JSON Patch操作有序地修改数据"
+    }
+
+    fn config_schema(&self) -> Option<Value> {
+        Some(json!({
+            "type": "object",
+            "properties": {
+                "operations": {
+                    "type": "array",
+                    "description": "Patch操作列表，按顺序执行",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "op": {"type": "string", "enum": ["add", "remove", "replace", "move", "copy", "test"]},
+                            "path": {"type": "string", "description": "目标位置（JSON Pointer）"},
+                            "from": {"type": "string", "description": "move/copy的来源位置"},
+                            "value": {"description": "add/replace/test的值，字符串支持模板表达式，{\"literal\": 值}为不求值的字面量"}
+                        },
+                        "required": ["op", "path"]
+                    }
+                },
+                "create_path": {
+                    "type": "boolean",
+                    "default": true,
+                    "description": "add时是否自动创建不存在的中间对象"
+                }
+            },
+            "additionalProperties": false
+        }))
+    }
+
+    fn examples(&self) -> Vec<Value> {
+        vec![json!({
+            "operations": [
+                {"op": "test", "path": "/version", "value": 2},
+                {"op": "remove", "path": "/password"},
+                {"op": "move", "from": "/uname", "path": "/username"}
+            ]
+        })]
+    }
+
+    fn process(
+        &self,
+        request: &ChainRequest,
+        response: &mut ChainResponse,
+        data: Option<serde_json::Value>,
+        config: Option<&ChainwareConfig>,
+    ) -> Option<serde_json::Value> {
+        let input = data.unwrap_or_default();
+
+        // 获取操作列表
+        let operations = match config.and_then(|cfg| cfg.config.get("operations")) {
+            Some(Value::Array(ops)) => ops,
+            Some(_) => {
+                response.status = ChainStatus::Error;
+                response.data = Some(
+                    ErrorResponse::new(
+                        error_codes::CONFIG_ERROR,
+                        "operations配置必须是数组类型".to_string(),
+                        None,
+                    )
+                    .to_json(),
+                );
+                return None;
+            }
+            None => {
+                // 如果没有operations配置，返回原数据
+                return Some(input);
+            }
+        };
+
+        let create_path = config
+            .and_then(|cfg| cfg.config.get("create_path"))
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+
+        // 构建完整的上下文对象
+        let context = JsonPathTemplate::build_path_context(&input, request, response);
+
+        match self.process_patch(&input, operations, &context, create_path) {
+            Ok(result) => Some(result),
+            Err(PatchError::TestFailed(msg)) => {
+                response.status = ChainStatus::Reject;
+                response.data = Some(
+                    ErrorResponse::new(
+                        error_codes::VALIDATION_FAILED,
+                        format!("patch校验未通过: {}", msg),
+                        None,
+                    )
+                    .to_json(),
+                );
+                None
+            }
+            Err(PatchError::Failed(msg)) => {
+                response.status = ChainStatus::Error;
+                response.data = Some(
+                    ErrorResponse::new(
+                        error_codes::INTERNAL_ERROR,
+                        format!("patch操作失败: {}", msg),
+                        Some(input),
+                    )
+                    .to_json(),
+                );
+                None
+            }
+        }
+    }
+}
@@ -7,7 +7,7 @@ use crate::chainware::config::ChainwareConfig;
 use crate::core::{ChainStatus, ChainRequest, ChainResponse};
 use crate::types::{error_codes, ErrorResponse};
 use regex::Regex;
-use serde_json::Value;
+use serde_json::{json, Value};
 
 /// 正则条件挂件
 pub struct RegexpConditionChainware {
@@ -75,6 +75,27 @@ impl Chainware for RegexpConditionChainware {
         &self.name
     }
 
+    fn description(&self) -> &str {
+        "使用正则表达式判断文本，不匹配时拒绝请求"
+    }
+
+    fn config_schema(&self) -> Option<Value> {
+        Some(json!({
+            "type": "object",
+            "properties": {
+                "pattern": {
+                    "type": "string",
+                    "description": "正则表达式，未配置时直接通过"
+                }
+            },
+            "additionalProperties": false
+        }))
+    }
+
+    fn examples(&self) -> Vec<Value> {
+        vec![json!({"pattern": "^1[3-9]\\d{9}$"})]
+    }
+
     fn process(
         &self,
         _request: &ChainRequest,
@@ -1,15 +1,27 @@
 //! 正则提取挂件
 //!
-//! 通过正则表达式从文本中提取内容，仅支持单个匹配内容的返回值
+//! 通过正则表达式从文本中提取内容，支持所有匹配、命名分组和替换
 
 use crate::chainware::config::ChainwareConfig;
 use crate::chainware::core::Chainware;
 use crate::core::{ChainStatus, ChainRequest, ChainResponse};
 use crate::types::{error_codes, ErrorResponse};
-use regex::Regex;
-use serde_json::Value;
+use crate::utils::json_path::JsonPathTemplate;
+use regex::{Captures, Regex, RegexBuilder};
+use serde_json::{json, Value};
 
 /// 正则提取挂件
+///
+/// 配置格式：
+/// {
+///   "pattern": "(?P<key>\\w+)=(?P<value>\\w+)",
+///   "mode": "first",          // first（默认）、all 或 replace
+///   "group": 1,               // 只返回指定分组（序号或名称）
+///   "output": "object",       // 分组的返回形式：array（默认）或 object（以分组名为键）
+///   "replacement": "$1",      // replace模式的替换字符串，支持$1、${name}
+///   "flags": "im",            // i忽略大小写、m多行、s点号匹配换行、x忽略空白、U非贪婪
+///   "source": "$.content"     // 从指定字段提取，缺省时使用整个输入
+/// }
 pub struct RegexpExtractChainware {
     name: String,
 }
@@ -32,6 +44,7 @@ impl RegexpExtractChainware {
         &self,
         input: &Value,
         pattern: Option<&str>,
+        options: &RegexpExtractOptions,
     ) -> Result<Value, String> {
         // 如果pattern为空，返回原data
         let pattern = match pattern {
@@ -46,33 +59,137 @@ impl RegexpExtractChainware {
         };
 
         // 编译正则表达式
-        let regex = match Regex::new(pattern) {
-            Ok(r) => r,
-            Err(e) => return Err(format!("正则表达式编译失败: {}", e)),
+        let regex = Self::build_regex(pattern, &options.flags)?;
+
+        match options.mode.as_str() {
+            // 替换模式：替换所有匹配，支持$1、${name}引用分组
+            "replace" => {
+                let replacement = options.replacement.as_deref().unwrap_or("");
+                Ok(Value::String(regex.replace_all(text, replacement).into_owned()))
+            }
+            // 所有匹配
+            "all" => Ok(Value::Array(
+                regex
+                    .captures_iter(text)
+                    .map(|captures| Self::match_value(&regex, &captures, options))
+                    .collect(),
+            )),
+            // 第一个匹配，未找到匹配时返回Null
+            _ => Ok(regex
+                .captures(text)
+                .map(|captures| Self::match_value(&regex, &captures, options))
+                .unwrap_or(Value::Null)),
+        }
+    }
+
+    /// 按标志编译正则表达式
+    /// 支持：i（忽略大小写）、m（多行）、s（.匹配换行）、x（忽略空白）、U（非贪婪）
+    fn build_regex(pattern: &str, flags: &str) -> Result<Regex, String> {
+        let mut builder = RegexBuilder::new(pattern);
+        for flag in flags.chars() {
+            match flag {
+                'i' => builder.case_insensitive(true),
+                'm' => builder.multi_line(true),
+                's' => builder.dot_matches_new_line(true),
+                'x' => builder.ignore_whitespace(true),
+                'U' => builder.swap_greed(true),
+                other => return Err(format!("不支持的正则标志: {}", other)),
+            };
+        }
+        builder.build().map_err(|e| format!("正则表达式编译失败: {}", e))
+    }
+
+    /// 将单个匹配转换为返回值
+    ///
+    /// - 指定group：返回该分组内容
+    /// - output为object：返回 {分组名: 内容} 对象，未命名的分组以序号为键
+    /// - 包含分组：返回所有分组内容数组，未参与匹配的分组为null
+    /// - 否则返回整个匹配
+    fn match_value(regex: &Regex, captures: &Captures, options: &RegexpExtractOptions) -> Value {
+        let to_value = |m: Option<regex::Match>| {
+            m.map(|m| Value::String(m.as_str().to_string()))
+                .unwrap_or(Value::Null)
         };
 
-        // 执行匹配
-        if let Some(captures) = regex.captures(text) {
-            // 如果有捕获组，返回所有捕获组结果
-            if captures.len() > 1 {
-                let mut results = Vec::new();
-                for i in 1..captures.len() {
-                    if let Some(matched) = captures.get(i) {
-                        results.push(Value::String(matched.as_str().to_string()));
-                    } else {
-                        results.push(Value::Null);
-                    }
-                }
-                return Ok(Value::Array(results));
+        match &options.group {
+            Some(Value::Number(index)) => {
+                return to_value(index.as_u64().and_then(|i| captures.get(i as usize)));
             }
-            // 否则返回整个匹配
-            if let Some(matched) = captures.get(0) {
-                return Ok(Value::String(matched.as_str().to_string()));
+            Some(Value::String(name)) => return to_value(captures.name(name)),
+            _ => {}
+        }
+
+        if options.output == "object" && captures.len() > 1 {
+            return Value::Object(
+                regex
+                    .capture_names()
+                    .enumerate()
+                    .skip(1)
+                    .map(|(i, name)| {
+                        let key = name.map(str::to_string).unwrap_or_else(|| i.to_string());
+                        (key, to_value(captures.get(i)))
+                    })
+                    .collect(),
+            );
+        }
+
+        if captures.len() > 1 {
+            return Value::Array((1..captures.len()).map(|i| to_value(captures.get(i))).collect());
+        }
+
+        to_value(captures.get(0))
+    }
+}
+
+/// 正则提取选项
+struct RegexpExtractOptions {
+    /// 匹配模式：first、all、replace
+    mode: String,
+    /// 返回指定分组（序号或名称）
+    group: Option<Value>,
+    /// 分组的返回形式：array、object
+    output: String,
+    /// replace模式的替换字符串
+    replacement: Option<String>,
+    /// 正则标志
+    flags: String,
+    /// 提取的目标字段（JsonPathTemplate表达式）
+    source: Option<String>,
+}
+
+impl RegexpExtractOptions {
+    fn from_config(config: Option<&ChainwareConfig>) -> Result<Self, String> {
+        let get = |key: &str| config.and_then(|cfg| cfg.config.get(key));
+        let get_str = |key: &str| -> Result<Option<String>, String> {
+            match get(key) {
+                None => Ok(None),
+                Some(Value::String(s)) => Ok(Some(s.clone())),
+                Some(_) => Err(format!("配置中的{}必须是字符串类型", key)),
             }
+        };
+
+        let mode = get_str("mode")?.unwrap_or_else(|| "first".to_string());
+        if !["first", "all", "replace"].contains(&mode.as_str()) {
+            return Err(format!("不支持的mode配置: {}", mode));
+        }
+        let output = get_str("output")?.unwrap_or_else(|| "array".to_string());
+        if !["array", "object"].contains(&output.as_str()) {
+            return Err(format!("不支持的output配置: {}", output));
         }
+        let group = match get("group") {
+            None => None,
+            Some(group @ (Value::Number(_) | Value::String(_))) => Some(group.clone()),
+            Some(_) => return Err("配置中的group必须是数字或字符串类型".to_string()),
+        };
 
-        // 未找到匹配
-        Ok(Value::Null)
+        Ok(Self {
+            mode,
+            group,
+            output,
+            replacement: get_str("replacement")?,
+            flags: get_str("flags")?.unwrap_or_default(),
+            source: get_str("source")?,
+        })
     }
 }
 
@@ -81,15 +198,99 @@ impl Chainware for RegexpExtractChainware {
         &self.name
     }
 
+    fn description(&self) -> &str {
+        "使用正则表达式从文本中提取或替换内容"
+    }
+
+    fn config_schema(&self) -> Option<Value> {
+        Some(json!({
+            "type": "object",
+            "properties": {
+                "pattern": {
+                    "type": "string",
+                    "description": "正则表达式，未配置时返回原数据"
+                },
+                "mode": {
+                    "type": "string",
+                    "enum": ["first", "all", "replace"],
+                    "default": "first",
+                    "description": "第一个匹配、所有匹配或替换所有匹配"
+                },
+                "group": {
+                    "type": ["integer", "string"],
+                    "description": "只返回指定序号或名称的分组"
+                },
+                "output": {
+                    "type": "string",
+                    "enum": ["array", "object"],
+                    "default": "array",
+                    "description": "分组的返回形式：分组内容数组，或以分组名（未命名时为序号）为键的对象"
+                },
+                "replacement": {
+                    "type": "string",
+                    "description": "replace模式的替换字符串，支持$1、${name}引用分组"
+                },
+                "flags": {
+                    "type": "string",
+                    "description": "正则标志：i忽略大小写、m多行、s点号匹配换行、x忽略空白、U非贪婪"
+                },
+                "source": {
+                    "type": "string",
+                    "description": "JsonPath表达式，从指定字段提取，默认使用整个输入"
+                }
+            },
+            "additionalProperties": false
+        }))
+    }
+
+    fn examples(&self) -> Vec<Value> {
+        vec![
+            json!({"pattern": "(?P<key>\\w+)=(?P<value>\\w+)", "mode": "all", "output": "object", "source": "$.content"}),
+            json!({"pattern": "(\\d{4})-(\\d{2})-(\\d{2})", "mode": "replace", "replacement": "$3/$2/$1"}),
+        ]
+    }
+
     fn process(
         &self,
-        _request: &ChainRequest,
+        request: &ChainRequest,
         response: &mut ChainResponse,
         data: Option<serde_json::Value>,
         config: Option<&ChainwareConfig>,
     ) -> Option<serde_json::Value> {
         let input = data.unwrap_or_default();
 
+        let options = match RegexpExtractOptions::from_config(config) {
+            Ok(options) => options,
+            Err(err) => {
+                response.status = ChainStatus::Error;
+                response.data = Some(ErrorResponse::new(error_codes::CONFIG_ERROR, err, None).to_json());
+                return None;
+            }
+        };
+
+        // 如果配置了source，从指定字段获取文本
+        let input = match &options.source {
+            Some(source) => {
+                let context = JsonPathTemplate::build_path_context(&input, request, response);
+                match JsonPathTemplate::get_path_value(&context, source) {
+                    Ok(value) => value.unwrap_or(Value::Null),
+                    Err(err) => {
+                        response.status = ChainStatus::Error;
+                        response.data = Some(
+                            ErrorResponse::new(
+                                error_codes::INTERNAL_ERROR,
+                                format!("正则提取失败: 无法获取source '{}': {}", source, err),
+                                None,
+                            )
+                            .to_json(),
+                        );
+                        return None;
+                    }
+                }
+            }
+            None => input,
+        };
+
         // 从配置中获取正则表达式
         let pattern = match config.and_then(|cfg| cfg.config.get("pattern")) {
             Some(Value::String(p)) => Some(p.as_str()),
@@ -108,7 +309,7 @@ impl Chainware for RegexpExtractChainware {
             None => None,
         };
 
-        match self.process_regexp_extract(&input, pattern) {
+        match self.process_regexp_extract(&input, pattern, &options) {
             Ok(result) => Some(result),
             Err(err) => {
                 response.status = ChainStatus::Error;
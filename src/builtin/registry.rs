@@ -6,8 +6,13 @@ use crate::chainware::core::{Chainware};
 use super::{
     ConditionChainware, ExtractJsonChainware, ExtractMapChainware, ExtractSqlChainware, JsonExtractChainware, LoggerChainware,
     MapFieldsChainware, MergeChainware, RegexpConditionChainware, RegexpExtractChainware,
-    IpBlacklistChainware, IpWhitelistChainware,
+    IpBlacklistChainware, IpWhitelistChainware, PatchChainware, ConvertChainware, ArrayOpsChainware,
+    ExtractCodeChainware, SetVarChainware, SwitchChainware, LoopChainware,
 };
+#[cfg(feature = "geo")]
+use super::GeoFilterChainware;
+use crate::utils::json_schema;
+use serde_json::{json, Value};
 use std::collections::HashMap;
 
 use std::sync::OnceLock;
@@ -35,6 +40,16 @@ pub enum BuiltinChainwareType {
     RegexpCondition,
     IpBlacklist,
     IpWhitelist,
+    Patch,
+    Convert,
+    ArrayOps,
+    ExtractCode,
+    /// 需要启用`geo`特性
+    #[cfg(feature = "geo")]
+    GeoFilter,
+    SetVar,
+    Switch,
+    Loop,
     Unknown(String),
 }
 
@@ -53,11 +68,91 @@ impl From<&str> for BuiltinChainwareType {
             "regexp_condition" => BuiltinChainwareType::RegexpCondition,
             "ip_blacklist" => BuiltinChainwareType::IpBlacklist,
             "ip_whitelist" => BuiltinChainwareType::IpWhitelist,
+            "patch" => BuiltinChainwareType::Patch,
+            "convert" => BuiltinChainwareType::Convert,
+            "array_ops" => BuiltinChainwareType::ArrayOps,
+            "extract_code" => BuiltinChainwareType::ExtractCode,
+            #[cfg(feature = "geo")]
+            "geo_filter" => BuiltinChainwareType::GeoFilter,
+            "set_var" => BuiltinChainwareType::SetVar,
+            "switch" => BuiltinChainwareType::Switch,
+            "loop" => BuiltinChainwareType::Loop,
             other => BuiltinChainwareType::Unknown(other.to_string()),
         }
     }
 }
 
+/// 挂件描述信息，用于生成配置表单和文档
+#[derive(Debug, Clone)]
+pub struct ChainwareInfo {
+    /// 挂件名称
+    pub name: String,
+    /// 功能描述
+    pub description: String,
+    /// 配置的JSON Schema（已包含`enabled`等所有挂件通用的配置项）
+    pub config_schema: Value,
+    /// 配置示例
+    pub examples: Vec<Value>,
+}
+
+impl ChainwareInfo {
+    /// 转换为JSON格式
+    pub fn to_json(&self) -> Value {
+        json!({
+            "name": self.name,
+            "description": self.description,
+            "config_schema": self.config_schema,
+            "examples": self.examples,
+        })
+    }
+}
+
+/// 所有挂件通用的配置项，由挂件包装器处理
+fn common_config_properties() -> Value {
+    json!({
+        "enabled": {
+            "type": "boolean",
+            "default": true,
+            "description": "是否启用挂件，禁用时数据原样透传"
+        },
+        "when": {
+            "type": "string",
+            "description": "执行条件，语法与condition挂件相同，不满足时跳过挂件、数据原样透传"
+        },
+        "input_path": {
+            "type": "string",
+            "description": "挂件只处理该路径选取的数据，支持$.、$params、$meta、$vars等路径和模板"
+        },
+        "output_path": {
+            "type": "string",
+            "description": "挂件结果写入原数据的该路径（$vars.x写入链变量），其余数据保持不变"
+        },
+        "on_error": {
+            "type": ["array", "object"],
+            "description": "挂件出错时执行的子链，挂件数组或{\"chain\": [...], \"recover\": true}"
+        },
+        "continue_on_error": {
+            "type": "boolean",
+            "default": false,
+            "description": "挂件出错时忽略错误，使用挂件的输入数据继续执行后续挂件"
+        }
+    })
+}
+
+/// 在挂件声明的Schema中加入通用配置项；未声明Schema的挂件接受任意对象
+fn with_common_properties(schema: Option<Value>) -> Value {
+    let mut schema = schema.unwrap_or_else(|| json!({"type": "object"}));
+    if let Some(obj) = schema.as_object_mut() {
+        let properties = obj.entry("properties").or_insert_with(|| json!({}));
+        if let (Some(properties), Value::Object(common)) = (properties.as_object_mut(), common_config_properties()) {
+            for (key, value) in common {
+                properties.entry(key).or_insert(value);
+            }
+        }
+    }
+    schema
+}
+
 /// 内置挂件注册表
 pub struct BuiltinChainwareRegistry {
     chainware_types: HashMap<String, BuiltinChainwareType>,
@@ -83,6 +178,15 @@ impl BuiltinChainwareRegistry {
         registry.register("regexp_extract", BuiltinChainwareType::RegexpExtract);
         registry.register("ip_blacklist", BuiltinChainwareType::IpBlacklist);
         registry.register("ip_whitelist", BuiltinChainwareType::IpWhitelist);
+        registry.register("patch", BuiltinChainwareType::Patch);
+        registry.register("convert", BuiltinChainwareType::Convert);
+        registry.register("array_ops", BuiltinChainwareType::ArrayOps);
+        registry.register("extract_code", BuiltinChainwareType::ExtractCode);
+        #[cfg(feature = "geo")]
+        registry.register("geo_filter", BuiltinChainwareType::GeoFilter);
+        registry.register("set_var", BuiltinChainwareType::SetVar);
+        registry.register("switch", BuiltinChainwareType::Switch);
+        registry.register("loop", BuiltinChainwareType::Loop);
 
         registry
     }
@@ -98,6 +202,42 @@ impl BuiltinChainwareRegistry {
         self.chainware_types.get(&name.to_lowercase())
     }
 
+    /// 所有已注册的挂件名称（按名称排序）
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.chainware_types.keys().map(|k| k.as_str()).collect();
+        names.sort();
+        names
+    }
+
+    /// 获取挂件的描述信息
+    pub fn describe(&self, name: &str) -> Option<ChainwareInfo> {
+        let chainware = self.create_chainware(name)?;
+        Some(ChainwareInfo {
+            name: chainware.name().to_string(),
+            description: chainware.description().to_string(),
+            config_schema: with_common_properties(chainware.config_schema()),
+            examples: chainware.examples(),
+        })
+    }
+
+    /// 获取所有挂件的描述信息（按名称排序）
+    pub fn describe_all(&self) -> Vec<ChainwareInfo> {
+        self.names().into_iter().filter_map(|name| self.describe(name)).collect()
+    }
+
+    /// 按挂件的配置Schema校验配置，返回所有错误
+    pub fn validate_config(&self, name: &str, config: &Value) -> Result<(), Vec<String>> {
+        let info = self
+            .describe(name)
+            .ok_or_else(|| vec![format!("未找到内置挂件: {}", name)])?;
+        let errors = json_schema::validate(config, &info.config_schema);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
     /// 创建内置挂件实例
     pub fn create_chainware(&self, name: &str) -> Option<Box<dyn Chainware>> {
         match self.get_type(name)? {
@@ -115,6 +255,15 @@ impl BuiltinChainwareRegistry {
             }
             BuiltinChainwareType::IpBlacklist => Some(Box::new(IpBlacklistChainware::new())),
             BuiltinChainwareType::IpWhitelist => Some(Box::new(IpWhitelistChainware::new())),
+            BuiltinChainwareType::Patch => Some(Box::new(PatchChainware::new())),
+            BuiltinChainwareType::Convert => Some(Box::new(ConvertChainware::new())),
+            BuiltinChainwareType::ArrayOps => Some(Box::new(ArrayOpsChainware::new())),
+            BuiltinChainwareType::ExtractCode => Some(Box::new(ExtractCodeChainware::new())),
+            #[cfg(feature = "geo")]
+            BuiltinChainwareType::GeoFilter => Some(Box::new(GeoFilterChainware::new())),
+            BuiltinChainwareType::SetVar => Some(Box::new(SetVarChainware::new())),
+            BuiltinChainwareType::Switch => Some(Box::new(SwitchChainware::new())),
+            BuiltinChainwareType::Loop => Some(Box::new(LoopChainware::new())),
             BuiltinChainwareType::Unknown(_) => None,
         }
     }
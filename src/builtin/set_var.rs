@@ -0,0 +1,147 @@
+//! 链变量设置挂件
+//!
+//! 将数据写入链变量，后续节点通过`$vars.x`读取，数据原样透传
+
+use crate::chainware::config::ChainwareConfig;
+use crate::chainware::core::Chainware;
+use crate::core::{ChainStatus, ChainRequest, ChainResponse};
+use crate::types::{error_codes, ErrorResponse};
+use crate::utils::json_path::{JsonPathTemplate, PathContext};
+use serde_json::{json, Value};
+
+/// 链变量设置挂件
+///
+/// 配置格式：
+/// {
+///   "vars": {
+///     "user": "$.user",                  // 使用JSONPath提取值，路径不存在时为null
+///     "greeting": "你好，${$.name}",     // 使用模板字符串
+///     "retries": 3                       // 非字符串直接作为字面量
+///   }
+/// }
+///
+/// 同一节点中的变量基于执行前的上下文求值，不能引用本节点设置的其他变量
+pub struct SetVarChainware {
+    name: String,
+}
+
+impl Default for SetVarChainware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SetVarChainware {
+    pub fn new() -> Self {
+        Self {
+            name: "set_var".to_string(),
+        }
+    }
+
+    /// 计算变量值：`$`开头的路径和包含`${}`的模板从上下文中取值，其他字符串和非字符串值作为字面量
+    fn evaluate(value: &Value, context: &PathContext<'_>) -> Result<Value, String> {
+        match value {
+            Value::String(template) => {
+                let template = template.trim();
+                if template.starts_with('$') || template.contains("${") {
+                    Ok(JsonPathTemplate::get_path_value(context, template)?.unwrap_or(Value::Null))
+                } else {
+                    Ok(Value::String(template.to_string()))
+                }
+            }
+            other => Ok(other.clone()),
+        }
+    }
+}
+
+impl Chainware for SetVarChainware {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        "将数据写入链变量，后续节点通过$vars读取，数据原样透传"
+    }
+
+    fn config_schema(&self) -> Option<Value> {
+        Some(json!({
+            "type": "object",
+            "properties": {
+                "vars": {
+                    "type": "object",
+                    "description": "key为变量名，value为路径、模板或字面量"
+                }
+            },
+            "required": ["vars"],
+            "additionalProperties": false
+        }))
+    }
+
+    fn examples(&self) -> Vec<Value> {
+        vec![json!({
+            "vars": {
+                "user": "$.user",
+                "greeting": "你好，${$.user.name}",
+                "source": "$meta.ip_address"
+            }
+        })]
+    }
+
+    fn process(
+        &self,
+        request: &ChainRequest,
+        response: &mut ChainResponse,
+        data: Option<serde_json::Value>,
+        config: Option<&ChainwareConfig>,
+    ) -> Option<serde_json::Value> {
+        let input = data.unwrap_or_default();
+
+        let vars = match config.and_then(|cfg| cfg.config.get("vars")) {
+            Some(Value::Object(vars)) => vars,
+            Some(_) => {
+                response.status = ChainStatus::Error;
+                response.data = Some(
+                    ErrorResponse::new(error_codes::CONFIG_ERROR, "vars配置必须是对象类型".to_string(), None)
+                        .to_json(),
+                );
+                return None;
+            }
+            None => {
+                response.status = ChainStatus::Error;
+                response.data = Some(
+                    ErrorResponse::new(error_codes::CONFIG_ERROR, "set_var挂件需要vars配置".to_string(), None)
+                        .to_json(),
+                );
+                return None;
+            }
+        };
+
+        // 所有变量基于同一个上下文求值，全部成功后再写入
+        let context = JsonPathTemplate::build_path_context(&input, request, response);
+        let mut values = Vec::with_capacity(vars.len());
+        for (name, value) in vars {
+            match Self::evaluate(value, &context) {
+                Ok(value) => values.push((name.clone(), value)),
+                Err(err) => {
+                    response.status = ChainStatus::Error;
+                    response.data = Some(
+                        ErrorResponse::new(
+                            error_codes::INTERNAL_ERROR,
+                            format!("变量 '{}' 求值失败: {}", name, err),
+                            None,
+                        )
+                        .to_json(),
+                    );
+                    return None;
+                }
+            }
+        }
+
+        for (name, value) in values {
+            response.set_var(name, value);
+        }
+
+        // 透传数据
+        Some(input)
+    }
+}
@@ -0,0 +1,314 @@
+//! 多路分发挂件
+//!
+//! 根据表达式的值选择一个分支子链执行，用于按工具名、请求类型、API版本等分发到不同的处理流程
+
+use crate::chain::executor::ChainExecutor;
+use crate::chainware::config::ChainwareConfig;
+use crate::chainware::core::Chainware;
+use crate::core::{ChainStatus, ChainRequest, ChainResponse};
+use crate::types::{error_codes, ErrorResponse};
+use crate::utils::condition;
+use crate::utils::json_path::{JsonPathTemplate, PathContext};
+use regex::Regex;
+use serde_json::{json, Value};
+use std::sync::OnceLock;
+
+/// 默认记录选中分支的meta字段名
+const DEFAULT_CASE_KEY: &str = "switch_case";
+
+/// 分支匹配方式
+enum CaseMatcher {
+    /// 值与分支完全相等
+    Exact(String),
+    /// 值匹配分支的正则表达式
+    Regex(Regex),
+    /// 分支的条件表达式为true，语法与condition挂件相同
+    Condition(String),
+}
+
+/// 分支：名称、匹配方式和子链
+struct SwitchCase {
+    name: String,
+    matcher: CaseMatcher,
+    chain: ChainExecutor,
+}
+
+/// 编译后的分发规则
+struct SwitchRules {
+    /// 分发表达式，condition匹配方式下不需要
+    value: Option<String>,
+    cases: Vec<SwitchCase>,
+    default: Option<ChainExecutor>,
+    case_key: String,
+}
+
+impl SwitchRules {
+    fn from_config(config: Option<&ChainwareConfig>) -> Result<Self, String> {
+        let config = config.ok_or_else(|| "switch挂件需要cases配置".to_string())?;
+
+        let mode = match config.get_param("match") {
+            None => "exact",
+            Some(Value::String(mode)) if ["exact", "regex", "condition"].contains(&mode.as_str()) => mode.as_str(),
+            Some(other) => return Err(format!("不支持的匹配方式: {}，可选值为exact、regex、condition", other)),
+        };
+
+        let value = match config.get_param("value") {
+            Some(Value::String(value)) => Some(value.clone()),
+            Some(_) => return Err("value配置必须是字符串类型".to_string()),
+            None if mode == "condition" => None,
+            None => return Err(format!("{}匹配方式需要value配置", mode)),
+        };
+
+        // 对象形式为 {"匹配值": [子链]}，按key排序匹配；数组形式按顺序匹配
+        let entries: Vec<(Option<String>, String, &Value)> = match config.get_param("cases") {
+            Some(Value::Object(cases)) => cases.iter().map(|(key, chain)| (None, key.clone(), chain)).collect(),
+            Some(Value::Array(cases)) => cases
+                .iter()
+                .enumerate()
+                .map(|(index, case)| {
+                    let pattern = case
+                        .get("case")
+                        .and_then(|v| v.as_str())
+                        .ok_or_else(|| format!("第{}个分支缺少字符串类型的case字段", index + 1))?;
+                    let chain = case
+                        .get("chain")
+                        .ok_or_else(|| format!("第{}个分支缺少chain字段", index + 1))?;
+                    let name = case.get("name").and_then(|v| v.as_str()).map(|name| name.to_string());
+                    Ok((name, pattern.to_string(), chain))
+                })
+                .collect::<Result<_, String>>()?,
+            Some(_) => return Err("cases配置必须是对象或数组".to_string()),
+            None => return Err("switch挂件需要cases配置".to_string()),
+        };
+
+        let mut cases = Vec::with_capacity(entries.len());
+        for (name, pattern, chain) in entries {
+            let matcher = match mode {
+                "regex" => CaseMatcher::Regex(
+                    Regex::new(&pattern).map_err(|e| format!("分支 '{}' 的正则表达式无效: {}", pattern, e))?,
+                ),
+                "condition" => CaseMatcher::Condition(pattern.clone()),
+                _ => CaseMatcher::Exact(pattern.clone()),
+            };
+            let chain = ChainExecutor::new()
+                .add_chainwares(chain.clone())
+                .map_err(|e| format!("分支 '{}' 的子链配置错误: {}", pattern, e.msg))?;
+            cases.push(SwitchCase {
+                name: name.unwrap_or(pattern),
+                matcher,
+                chain,
+            });
+        }
+
+        let default = match config.get_param("default") {
+            Some(chain) => Some(
+                ChainExecutor::new()
+                    .add_chainwares(chain.clone())
+                    .map_err(|e| format!("default子链配置错误: {}", e.msg))?,
+            ),
+            None => None,
+        };
+
+        let case_key = match config.get_param("case_key") {
+            Some(Value::String(key)) => key.clone(),
+            Some(_) => return Err("case_key配置必须是字符串类型".to_string()),
+            None => DEFAULT_CASE_KEY.to_string(),
+        };
+
+        Ok(Self {
+            value,
+            cases,
+            default,
+            case_key,
+        })
+    }
+
+    /// 选择第一个匹配的分支，没有匹配时使用默认分支
+    fn select(&self, context: &PathContext<'_>) -> Result<Option<(&str, &ChainExecutor)>, String> {
+        // 分发值转换为字符串比较，值不存在或为null时只能匹配默认分支
+        let value = match &self.value {
+            Some(template) => match JsonPathTemplate::get_path_value(context, template)
+                .map_err(|e| format!("value表达式 '{}' 求值失败: {}", template, e))?
+            {
+                None | Some(Value::Null) => None,
+                Some(Value::String(s)) => Some(s),
+                Some(other) => Some(other.to_string()),
+            },
+            None => None,
+        };
+
+        for case in &self.cases {
+            let matched = match &case.matcher {
+                CaseMatcher::Exact(expected) => value.as_deref() == Some(expected.as_str()),
+                CaseMatcher::Regex(re) => value.as_deref().is_some_and(|v| re.is_match(v)),
+                CaseMatcher::Condition(expression) => condition::evaluate(expression, context)
+                    .map_err(|e| format!("分支条件 '{}' 求值失败: {}", expression, e))?,
+            };
+            if matched {
+                return Ok(Some((&case.name, &case.chain)));
+            }
+        }
+
+        Ok(self.default.as_ref().map(|chain| ("default", chain)))
+    }
+}
+
+/// 多路分发挂件
+///
+/// 配置格式：
+/// {
+///   "value": "$meta.tool_name",          // 分发表达式，支持JSONPath和模板
+///   "match": "exact",                    // 匹配方式：exact（默认）、regex、condition
+///   "cases": {                           // 匹配值 -> 子链，按key排序匹配
+///     "search": [{"name": "...", "config": {...}}],
+///     "weather": [...]
+///   },
+///   "default": [...],                    // 默认分支（可选），没有匹配的分支时执行
+///   "case_key": "switch_case"            // 选中分支名称写入响应meta的字段名
+/// }
+///
+/// `cases`也可以是数组，按顺序匹配第一个满足的分支：
+/// `[{"case": "^v2\\.", "name": "v2", "chain": [...]}]`，condition匹配方式下`case`为条件表达式
+///
+/// 选中的分支以当前数据为输入执行，输出作为节点输出；没有匹配的分支且没有默认分支时数据原样透传
+pub struct SwitchChainware {
+    name: String,
+    /// 编译后的分发规则，首次执行时根据节点配置构建
+    rules: OnceLock<Result<SwitchRules, String>>,
+}
+
+impl Default for SwitchChainware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SwitchChainware {
+    pub fn new() -> Self {
+        Self {
+            name: "switch".to_string(),
+            rules: OnceLock::new(),
+        }
+    }
+
+    /// 获取编译后的分发规则，节点配置不变，只在首次执行时构建
+    fn rules(&self, config: Option<&ChainwareConfig>) -> &Result<SwitchRules, String> {
+        self.rules.get_or_init(|| SwitchRules::from_config(config))
+    }
+}
+
+impl Chainware for SwitchChainware {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        "根据表达式的值选择一个分支子链执行，支持完全匹配、正则匹配和条件匹配"
+    }
+
+    fn config_schema(&self) -> Option<Value> {
+        Some(json!({
+            "type": "object",
+            "properties": {
+                "value": {
+                    "type": "string",
+                    "description": "分发表达式，支持$.、$params、$meta、$vars等路径和模板，condition匹配方式下不需要"
+                },
+                "match": {
+                    "type": "string",
+                    "enum": ["exact", "regex", "condition"],
+                    "default": "exact",
+                    "description": "匹配方式：完全相等、正则匹配或第一个条件表达式为true的分支"
+                },
+                "cases": {
+                    "type": ["object", "array"],
+                    "description": "对象形式为{匹配值: 子链}，数组形式为[{case, name, chain}]并按顺序匹配",
+                    "additionalProperties": {"type": "array"},
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "case": {"type": "string", "description": "匹配值、正则表达式或条件表达式"},
+                            "name": {"type": "string", "description": "分支名称，默认为case"},
+                            "chain": {"type": "array", "description": "分支子链"}
+                        },
+                        "required": ["case", "chain"],
+                        "additionalProperties": false
+                    }
+                },
+                "default": {
+                    "type": "array",
+                    "description": "默认分支子链，没有匹配的分支时执行"
+                },
+                "case_key": {
+                    "type": "string",
+                    "default": DEFAULT_CASE_KEY,
+                    "description": "选中分支名称写入响应meta的字段名"
+                }
+            },
+            "required": ["cases"],
+            "additionalProperties": false
+        }))
+    }
+
+    fn examples(&self) -> Vec<Value> {
+        vec![
+            json!({
+                "value": "$meta.tool_name",
+                "cases": {
+                    "search": [{"name": "extract_map", "config": {"mapping": {"query": "$.q"}}}],
+                    "weather": [{"name": "extract_map", "config": {"mapping": {"city": "$.city"}}}]
+                },
+                "default": [{"name": "logger", "config": {"template": "未知工具: ${$meta.tool_name}"}}]
+            }),
+            json!({
+                "value": "$params.version",
+                "match": "regex",
+                "cases": [
+                    {"case": "^v2\\.", "name": "v2", "chain": [{"name": "logger"}]},
+                    {"case": "^v1\\.", "name": "v1", "chain": []}
+                ]
+            }),
+        ]
+    }
+
+    fn process(
+        &self,
+        request: &ChainRequest,
+        response: &mut ChainResponse,
+        data: Option<serde_json::Value>,
+        config: Option<&ChainwareConfig>,
+    ) -> Option<serde_json::Value> {
+        let input = data.unwrap_or_default();
+
+        let rules = match self.rules(config) {
+            Ok(rules) => rules,
+            Err(err) => {
+                response.status = ChainStatus::Error;
+                response.data = Some(ErrorResponse::new(error_codes::CONFIG_ERROR, err.clone(), None).to_json());
+                return None;
+            }
+        };
+
+        let context = JsonPathTemplate::build_path_context(&input, request, response);
+        let (case_name, chain) = match rules.select(&context) {
+            Ok(Some(selected)) => selected,
+            // 没有匹配的分支，数据透传
+            Ok(None) => return Some(input),
+            Err(err) => {
+                response.status = ChainStatus::Error;
+                response.data = Some(ErrorResponse::new(error_codes::INTERNAL_ERROR, err, None).to_json());
+                return None;
+            }
+        };
+        response.set_meta(rules.case_key.clone(), json!(case_name));
+
+        // 分支子链的状态和数据作为本节点的结果
+        let sub_response = chain.run_nested(request, response, input);
+        if sub_response.status != ChainStatus::Continue {
+            response.status = sub_response.status;
+            response.data = sub_response.data;
+            return None;
+        }
+        sub_response.data
+    }
+}
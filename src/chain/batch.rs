@@ -0,0 +1,415 @@
+//! 批量执行模块
+//!
+//! 在线程池中并行执行多个请求，响应按请求顺序返回：
+//! 单个请求的panic被隔离为该请求的Error响应，大量请求可按块流式执行，并汇总各状态数量和延迟分位数
+
+use super::executor::ChainExecutor;
+use crate::core::utils::current_timestamp_ms;
+use crate::core::{ChainStatus, ChainRequest, ChainResponse};
+use crate::types::{error_codes, ErrorResponse};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// 默认每块请求数
+const DEFAULT_CHUNK_SIZE: usize = 1000;
+
+/// 批量执行选项
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchOptions {
+    /// 工作线程数，默认为可用CPU数
+    pub workers: usize,
+    /// 流式执行时每块的请求数，默认1000
+    pub chunk_size: usize,
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BatchOptions {
+    pub fn new() -> Self {
+        Self {
+            workers: thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        }
+    }
+
+    /// 设置工作线程数，最少为1
+    pub fn workers(mut self, workers: usize) -> Self {
+        self.workers = workers.max(1);
+        self
+    }
+
+    /// 设置每块请求数，最少为1
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+}
+
+/// 批量执行统计
+#[derive(Debug, Default)]
+pub struct BatchStats {
+    /// 请求总数
+    pub total: usize,
+    /// 各状态的请求数，key为状态名称
+    pub by_status: BTreeMap<String, usize>,
+    /// 执行总耗时（流式执行时为各块耗时之和）
+    pub elapsed: Duration,
+    /// 各请求的执行耗时，查询分位数时才排序
+    latencies: Mutex<Latencies>,
+}
+
+/// 请求耗时记录，记录和合并时只追加，查询时排序一次
+#[derive(Debug, Clone, Default)]
+struct Latencies {
+    values: Vec<Duration>,
+    sorted: bool,
+}
+
+impl Latencies {
+    fn push(&mut self, latency: Duration) {
+        self.values.push(latency);
+        self.sorted = false;
+    }
+
+    fn extend(&mut self, other: &[Duration]) {
+        self.values.extend_from_slice(other);
+        self.sorted = false;
+    }
+
+    /// 按从小到大排序后的耗时
+    fn sorted(&mut self) -> &[Duration] {
+        if !self.sorted {
+            self.values.sort_unstable();
+            self.sorted = true;
+        }
+        &self.values
+    }
+}
+
+impl Clone for BatchStats {
+    fn clone(&self) -> Self {
+        Self {
+            total: self.total,
+            by_status: self.by_status.clone(),
+            elapsed: self.elapsed,
+            latencies: Mutex::new(self.latencies().clone()),
+        }
+    }
+}
+
+impl BatchStats {
+    fn record(&mut self, response: &ChainResponse, latency: Duration) {
+        self.total += 1;
+        *self.by_status.entry(response.status.as_str().to_string()).or_insert(0) += 1;
+        self.latencies_mut().push(latency);
+    }
+
+    fn latencies(&self) -> MutexGuard<'_, Latencies> {
+        self.latencies.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn latencies_mut(&mut self) -> &mut Latencies {
+        self.latencies.get_mut().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 合并另一批的统计，用于汇总流式执行的各块
+    pub fn merge(&mut self, other: &BatchStats) {
+        self.total += other.total;
+        for (status, count) in &other.by_status {
+            *self.by_status.entry(status.clone()).or_insert(0) += count;
+        }
+        self.elapsed += other.elapsed;
+        let other = other.latencies();
+        self.latencies_mut().extend(&other.values);
+    }
+
+    /// 指定状态的请求数
+    pub fn count(&self, status: &ChainStatus) -> usize {
+        self.by_status.get(status.as_str()).copied().unwrap_or(0)
+    }
+
+    /// 延迟分位数，`percentile`取值0-100，没有请求时为0
+    pub fn latency_percentile(&self, percentile: f64) -> Duration {
+        let mut latencies = self.latencies();
+        let sorted = latencies.sorted();
+        if sorted.is_empty() {
+            return Duration::ZERO;
+        }
+        // 最近秩法：取第ceil(p/100*n)个值
+        let rank = (percentile.clamp(0.0, 100.0) / 100.0 * sorted.len() as f64).ceil() as usize;
+        sorted[rank.max(1) - 1]
+    }
+
+    /// 最大延迟
+    pub fn max_latency(&self) -> Duration {
+        self.latencies().values.iter().max().copied().unwrap_or(Duration::ZERO)
+    }
+
+    /// 转换为JSON，耗时单位为毫秒
+    pub fn to_json(&self) -> Value {
+        let ms = |duration: Duration| duration.as_secs_f64() * 1000.0;
+        json!({
+            "total": self.total,
+            "by_status": self.by_status,
+            "elapsed_ms": ms(self.elapsed),
+            "latency_ms": {
+                "p50": ms(self.latency_percentile(50.0)),
+                "p90": ms(self.latency_percentile(90.0)),
+                "p99": ms(self.latency_percentile(99.0)),
+                "max": ms(self.max_latency())
+            }
+        })
+    }
+}
+
+/// 批量执行结果
+#[derive(Debug)]
+pub struct BatchResult {
+    /// 响应，与请求顺序一致
+    pub responses: Vec<ChainResponse>,
+    /// 统计信息
+    pub stats: BatchStats,
+}
+
+/// 执行单个请求，panic转换为该请求的Error响应，不影响其他请求
+fn execute_isolated(executor: &ChainExecutor, request: ChainRequest) -> ChainResponse {
+    let start_time = request.start_time;
+    match panic::catch_unwind(AssertUnwindSafe(|| executor.execute(request))) {
+        Ok(response) => response,
+        Err(payload) => {
+            let reason = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "未知错误".to_string());
+            let mut response = ChainResponse::new(start_time);
+            response.set_status(ChainStatus::Error);
+            response.set_data(
+                ErrorResponse::new(error_codes::INTERNAL_ERROR, format!("请求执行时发生panic: {}", reason), None)
+                    .to_json(),
+            );
+            response.set_end_time();
+            response
+        }
+    }
+}
+
+/// 已执行请求的位置、响应和耗时
+type Finished = (usize, ChainResponse, Duration);
+
+/// 工作线程异常退出时，未返回响应的请求以Error响应占位，保持响应与请求一一对应
+fn lost_response() -> ChainResponse {
+    let mut response = ChainResponse::new(current_timestamp_ms());
+    response.set_status(ChainStatus::Error);
+    response.set_data(
+        ErrorResponse::new(error_codes::INTERNAL_ERROR, "工作线程异常退出，请求未执行完成".to_string(), None)
+            .to_json(),
+    );
+    response.set_end_time();
+    response
+}
+
+/// 按请求位置整理执行结果并统计，缺失的结果以Error响应填充
+fn collect_results(total: usize, finished: impl IntoIterator<Item = Finished>, started: Instant) -> BatchResult {
+    let mut slots: Vec<Option<(ChainResponse, Duration)>> = (0..total).map(|_| None).collect();
+    for (index, response, latency) in finished {
+        slots[index] = Some((response, latency));
+    }
+
+    let mut stats = BatchStats::default();
+    let mut responses = Vec::with_capacity(total);
+    for slot in slots {
+        let (response, latency) = slot.unwrap_or_else(|| (lost_response(), Duration::ZERO));
+        stats.record(&response, latency);
+        responses.push(response);
+    }
+    stats.elapsed = started.elapsed();
+
+    BatchResult { responses, stats }
+}
+
+/// 运行工作线程循环，异常退出后重新进入循环继续处理剩余请求
+fn keep_working(work: impl Fn()) {
+    while panic::catch_unwind(AssertUnwindSafe(&work)).is_err() {}
+}
+
+/// 在线程池中执行一批请求，响应按请求顺序返回
+pub(crate) fn run_batch(executor: &ChainExecutor, requests: Vec<ChainRequest>, workers: usize) -> BatchResult {
+    let started = Instant::now();
+    let total = requests.len();
+    let queue = Mutex::new(requests.into_iter().enumerate());
+    // 每个请求执行完立即写入结果，线程异常退出时只丢失正在执行的请求
+    let finished = Mutex::new(Vec::with_capacity(total));
+
+    // 工作线程从共享队列中取请求，直到队列为空
+    let work = || loop {
+        let next = queue.lock().unwrap_or_else(|e| e.into_inner()).next();
+        let Some((index, request)) = next else {
+            break;
+        };
+        let begin = Instant::now();
+        let response = execute_isolated(executor, request);
+        finished
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push((index, response, begin.elapsed()));
+    };
+
+    match workers.clamp(1, total.max(1)) {
+        // 单线程时直接在当前线程执行
+        1 => keep_working(work),
+        workers => thread::scope(|scope| {
+            let handles: Vec<_> = (0..workers).map(|_| scope.spawn(|| keep_working(work))).collect();
+            // 逐个join，避免线程异常退出时scope向调用方传播panic
+            for handle in handles {
+                let _ = handle.join();
+            }
+        }),
+    }
+
+    let finished = finished.into_inner().unwrap_or_else(|e| e.into_inner());
+    collect_results(total, finished, started)
+}
+
+/// 工作线程正在执行的请求
+///
+/// 线程在执行过程中异常退出时，释放时为该请求返回Error响应，避免等待结果的一方永久阻塞
+struct InFlight<'a> {
+    index: Option<usize>,
+    results: &'a Sender<Finished>,
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        if let Some(index) = self.index.take() {
+            let _ = self.results.send((index, lost_response(), Duration::ZERO));
+        }
+    }
+}
+
+/// 常驻工作线程池
+///
+/// 线程在流式执行期间保持运行，各块的请求通过通道分发给空闲线程，结果通过通道返回
+struct WorkerPool {
+    jobs: Option<Sender<(usize, ChainRequest)>>,
+    results: Receiver<Finished>,
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl WorkerPool {
+    fn new(executor: Arc<ChainExecutor>, workers: usize) -> Self {
+        let (jobs, job_receiver) = mpsc::channel::<(usize, ChainRequest)>();
+        let (result_sender, results) = mpsc::channel();
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+
+        let handles = (0..workers)
+            .map(|_| {
+                let executor = Arc::clone(&executor);
+                let job_receiver = Arc::clone(&job_receiver);
+                let result_sender = result_sender.clone();
+                thread::spawn(move || keep_working(|| loop {
+                    // 通道关闭（流结束）时退出
+                    let job = job_receiver.lock().unwrap_or_else(|e| e.into_inner()).recv();
+                    let Ok((index, request)) = job else {
+                        break;
+                    };
+                    let mut in_flight = InFlight {
+                        index: Some(index),
+                        results: &result_sender,
+                    };
+                    let begin = Instant::now();
+                    let response = execute_isolated(&executor, request);
+                    in_flight.index = None;
+                    if result_sender.send((index, response, begin.elapsed())).is_err() {
+                        break;
+                    }
+                }))
+            })
+            .collect();
+
+        Self {
+            jobs: Some(jobs),
+            results,
+            handles,
+        }
+    }
+
+    /// 执行一块请求，响应按请求顺序返回
+    fn run(&self, chunk: Vec<ChainRequest>) -> BatchResult {
+        let started = Instant::now();
+        let total = chunk.len();
+        let mut sent = 0;
+        if let Some(jobs) = &self.jobs {
+            for job in chunk.into_iter().enumerate() {
+                if jobs.send(job).is_err() {
+                    break;
+                }
+                sent += 1;
+            }
+        }
+
+        // 所有工作线程都已退出时结果通道关闭，未返回的请求由collect_results填充
+        let finished: Vec<Finished> = (0..sent).map_while(|_| self.results.recv().ok()).collect();
+        collect_results(total, finished, started)
+    }
+}
+
+impl Drop for WorkerPool {
+    fn drop(&mut self) {
+        // 关闭任务通道，工作线程取完任务后退出
+        self.jobs = None;
+        for handle in self.handles.drain(..) {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// 按块流式执行的迭代器
+///
+/// 通过 [`ChainExecutor::execute_stream`] 创建，每次从输入中取出`chunk_size`个请求并行执行，
+/// 返回该块的结果，输入不会一次性全部载入内存。工作线程在首块执行时创建，各块复用，迭代器释放时退出
+pub struct BatchStream<I> {
+    executor: Arc<ChainExecutor>,
+    requests: I,
+    options: BatchOptions,
+    pool: Option<WorkerPool>,
+}
+
+impl<I: Iterator<Item = ChainRequest>> BatchStream<I> {
+    pub(crate) fn new(executor: Arc<ChainExecutor>, requests: I, options: BatchOptions) -> Self {
+        Self {
+            executor,
+            requests,
+            options,
+            pool: None,
+        }
+    }
+}
+
+impl<I: Iterator<Item = ChainRequest>> Iterator for BatchStream<I> {
+    type Item = BatchResult;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let chunk: Vec<ChainRequest> = self.requests.by_ref().take(self.options.chunk_size.max(1)).collect();
+        if chunk.is_empty() {
+            return None;
+        }
+        // 单线程或每块只有一个请求时直接在当前线程执行
+        let workers = self.options.workers.min(self.options.chunk_size).max(1);
+        if workers == 1 {
+            return Some(run_batch(&self.executor, chunk, 1));
+        }
+        let executor = &self.executor;
+        let pool = self.pool.get_or_insert_with(|| WorkerPool::new(Arc::clone(executor), workers));
+        Some(pool.run(chunk))
+    }
+}
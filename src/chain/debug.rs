@@ -0,0 +1,274 @@
+//! 链调试模块
+//!
+//! 提供逐节点执行的调试会话，用于检查挂件链配置：
+//! 步骤之间可查看和修改数据，支持按节点或状态变化设置断点，并可回退到任意步骤重新执行
+
+use super::executor::ChainExecutor;
+use crate::core::{ChainStatus, ChainRequest, ChainResponse};
+use serde_json::Value;
+
+/// 断点
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Breakpoint {
+    /// 在指定名称的挂件执行之前暂停
+    Node(String),
+    /// 在指定位置的挂件执行之前暂停
+    Index(usize),
+    /// 执行状态发生变化后暂停
+    StatusChange,
+}
+
+/// 暂停原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DebugStop {
+    /// 命中节点断点，`index`为即将执行的节点位置
+    Breakpoint { index: usize, node_name: String },
+    /// 执行状态发生变化，`index`为导致变化的节点位置
+    StatusChanged { index: usize, from: ChainStatus, to: ChainStatus },
+    /// 链执行结束
+    Finished(ChainStatus),
+}
+
+/// 单个步骤的执行记录
+#[derive(Debug, Clone)]
+pub struct DebugStep {
+    /// 节点位置
+    pub index: usize,
+    /// 挂件名称
+    pub node_name: String,
+    /// 节点输入数据
+    pub input: Value,
+    /// 节点输出数据
+    pub output: Value,
+    /// 执行前的状态
+    pub status_before: ChainStatus,
+    /// 执行后的状态
+    pub status: ChainStatus,
+    /// 执行后的响应数据（错误或拒绝时为错误信息）
+    pub response_data: Option<Value>,
+}
+
+/// 步骤执行前的快照，用于回退
+struct Snapshot {
+    data: Value,
+    request: ChainRequest,
+    response: ChainResponse,
+}
+
+/// 调试会话
+///
+/// 通过 [`ChainExecutor::debug_session`] 创建，执行逻辑与 [`ChainExecutor::execute`] 一致
+pub struct DebugSession<'a> {
+    executor: &'a ChainExecutor,
+    request: ChainRequest,
+    response: ChainResponse,
+    /// 下一个节点的输入数据
+    data: Value,
+    /// 下一个待执行节点的位置
+    position: usize,
+    breakpoints: Vec<Breakpoint>,
+    history: Vec<DebugStep>,
+    snapshots: Vec<Snapshot>,
+    /// 上次因节点断点暂停的位置，继续执行时不再重复暂停
+    paused_at: Option<usize>,
+}
+
+impl<'a> DebugSession<'a> {
+    pub(crate) fn new(executor: &'a ChainExecutor, request: ChainRequest) -> Self {
+        let mut response = ChainResponse::new(request.start_time);
+        let data = request.params.clone();
+        response.set_data(data.clone());
+
+        Self {
+            executor,
+            request,
+            response,
+            data,
+            position: 0,
+            breakpoints: Vec::new(),
+            history: Vec::new(),
+            snapshots: Vec::new(),
+            paused_at: None,
+        }
+    }
+
+    /// 添加断点
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) -> &mut Self {
+        if !self.breakpoints.contains(&breakpoint) {
+            self.breakpoints.push(breakpoint);
+        }
+        self
+    }
+
+    /// 清除所有断点
+    pub fn clear_breakpoints(&mut self) -> &mut Self {
+        self.breakpoints.clear();
+        self
+    }
+
+    /// 下一个待执行节点的位置
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// 下一个待执行节点的名称，执行结束时为None
+    pub fn next_node(&self) -> Option<&str> {
+        if self.is_finished() {
+            None
+        } else {
+            self.executor.node_name(self.position)
+        }
+    }
+
+    /// 链是否已执行结束（所有节点执行完成，或状态不再为Continue）
+    pub fn is_finished(&self) -> bool {
+        self.response.status != ChainStatus::Continue || self.position >= self.executor.len()
+    }
+
+    /// 下一个节点的输入数据
+    pub fn data(&self) -> &Value {
+        &self.data
+    }
+
+    /// 修改下一个节点的输入数据
+    pub fn data_mut(&mut self) -> &mut Value {
+        &mut self.data
+    }
+
+    /// 替换下一个节点的输入数据
+    pub fn set_data(&mut self, data: Value) {
+        self.data = data;
+    }
+
+    /// 请求上下文
+    pub fn request(&self) -> &ChainRequest {
+        &self.request
+    }
+
+    /// 修改请求上下文（如meta中的IP地址）
+    pub fn request_mut(&mut self) -> &mut ChainRequest {
+        &mut self.request
+    }
+
+    /// 当前响应
+    pub fn response(&self) -> &ChainResponse {
+        &self.response
+    }
+
+    /// 已执行步骤的记录
+    pub fn history(&self) -> &[DebugStep] {
+        &self.history
+    }
+
+    /// 执行下一个节点，链已结束时返回None
+    pub fn step(&mut self) -> Option<&DebugStep> {
+        if self.is_finished() {
+            return None;
+        }
+
+        let index = self.position;
+        let input = self.data.clone();
+        let status_before = self.response.status.clone();
+        self.snapshots.push(Snapshot {
+            data: input.clone(),
+            request: self.request.clone(),
+            response: self.response.clone(),
+        });
+
+        let output = self
+            .executor
+            .execute_node(index, &self.request, &mut self.response, input.clone());
+        self.data = output.clone();
+        self.position += 1;
+        self.paused_at = None;
+
+        self.history.push(DebugStep {
+            index,
+            node_name: self.executor.node_name(index).unwrap_or_default().to_string(),
+            input,
+            output,
+            status_before,
+            status: self.response.status.clone(),
+            // 继续执行时响应数据仍为节点的输入数据，记录节点的输出
+            response_data: match self.response.status {
+                ChainStatus::Continue => Some(self.data.clone()),
+                _ => self.response.data.clone(),
+            },
+        });
+        self.history.last()
+    }
+
+    /// 继续执行，直到命中断点或链执行结束
+    pub fn run(&mut self) -> DebugStop {
+        loop {
+            if self.is_finished() {
+                return DebugStop::Finished(self.finished_status());
+            }
+
+            // 节点断点：上次已在该位置暂停时不再重复暂停
+            if self.paused_at != Some(self.position) && self.hits_node_breakpoint(self.position) {
+                self.paused_at = Some(self.position);
+                return DebugStop::Breakpoint {
+                    index: self.position,
+                    node_name: self.executor.node_name(self.position).unwrap_or_default().to_string(),
+                };
+            }
+
+            let step = self.step().cloned();
+            if let Some(step) = step {
+                if step.status != step.status_before && self.breakpoints.contains(&Breakpoint::StatusChange) {
+                    return DebugStop::StatusChanged {
+                        index: step.index,
+                        from: step.status_before,
+                        to: step.status,
+                    };
+                }
+            }
+        }
+    }
+
+    /// 回退到第`step`步执行之前的状态，之后的执行记录被丢弃
+    ///
+    /// 数据、请求上下文和响应一并恢复，之后通过 [`request_mut`](Self::request_mut) 所做的修改不会保留；
+    /// 回退后可修改数据再重新执行，`step`为0时回到初始状态
+    pub fn rewind(&mut self, step: usize) -> bool {
+        if step >= self.snapshots.len() {
+            return false;
+        }
+
+        self.snapshots.truncate(step + 1);
+        self.history.truncate(step);
+        let snapshot = self.snapshots.pop().expect("快照数量与步骤一致");
+        self.data = snapshot.data;
+        self.request = snapshot.request;
+        self.response = snapshot.response;
+        self.position = step;
+        self.paused_at = None;
+        true
+    }
+
+    /// 忽略断点执行所有剩余节点，返回与 [`ChainExecutor::execute`] 相同的最终响应
+    pub fn finish(mut self) -> ChainResponse {
+        while self.step().is_some() {}
+        let data = std::mem::take(&mut self.data);
+        ChainExecutor::finish_response(&mut self.response, data);
+        self.response
+    }
+
+    fn hits_node_breakpoint(&self, index: usize) -> bool {
+        let name = self.executor.node_name(index);
+        self.breakpoints.iter().any(|breakpoint| match breakpoint {
+            Breakpoint::Node(node) => name == Some(node.as_str()),
+            Breakpoint::Index(i) => *i == index,
+            Breakpoint::StatusChange => false,
+        })
+    }
+
+    /// 执行结束时的最终状态
+    fn finished_status(&self) -> ChainStatus {
+        match self.response.status {
+            ChainStatus::Continue => ChainStatus::Completed,
+            ref status => status.clone(),
+        }
+    }
+}
@@ -0,0 +1,112 @@
+//! 错误处理子链
+//!
+//! 节点执行出错（状态为Error）时执行的子链，子链的输入为：
+//! ```json
+//! {
+//!   "error": {"errno": 500, "msg": "...", "detail": null},
+//!   "node": {"index": 1, "name": "extract_json"},
+//!   "data": "出错节点的输入数据（最后一份正确的数据）"
+//! }
+//! ```
+//!
+//! 配置格式：子链挂件数组，或 `{"chain": [...], "recover": true}`
+//! - `recover`为true（默认）时，子链正常执行完成后其输出作为替代数据，链继续执行
+//! - `recover`为false时，子链输出作为自定义的错误响应，链以Error结束
+
+use super::executor::ChainExecutor;
+use crate::core::{ChainStatus, ChainRequest, ChainResponse};
+use serde_json::{json, Value};
+
+/// 错误处理结果
+pub(crate) enum HandlerOutcome {
+    /// 已恢复，链使用替代数据继续执行
+    Recovered(Value),
+    /// 已生成最终响应（自定义错误、提前完成或处理子链本身失败），链结束执行
+    Responded,
+}
+
+/// 错误处理子链
+pub struct ErrorHandler {
+    chain: Box<ChainExecutor>,
+    recover: bool,
+}
+
+impl ErrorHandler {
+    /// 使用已构建的子链创建错误处理器
+    pub fn new(chain: ChainExecutor, recover: bool) -> Self {
+        Self {
+            chain: Box::new(chain),
+            recover,
+        }
+    }
+
+    /// 从配置创建错误处理器：挂件数组，或包含`chain`和`recover`的对象
+    pub fn from_config(config: &Value) -> Result<Self, String> {
+        let (chain, recover) = match config {
+            Value::Array(_) => (config.clone(), true),
+            Value::Object(obj) => {
+                let chain = obj
+                    .get("chain")
+                    .cloned()
+                    .ok_or_else(|| "on_error配置缺少chain字段".to_string())?;
+                let recover = match obj.get("recover") {
+                    None => true,
+                    Some(Value::Bool(recover)) => *recover,
+                    Some(_) => return Err("on_error的recover必须是布尔类型".to_string()),
+                };
+                (chain, recover)
+            }
+            _ => return Err("on_error配置必须是挂件数组或对象".to_string()),
+        };
+
+        let chain = ChainExecutor::new()
+            .add_chainwares(chain)
+            .map_err(|e| format!("on_error子链配置错误: {}", e.msg))?;
+        Ok(Self::new(chain, recover))
+    }
+
+    /// 是否在子链执行完成后恢复执行
+    pub fn recover(&self) -> bool {
+        self.recover
+    }
+
+    /// 处理器定义，用于计算链定义的哈希值
+    pub fn definition(&self) -> Value {
+        json!({"chain": self.chain.definition(), "recover": self.recover})
+    }
+
+    /// 执行错误处理子链
+    ///
+    /// `response`中为出错节点的错误信息，子链共享链变量，执行后写回响应
+    pub(crate) fn handle(
+        &self,
+        request: &ChainRequest,
+        response: &mut ChainResponse,
+        index: usize,
+        node_name: &str,
+        last_data: Value,
+    ) -> HandlerOutcome {
+        let mut input = json!({
+            "error": response.data.clone().unwrap_or_default(),
+            "node": {"index": index, "name": node_name},
+        });
+        // 出错节点的输入数据直接移入，不复制
+        input["data"] = last_data;
+
+        let sub_response = self.chain.run_nested(request, response, input);
+        match sub_response.status {
+            ChainStatus::Continue if self.recover => {
+                HandlerOutcome::Recovered(sub_response.data.unwrap_or_default())
+            }
+            ChainStatus::Continue => {
+                response.data = sub_response.data;
+                HandlerOutcome::Responded
+            }
+            status => {
+                response.status = status;
+                response.data = sub_response.data;
+                HandlerOutcome::Responded
+            }
+        }
+    }
+}
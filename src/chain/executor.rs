@@ -2,24 +2,97 @@
 //!
 //! 实现简化的链执行器，支持内置挂件和自定义回调
 
+use super::batch::{self, BatchOptions, BatchResult, BatchStream};
+use super::debug::DebugSession;
+use super::error_handler::{ErrorHandler, HandlerOutcome};
+use super::stream::StreamSession;
 use crate::builtin::get_global_registry;
 use crate::chainware::core::{Chainware, ChainwareWrapper, Closureware};
 use crate::chainware::config::ChainwareConfig;
-use crate::core::{ChainStatus, ChainRequest, ChainResponse};
+use crate::core::{stable_hash, ChainCheckpoint, ChainStatus, ChainRequest, ChainResponse, CHECKPOINT_VERSION};
 use crate::types::{ErrorResponse, error_codes};
 use std::collections::HashMap;
+use std::sync::Arc;
+
+/// 恢复执行时外部输入写入的链变量名
+pub const RESUME_INPUT_VAR: &str = "resume_input";
+
+/// 链节点：挂件及其出错处理方式
+struct ChainNode {
+    wrapper: ChainwareWrapper,
+    /// 节点级错误处理子链
+    on_error: Option<ErrorHandler>,
+    /// 出错时忽略错误，使用节点输入数据继续执行
+    continue_on_error: bool,
+}
+
+impl ChainNode {
+    /// 出错时是否需要节点的输入数据
+    fn handles_error(&self) -> bool {
+        self.on_error.is_some() || self.continue_on_error
+    }
+}
 
 /// 链执行器
 /// 简化的链执行器，外部程序创建链后添加挂件然后执行
 pub struct ChainExecutor {
     /// 挂件节点列表
-    nodes: Vec<ChainwareWrapper>,
+    nodes: Vec<ChainNode>,
+    /// 链级错误处理子链，节点未处理的错误由其处理
+    on_error: Option<ErrorHandler>,
 }
 
 impl ChainExecutor {
     /// 创建新的链执行器
     pub fn new() -> Self {
-        Self { nodes: Vec::new() }
+        Self {
+            nodes: Vec::new(),
+            on_error: None,
+        }
+    }
+
+    /// 设置链级错误处理子链
+    ///
+    /// 节点出错且未配置`on_error`或`continue_on_error`时执行，配置格式与节点的`on_error`相同
+    ///
+    /// # 使用方式
+    /// ```ignore
+    /// let executor = ChainExecutor::new()
+    ///     .add_chainwares(configs)?
+    ///     .on_error(json!({"chain": [{"name": "map_fields", "config": {...}}], "recover": false}))?;
+    /// ```
+    pub fn on_error(mut self, handler: serde_json::Value) -> Result<Self, ErrorResponse> {
+        let handler = ErrorHandler::from_config(&handler)
+            .map_err(|msg| ErrorResponse::new(error_codes::CONFIG_ERROR, msg, None))?;
+        self.on_error = Some(handler);
+        Ok(self)
+    }
+
+    /// 从链定义创建执行器
+    ///
+    /// 链定义为挂件配置数组（格式与`add_chainwares`相同），或带链级错误处理子链的对象
+    /// `{"chain": [...], "on_error": ...}`，与 [`definition`](Self::definition) 的输出格式一致。
+    /// 链配置文件、命令行工具和黄金文件测试都通过该方法加载链
+    pub fn from_definition(definition: serde_json::Value) -> Result<Self, ErrorResponse> {
+        let config_error = |msg: String| ErrorResponse::new(error_codes::CONFIG_ERROR, msg, None);
+        match definition {
+            serde_json::Value::Array(_) => Self::new().add_chainwares(definition),
+            serde_json::Value::Object(mut obj) => {
+                let chain = obj
+                    .remove("chain")
+                    .ok_or_else(|| config_error("链定义缺少chain字段".to_string()))?;
+                let on_error = obj.remove("on_error");
+                if let Some(key) = obj.keys().next() {
+                    return Err(config_error(format!("链定义中不支持的字段: {}", key)));
+                }
+                let executor = Self::new().add_chainwares(chain)?;
+                match on_error {
+                    Some(handler) => executor.on_error(handler),
+                    None => Ok(executor),
+                }
+            }
+            _ => Err(config_error("链定义必须是挂件数组或包含chain字段的对象".to_string())),
+        }
     }
 
     /// 插入挂件到指定位置
@@ -85,7 +158,19 @@ impl ChainExecutor {
             }
         };
 
-        let wrapper = ChainwareWrapper::new(chainware, config);
+        let on_error = match config.as_ref().and_then(|cfg| cfg.get_on_error()) {
+            Some(handler) => Some(
+                ErrorHandler::from_config(handler)
+                    .map_err(|msg| ErrorResponse::new(error_codes::CONFIG_ERROR, msg, None))?,
+            ),
+            None => None,
+        };
+        let continue_on_error = config.as_ref().is_some_and(|cfg| cfg.get_continue_on_error());
+        let node = ChainNode {
+            wrapper: ChainwareWrapper::new(chainware, config),
+            on_error,
+            continue_on_error,
+        };
         match position {
             Some(pos) => {
                 let index = if pos >= 0 {
@@ -103,14 +188,14 @@ impl ChainExecutor {
                 
                 // 确保索引不超过当前长度
                 if index <= self.nodes.len() {
-                    self.nodes.insert(index, wrapper);
+                    self.nodes.insert(index, node);
                 } else {
                     // 如果位置超出了范围，则添加到末尾
-                    self.nodes.push(wrapper);
+                    self.nodes.push(node);
                 }
             }
             None => {
-                self.nodes.push(wrapper);
+                self.nodes.push(node);
             }
         }
         Ok(self)
@@ -202,57 +287,404 @@ impl ChainExecutor {
     /// 执行链
     pub fn execute(&self, request: ChainRequest) -> ChainResponse {
         let mut response = ChainResponse::new(request.start_time);
-        // 初始化数据为请求数据
-        let mut params: serde_json::Value = request.params.clone();
-        response.set_data(params.clone());
+        // 初始化数据为请求数据，节点可能修改数据，请求参数保持不变供$params读取
+        let params: serde_json::Value = request.params.clone();
+
+        let data = self.run_nodes(&request, &mut response, params);
+
+        Self::finish_response(&mut response, data);
+        response
+    }
+
+    /// 从检查点恢复执行
+    ///
+    /// 从挂起节点的下一个节点继续执行，检查点中的数据、元数据和链变量被还原，
+    /// `input`（如审批结果、回调数据）写入链变量`resume_input`，后续节点通过`$vars.resume_input`读取
+    ///
+    /// 检查点版本不兼容或与当前链定义不匹配时返回Error
+    ///
+    /// # 使用方式
+    /// ```ignore
+    /// let response = executor.execute(request);
+    /// if response.status == ChainStatus::Suspended {
+    ///     let saved = response.checkpoint.unwrap().to_json();  // 保存到数据库等
+    ///     // ... 在其他进程中
+    ///     let checkpoint = ChainCheckpoint::from_json(saved)?;
+    ///     let response = executor.resume(checkpoint, json!({"approved": true}));
+    /// }
+    /// ```
+    pub fn resume(&self, checkpoint: ChainCheckpoint, input: serde_json::Value) -> ChainResponse {
+        let request = ChainRequest::new(checkpoint.params.clone(), checkpoint.request_meta.clone());
+        let mut response = ChainResponse::new(request.start_time);
+
+        if let Err(err) = self.check_checkpoint(&checkpoint) {
+            response.set_status(ChainStatus::Error);
+            response.set_data(err.to_json());
+            Self::finish_response(&mut response, serde_json::Value::Null);
+            return response;
+        }
+
+        response.meta = checkpoint.meta;
+        response.vars = checkpoint.vars;
+        response.set_var(RESUME_INPUT_VAR.to_string(), input);
+
+        let data = self.run_nodes_from(checkpoint.node_index + 1, &request, &mut response, checkpoint.data);
+
+        Self::finish_response(&mut response, data);
+        response
+    }
+
+    /// 检查检查点能否在当前链上恢复
+    fn check_checkpoint(&self, checkpoint: &ChainCheckpoint) -> Result<(), ErrorResponse> {
+        if checkpoint.version != CHECKPOINT_VERSION {
+            return Err(ErrorResponse::new(
+                error_codes::BAD_REQUEST,
+                format!("检查点版本{}不兼容，当前版本为{}", checkpoint.version, CHECKPOINT_VERSION),
+                None,
+            ));
+        }
+        if checkpoint.chain_hash != self.chain_hash() {
+            return Err(ErrorResponse::new(
+                error_codes::BAD_REQUEST,
+                "检查点与当前链定义不匹配".to_string(),
+                Some(serde_json::json!({"expected": self.chain_hash(), "actual": checkpoint.chain_hash})),
+            ));
+        }
+        if self.node_name(checkpoint.node_index) != Some(checkpoint.node_name.as_str()) {
+            return Err(ErrorResponse::new(
+                error_codes::BAD_REQUEST,
+                format!("检查点中的第{}个挂件 '{}' 不存在", checkpoint.node_index + 1, checkpoint.node_name),
+                None,
+            ));
+        }
+        Ok(())
+    }
+
+    /// 链定义：每个节点的名称和配置，以及链级错误处理子链
+    ///
+    /// 自定义回调挂件只包含名称和配置，回调逻辑变化不会改变链定义
+    pub fn definition(&self) -> serde_json::Value {
+        let nodes: Vec<serde_json::Value> = self
+            .nodes
+            .iter()
+            .map(|node| {
+                // 配置转换为有序的JSON对象，保证相同配置的定义一致
+                let config: serde_json::Map<String, serde_json::Value> = node
+                    .wrapper
+                    .config()
+                    .map(|config| config.config.clone().into_iter().collect())
+                    .unwrap_or_default();
+                serde_json::json!({"name": node.wrapper.name(), "config": config})
+            })
+            .collect();
+
+        match &self.on_error {
+            Some(handler) => serde_json::json!({"chain": nodes, "on_error": handler.definition()}),
+            None => serde_json::Value::Array(nodes),
+        }
+    }
+
+    /// 链定义的哈希值，检查点与其绑定
+    pub fn chain_hash(&self) -> String {
+        stable_hash(&self.definition().to_string())
+    }
+
+    /// 从给定数据开始按顺序执行所有节点，返回最后的数据
+    pub(crate) fn run_nodes(
+        &self,
+        request: &ChainRequest,
+        response: &mut ChainResponse,
+        params: serde_json::Value,
+    ) -> serde_json::Value {
+        self.run_nodes_from(0, request, response, params)
+    }
+
+    /// 从指定位置的节点开始按顺序执行，返回最后的数据
+    fn run_nodes_from(
+        &self,
+        start: usize,
+        request: &ChainRequest,
+        response: &mut ChainResponse,
+        mut params: serde_json::Value,
+    ) -> serde_json::Value {
+        for index in start..self.nodes.len() {
+            // 执行节点，更新数据为当前节点的返回数据
+            params = self.execute_node(index, request, response, params);
+
+            // 根据响应状态判断是否继续执行，错误、拒绝或完成时停止执行
+            if response.status != ChainStatus::Continue {
+                break;
+            }
+        }
+        params
+    }
+
+    /// 作为子链执行：共享父响应的链变量，执行后将变量和meta写回父响应，返回子链的响应
+    ///
+    /// 子链响应的data为子链的输出（Continue）或结束时的数据（Reject、Error、Completed）
+    pub(crate) fn run_nested(
+        &self,
+        request: &ChainRequest,
+        parent: &mut ChainResponse,
+        input: serde_json::Value,
+    ) -> ChainResponse {
+        let mut response = ChainResponse::new(parent.start_time);
+        response.vars = std::mem::take(&mut parent.vars);
 
-        // 按顺序执行所有节点
-        for (index, node) in self.nodes.iter().enumerate() {
-            // 执行节点，获取返回数据
-            let node_result = node.execute(&request, &mut response, Some(params.clone()));
+        let output = self.run_nodes(request, &mut response, input);
+        match response.status {
+            ChainStatus::Continue => response.set_data(output),
+            // 检查点只记录主链的位置，子链中无法恢复
+            ChainStatus::Suspended => {
+                let node_name = response
+                    .checkpoint
+                    .take()
+                    .map(|checkpoint| checkpoint.node_name)
+                    .unwrap_or_default();
+                response.set_status(ChainStatus::Error);
+                response.set_data(
+                    ErrorResponse::new(
+                        error_codes::INTERNAL_ERROR,
+                        format!("子链中的挂件 '{}' 不支持挂起执行", node_name),
+                        None,
+                    )
+                    .to_json(),
+                );
+            }
+            _ => {}
+        }
+
+        parent.vars = std::mem::take(&mut response.vars);
+        parent.meta.extend(response.meta.clone());
+        response
+    }
+
+    /// 挂件节点数量
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// 是否没有挂件节点
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
 
-            // 更新数据为当前节点的返回数据
-            params = node_result.unwrap_or_default();
+    /// 获取指定位置的挂件名称
+    pub fn node_name(&self, index: usize) -> Option<&str> {
+        self.nodes.get(index).map(|node| node.wrapper.name())
+    }
+
+    /// 获取指定位置的挂件包装器
+    pub(crate) fn node_wrapper(&self, index: usize) -> Option<&ChainwareWrapper> {
+        self.nodes.get(index).map(|node| &node.wrapper)
+    }
+
+    /// 执行单个节点，返回节点输出数据，并根据执行状态更新响应
+    pub(crate) fn execute_node(
+        &self,
+        index: usize,
+        request: &ChainRequest,
+        response: &mut ChainResponse,
+        params: serde_json::Value,
+    ) -> serde_json::Value {
+        let node = &self.nodes[index];
+
+        // 配置了错误处理时保留输入数据，出错后交给处理子链或继续执行；未启用的节点不会出错
+        let input = if node.wrapper.is_enabled() && (node.handles_error() || self.on_error.is_some()) {
+            Some(params.clone())
+        } else {
+            None
+        };
+
+        // 响应数据为当前数据，挂件停止执行但未设置响应数据时即为最终数据
+        response.set_data(params.clone());
 
-            // 根据响应状态判断是否继续执行
-            match response.status {
-                ChainStatus::Continue => {
-                    // 继续执行下一个节点
-                    response.set_data(params.clone());
-                    continue;
+        // 执行节点，获取返回数据
+        let params = node.wrapper.execute(request, response, Some(params)).unwrap_or_default();
+
+        match response.status {
+            ChainStatus::Continue => params,
+            ChainStatus::Error => match input {
+                Some(input) => self.handle_error(index, request, response, input, params),
+                None => {
+                    Self::set_error_details(index, node.wrapper.name(), response);
+                    params
                 }
-                ChainStatus::Error | ChainStatus::Reject => {
-                    // 错误或拒绝，停止执行
-                    // 添加详细的错误信息到响应中
-                    if response.status == ChainStatus::Error {
-                        let node_name = node.name();
-                        response.set_meta(
-                            "error_details".to_string(), 
-                            serde_json::json!({
-                                "node_index": index,
-                                "node_name": node_name,
-                                "message": format!("在执行第{}个挂件 '{}' 时发生错误", index + 1, node_name)
-                            })
-                        );
-                    }
-                    break;
+            },
+            ChainStatus::Suspended => {
+                // 挂起，记录检查点，恢复时从下一个节点继续执行
+                response.checkpoint = Some(ChainCheckpoint {
+                    version: CHECKPOINT_VERSION,
+                    chain_hash: self.chain_hash(),
+                    node_index: index,
+                    node_name: node.wrapper.name().to_string(),
+                    data: params.clone(),
+                    params: request.params.clone(),
+                    request_meta: request.meta.clone(),
+                    meta: response.meta.clone(),
+                    vars: response.vars.clone(),
+                });
+                params
+            }
+            ChainStatus::Reject | ChainStatus::Completed => params,
+        }
+    }
+
+    /// 处理节点错误：依次尝试节点的on_error、continue_on_error和链级on_error
+    fn handle_error(
+        &self,
+        index: usize,
+        request: &ChainRequest,
+        response: &mut ChainResponse,
+        input: serde_json::Value,
+        params: serde_json::Value,
+    ) -> serde_json::Value {
+        let node = &self.nodes[index];
+        let node_name = node.wrapper.name();
+
+        let handler = match (&node.on_error, node.continue_on_error) {
+            (Some(handler), _) => handler,
+            (None, true) => {
+                // 忽略错误，使用节点的输入数据继续执行
+                Self::record_recovered(index, node_name, response);
+                response.set_status(ChainStatus::Continue);
+                return input;
+            }
+            (None, false) => match &self.on_error {
+                Some(handler) => handler,
+                None => {
+                    Self::set_error_details(index, node_name, response);
+                    return params;
                 }
-                ChainStatus::Completed => {
-                    // 执行完成，停止执行
-                    break;
+            },
+        };
+
+        let error = response.data.clone();
+        match handler.handle(request, response, index, node_name, input) {
+            HandlerOutcome::Recovered(data) => {
+                response.data = error;
+                Self::record_recovered(index, node_name, response);
+                response.set_status(ChainStatus::Continue);
+                data
+            }
+            HandlerOutcome::Responded => {
+                if response.status == ChainStatus::Error {
+                    Self::set_error_details(index, node_name, response);
                 }
+                response.data.clone().unwrap_or_default()
             }
         }
+    }
 
-        // 如果所有节点都执行完成且状态仍为Continue，则设置为Completed
+    /// 添加详细的错误信息到响应中
+    fn set_error_details(index: usize, node_name: &str, response: &mut ChainResponse) {
+        response.set_meta(
+            "error_details".to_string(),
+            serde_json::json!({
+                "node_index": index,
+                "node_name": node_name,
+                "message": format!("在执行第{}个挂件 '{}' 时发生错误", index + 1, node_name)
+            })
+        );
+    }
+
+    /// 记录已恢复的错误到meta的recovered_errors中，response.data为错误信息
+    fn record_recovered(index: usize, node_name: &str, response: &mut ChainResponse) {
+        let record = serde_json::json!({
+            "node_index": index,
+            "node_name": node_name,
+            "error": response.data.clone().unwrap_or_default()
+        });
+        match response.meta.get_mut("recovered_errors") {
+            Some(serde_json::Value::Array(records)) => records.push(record),
+            _ => response.set_meta("recovered_errors".to_string(), serde_json::json!([record])),
+        }
+    }
+
+    /// 结束执行：所有节点都执行完成且状态仍为Continue时设置为Completed，并记录结束时间
+    pub(crate) fn finish_response(response: &mut ChainResponse, data: serde_json::Value) {
         if response.status == ChainStatus::Continue {
             response.set_status(ChainStatus::Completed);
+            response.set_data(data);
         }
-
-        // 设置执行结束时间
         response.set_end_time();
+    }
 
-        response
+    /// 创建单步调试会话
+    ///
+    /// 会话逐个节点执行链，可在步骤之间查看和修改数据、设置断点，并回退到任意步骤重新执行
+    ///
+    /// # 使用方式
+    /// ```ignore
+    /// let mut session = executor.debug_session(request);
+    /// session.add_breakpoint(Breakpoint::Node("condition".to_string()));
+    /// session.run();                      // 停在condition节点之前
+    /// session.set_data(json!({"age": 20}));
+    /// session.step();                     // 执行condition节点
+    /// println!("{:?}", session.history());
+    /// ```
+    pub fn debug_session(&self, request: ChainRequest) -> DebugSession<'_> {
+        DebugSession::new(self, request)
+    }
+
+    /// 批量并行执行
+    ///
+    /// 在`options.workers`个线程中执行所有请求，响应按请求顺序返回；
+    /// 单个请求的panic只影响该请求，转换为INTERNAL_ERROR的Error响应
+    ///
+    /// # 使用方式
+    /// ```ignore
+    /// let result = executor.execute_batch(requests, &BatchOptions::new().workers(8));
+    /// println!("{}", result.stats.to_json()); // 各状态数量和延迟分位数
+    /// ```
+    pub fn execute_batch(&self, requests: Vec<ChainRequest>, options: &BatchOptions) -> BatchResult {
+        batch::run_batch(self, requests, options.workers)
+    }
+
+    /// 按块流式批量执行
+    ///
+    /// 每次从输入中取出`options.chunk_size`个请求并行执行，迭代返回每块的结果，
+    /// 适合日志文件等大量输入；各块统计可通过 [`BatchStats::merge`](batch::BatchStats::merge) 汇总。
+    /// 工作线程在各块之间复用，线程共享执行器，因此需要通过`Arc`调用
+    ///
+    /// # 使用方式
+    /// ```ignore
+    /// let executor = Arc::new(executor);
+    /// for chunk in executor.execute_stream(requests, BatchOptions::new().chunk_size(500)) {
+    ///     write_results(chunk.responses);
+    /// }
+    /// ```
+    pub fn execute_stream<I>(self: &Arc<Self>, requests: I, options: BatchOptions) -> BatchStream<I::IntoIter>
+    where
+        I: IntoIterator<Item = ChainRequest>,
+    {
+        BatchStream::new(Arc::clone(self), requests.into_iter(), options)
+    }
+
+    /// 创建流式输入会话，流式文本作为整个请求参数
+    ///
+    /// 逐块接收增量文本，支持流式输入的挂件（如`extract_json`）
+    /// 在文本接收完整前提前给出结果，输入结束后以完整文本执行整条链
+    ///
+    /// # 使用方式
+    /// ```ignore
+    /// let mut session = executor.stream_session(request);
+    /// for token in llm_tokens {
+    ///     for event in session.push(&token) {
+    ///         println!("{}: {}", event.node_name, event.value); // 如第一个完整的JSON对象
+    ///     }
+    /// }
+    /// let response = session.finish();
+    /// ```
+    pub fn stream_session(&self, request: ChainRequest) -> StreamSession<'_> {
+        StreamSession::new(self, request, "$").expect("根路径总是有效的写入路径")
+    }
+
+    /// 创建流式输入会话，流式文本写入请求参数的`text_path`位置，如`$.llm_output`
+    ///
+    /// 提前给出结果的挂件需要通过`input_path`读取该位置；路径无效或指向`$vars`时返回BAD_REQUEST错误
+    pub fn stream_session_at(&self, request: ChainRequest, text_path: &str) -> Result<StreamSession<'_>, ErrorResponse> {
+        StreamSession::new(self, request, text_path)
     }
 }
 
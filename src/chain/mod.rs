@@ -1,3 +1,11 @@
+pub mod batch;
+pub mod debug;
+pub mod error_handler;
 pub mod executor;
+pub mod stream;
 
-pub use executor::*; 
\ No newline at end of file
+pub use batch::*;
+pub use debug::*;
+pub use error_handler::*;
+pub use executor::*;
+pub use stream::*;
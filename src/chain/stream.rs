@@ -0,0 +1,163 @@
+//! 流式输入模块
+//!
+//! 逐块接收增量文本（如LLM逐token输出），支持流式输入的挂件在文本接收完整前提前给出结果，
+//! 例如第一个完整的JSON对象；输入结束后以完整文本执行整条链
+
+use super::executor::ChainExecutor;
+use crate::chainware::core::ChainwareWrapper;
+use crate::core::{ChainStatus, ChainRequest, ChainResponse};
+use crate::types::{error_codes, ErrorResponse};
+use crate::utils::json_path::{JsonPathTemplate, PathRoot};
+use serde_json::Value;
+
+/// 挂件提前给出的结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamEvent {
+    /// 节点位置
+    pub index: usize,
+    /// 挂件名称
+    pub node_name: String,
+    /// 挂件基于已接收文本给出的结果
+    pub value: Value,
+}
+
+/// 流式输入会话
+///
+/// 通过 [`ChainExecutor::stream_session`] 创建。链开头连续的、以流式文本为输入的流式挂件
+/// 在每次`push`后尝试给出结果，每个节点只给出一次；其余挂件等待输入结束，在`finish`时执行
+pub struct StreamSession<'a> {
+    executor: &'a ChainExecutor,
+    request: ChainRequest,
+    /// 流式文本在请求参数中的位置，空表示整个请求参数
+    text_tokens: Vec<String>,
+    buffer: String,
+    /// 可以提前给出结果的节点位置
+    streaming_nodes: Vec<usize>,
+    /// 已给出结果的节点位置
+    emitted: Vec<usize>,
+    /// 各流式节点下次开始扫描的位置，与`streaming_nodes`一一对应
+    scan_offsets: Vec<usize>,
+}
+
+impl<'a> StreamSession<'a> {
+    pub(crate) fn new(executor: &'a ChainExecutor, request: ChainRequest, text_path: &str) -> Result<Self, ErrorResponse> {
+        let text_tokens = match JsonPathTemplate::parse_write_path(text_path) {
+            Ok((PathRoot::Input, tokens)) => tokens,
+            Ok((PathRoot::Vars, _)) => {
+                return Err(ErrorResponse::new(
+                    error_codes::BAD_REQUEST,
+                    format!("流式文本路径不能写入链变量: {}", text_path),
+                    None,
+                ))
+            }
+            Err(err) => return Err(ErrorResponse::new(error_codes::BAD_REQUEST, err, None)),
+        };
+
+        let streaming_nodes = Self::streaming_nodes(executor, &text_tokens);
+        let scan_offsets = vec![0; streaming_nodes.len()];
+        Ok(Self {
+            executor,
+            request,
+            text_tokens,
+            buffer: String::new(),
+            streaming_nodes,
+            emitted: Vec::new(),
+            scan_offsets,
+        })
+    }
+
+    /// 查找可以提前给出结果的节点
+    ///
+    /// 从第一个节点开始，节点支持流式输入且`input_path`指向流式文本时可以提前给出结果；
+    /// 遇到不支持流式输入的节点，或节点输出覆盖了流式文本时停止
+    fn streaming_nodes(executor: &ChainExecutor, text_tokens: &[String]) -> Vec<usize> {
+        let path_tokens = |path: Option<&str>| match path.map(JsonPathTemplate::parse_write_path) {
+            None => Some(Vec::new()),
+            Some(Ok((PathRoot::Input, tokens))) => Some(tokens),
+            Some(_) => None,
+        };
+
+        let mut nodes = Vec::new();
+        for index in 0..executor.len() {
+            let Some(wrapper) = executor.node_wrapper(index) else {
+                break;
+            };
+            if !wrapper.is_enabled() {
+                continue;
+            }
+            let config = wrapper.config();
+            let input = path_tokens(config.and_then(|config| config.get_input_path()));
+            if !wrapper.supports_streaming() || input.as_deref() != Some(text_tokens) {
+                break;
+            }
+            nodes.push(index);
+
+            // 未配置output_path时节点结果替换整个数据，之后的节点不再以流式文本为输入
+            let Some(output_path) = config.and_then(|config| config.get_output_path()) else {
+                break;
+            };
+            let overlaps = match JsonPathTemplate::parse_write_path(output_path) {
+                Ok((PathRoot::Input, tokens)) => tokens.starts_with(text_tokens) || text_tokens.starts_with(&tokens),
+                Ok((PathRoot::Vars, _)) => false,
+                Err(_) => true,
+            };
+            if overlaps {
+                break;
+            }
+        }
+        nodes
+    }
+
+    /// 已接收的文本
+    pub fn text(&self) -> &str {
+        &self.buffer
+    }
+
+    /// 接收一块文本，返回本次新给出的结果
+    ///
+    /// 每个节点从上次停止的位置继续扫描，不重复扫描已确定不会产生结果的文本
+    pub fn push(&mut self, chunk: &str) -> Vec<StreamEvent> {
+        self.buffer.push_str(chunk);
+
+        let mut events = Vec::new();
+        for (&index, scan_from) in self.streaming_nodes.iter().zip(&mut self.scan_offsets) {
+            if self.emitted.contains(&index) {
+                continue;
+            }
+            let Some(wrapper) = self.executor.node_wrapper(index) else {
+                continue;
+            };
+            if let Some(value) = wrapper.process_partial(&self.buffer, scan_from) {
+                self.emitted.push(index);
+                events.push(StreamEvent {
+                    index,
+                    node_name: wrapper.name().to_string(),
+                    value,
+                });
+            }
+        }
+        events
+    }
+
+    /// 输入结束，将完整文本写入请求参数后执行整条链
+    pub fn finish(mut self) -> ChainResponse {
+        let text = Value::String(std::mem::take(&mut self.buffer));
+        if self.text_tokens.is_empty() {
+            self.request.params = text;
+        } else {
+            if !self.request.params.is_object() {
+                self.request.params = Value::Object(Default::default());
+            }
+            if let Err(err) = ChainwareWrapper::set_value(&mut self.request.params, &self.text_tokens, text) {
+                let mut response = ChainResponse::new(self.request.start_time);
+                response.set_status(ChainStatus::Error);
+                response.set_data(
+                    ErrorResponse::new(error_codes::BAD_REQUEST, format!("写入流式文本失败: {}", err), None).to_json(),
+                );
+                response.set_end_time();
+                return response;
+            }
+        }
+        self.executor.execute(self.request)
+    }
+}
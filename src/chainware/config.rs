@@ -23,6 +23,31 @@ impl ChainwareConfig {
         self.config.get(key)
     }
 
+    /// 获取执行条件：条件表达式，语法与condition挂件相同，未配置时为None
+    pub fn get_when(&self) -> Option<&serde_json::Value> {
+        self.config.get("when")
+    }
+
+    /// 获取输入路径：节点只处理该路径选取的数据
+    pub fn get_input_path(&self) -> Option<&str> {
+        self.config.get("input_path").and_then(|v| v.as_str())
+    }
+
+    /// 获取输出路径：节点结果写入原数据的该路径，而不是替换整个数据
+    pub fn get_output_path(&self) -> Option<&str> {
+        self.config.get("output_path").and_then(|v| v.as_str())
+    }
+
+    /// 获取错误处理配置：节点出错时执行的子链，未配置时为None
+    pub fn get_on_error(&self) -> Option<&serde_json::Value> {
+        self.config.get("on_error")
+    }
+
+    /// 获取出错后是否继续执行：为true时节点出错后使用其输入数据继续执行后续节点
+    pub fn get_continue_on_error(&self) -> bool {
+        self.config.get("continue_on_error").and_then(|v| v.as_bool()).unwrap_or(false)
+    }
+
     /// 获取启用状态
     pub fn get_enabled(&self) -> bool {
         self.config.get("enabled").unwrap_or(&serde_json::Value::Bool(true)).as_bool().unwrap_or(true)
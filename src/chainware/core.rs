@@ -4,6 +4,11 @@
 
 use crate::chainware::config::ChainwareConfig;
 use crate::core::{ChainStatus, ChainRequest, ChainResponse};
+use crate::types::{error_codes, ErrorResponse};
+use crate::utils::condition;
+use crate::utils::json_path::{JsonPathTemplate, PathRoot};
+use crate::utils::json_pointer;
+use serde_json::Value;
 
 /// 链挂件接口（挂件接口）
 /// 所有挂件都需要实现这个接口
@@ -11,6 +16,21 @@ pub trait Chainware: Send + Sync {
     /// 获取挂件名称
     fn name(&self) -> &str;
 
+    /// 挂件功能描述
+    fn description(&self) -> &str {
+        ""
+    }
+
+    /// 配置参数的JSON Schema，用于生成配置表单和部署前校验，None表示未声明
+    fn config_schema(&self) -> Option<serde_json::Value> {
+        None
+    }
+
+    /// 配置示例，每个示例为一个完整的config对象
+    fn examples(&self) -> Vec<serde_json::Value> {
+        Vec::new()
+    }
+
     /// 处理方法（核心方法）
     /// 挂件保持简单，主要做过滤判断和简单数据处理
     /// 参数：请求上下文、响应上下文、外部数据及上一个挂件返回数据
@@ -23,6 +43,28 @@ pub trait Chainware: Send + Sync {
         data: Option<serde_json::Value>,
         config: Option<&ChainwareConfig>,
     ) -> Option<serde_json::Value>;
+
+    /// 是否支持流式输入，默认不支持
+    ///
+    /// 支持流式输入的挂件在文本尚未接收完整时即可通过`process_partial`给出结果，
+    /// 不支持的挂件等待输入结束后再执行
+    fn supports_streaming(&self, _config: Option<&ChainwareConfig>) -> bool {
+        false
+    }
+
+    /// 基于已接收的部分文本提前给出结果，结果还不能确定时返回None
+    ///
+    /// `text`为已接收的全部文本，`scan_from`为该节点上次扫描停止的位置（字节偏移，初始为0）。
+    /// 挂件可将其推进到之后仍可能产生结果的最早位置，下次接收文本后从该位置继续扫描，
+    /// 避免每次重新扫描全部文本；提前给出的结果必须与完整文本执行的结果一致
+    fn process_partial(
+        &self,
+        _text: &str,
+        _scan_from: &mut usize,
+        _config: Option<&ChainwareConfig>,
+    ) -> Option<serde_json::Value> {
+        None
+    }
 }
 
 /// 挂件包装器
@@ -45,33 +87,150 @@ impl ChainwareWrapper {
         self.node.name()
     }
 
+    /// 获取挂件配置
+    pub fn config(&self) -> Option<&ChainwareConfig> {
+        self.config.as_ref()
+    }
+
     /// 检查挂件是否启用
-    fn is_enabled(&self) -> bool {
+    pub(crate) fn is_enabled(&self) -> bool {
         // 优先检查配置中的启用状态
         self.config
             .as_ref()
             .is_none_or(|config| config.get_enabled())
     }
 
+    /// 是否可以处理流式输入：挂件启用、没有`when`条件且支持流式输入
+    pub(crate) fn supports_streaming(&self) -> bool {
+        let guarded = self.config.as_ref().is_some_and(|config| config.get_when().is_some());
+        self.is_enabled() && !guarded && self.node.supports_streaming(self.config.as_ref())
+    }
+
+    /// 基于已接收的部分文本提前给出挂件结果
+    pub(crate) fn process_partial(&self, text: &str, scan_from: &mut usize) -> Option<Value> {
+        self.node.process_partial(text, scan_from, self.config.as_ref())
+    }
+
+    /// 检查执行条件：`when`表达式基于当前数据求值，未配置时返回true
+    fn check_guard(
+        &self,
+        request: &ChainRequest,
+        response: &ChainResponse,
+        data: Option<&Value>,
+    ) -> Result<bool, String> {
+        let Some(when) = self.config.as_ref().and_then(|config| config.get_when()) else {
+            return Ok(true);
+        };
+        let expression = when
+            .as_str()
+            .ok_or_else(|| "when配置必须是字符串类型".to_string())?;
+
+        let null = Value::Null;
+        let context = JsonPathTemplate::build_path_context(data.unwrap_or(&null), request, response);
+        condition::evaluate(expression, &context)
+            .map_err(|err| format!("when表达式 '{}' 求值失败: {}", expression, err))
+    }
+
     /// 执行挂件处理
+    ///
+    /// 配置了`when`时只在条件满足时执行，否则跳过节点、数据原样透传；
+    /// 配置了`input_path`时挂件只处理该路径选取的数据；配置了`output_path`时挂件结果写入原数据的该路径
+    /// （`$vars.x`写入链变量），其余数据保持不变，节点挂起时同样写回
     pub fn execute(
         &self,
         request: &ChainRequest,
         response: &mut ChainResponse,
         data: Option<serde_json::Value>,
     ) -> Option<serde_json::Value> {
-        if self.is_enabled() {
-            let result = self
-                .node
-                .process(request, response, data, self.config.as_ref());
-            if response.status == ChainStatus::Continue {
-                response.data = result.clone();
+        if !self.is_enabled() {
+            return data;
+        }
+
+        match self.check_guard(request, response, data.as_ref()) {
+            Ok(true) => {}
+            Ok(false) => return data,
+            Err(err) => {
+                Self::config_error(response, err);
+                return None;
             }
-            result
+        }
+
+        let input_path = self.config.as_ref().and_then(|config| config.get_input_path());
+        let output_path = self.config.as_ref().and_then(|config| config.get_output_path());
+
+        // 选取节点输入，未配置output_path时不需要保留原数据
+        let (input, original) = match input_path {
+            Some(path) => {
+                let original = data.unwrap_or_default();
+                let context = JsonPathTemplate::build_path_context(&original, request, response);
+                match JsonPathTemplate::get_path_value(&context, path) {
+                    Ok(value) => (Some(value.unwrap_or_default()), original),
+                    Err(err) => {
+                        Self::config_error(response, format!("input_path '{}' 解析失败: {}", path, err));
+                        return None;
+                    }
+                }
+            }
+            None if output_path.is_some() => (data.clone(), data.unwrap_or_default()),
+            None => (data, Value::Null),
+        };
+
+        let result = self
+            .node
+            .process(request, response, input, self.config.as_ref());
+        // 挂起时同样写回输出路径，检查点保存完整数据；拒绝、出错或结束时返回节点结果
+        if !matches!(response.status, ChainStatus::Continue | ChainStatus::Suspended) {
+            return result;
+        }
+
+        let result = match output_path {
+            Some(path) => match Self::write_output(original, path, result.unwrap_or_default(), response) {
+                Ok(data) => Some(data),
+                Err(err) => {
+                    Self::config_error(response, format!("output_path '{}' 写入失败: {}", path, err));
+                    return None;
+                }
+            },
+            None => result,
+        };
+        result
+    }
+
+    /// 将节点结果写入输出路径，返回写入后的数据
+    fn write_output(
+        mut original: Value,
+        path: &str,
+        result: Value,
+        response: &mut ChainResponse,
+    ) -> Result<Value, String> {
+        let (root, tokens) = JsonPathTemplate::parse_write_path(path)?;
+        match root {
+            PathRoot::Input => {
+                Self::set_value(&mut original, &tokens, result)?;
+            }
+            PathRoot::Vars => {
+                let (name, rest) = tokens.split_first().ok_or_else(|| "缺少变量名".to_string())?;
+                let slot = response.vars.entry(name.clone()).or_default();
+                Self::set_value(slot, rest, result)?;
+            }
+        }
+        Ok(original)
+    }
+
+    /// 设置路径上的值：已存在时替换，不存在时创建中间对象后添加
+    pub(crate) fn set_value(target: &mut Value, tokens: &[String], value: Value) -> Result<(), String> {
+        if json_pointer::get(target, tokens).is_some() {
+            json_pointer::replace(target, tokens, value)
         } else {
-            data
+            json_pointer::add(target, tokens, value, true)
         }
     }
+
+    /// 执行条件或数据路由配置错误
+    fn config_error(response: &mut ChainResponse, msg: String) {
+        response.status = ChainStatus::Error;
+        response.data = Some(ErrorResponse::new(error_codes::CONFIG_ERROR, msg, None).to_json());
+    }
 }
 
 /// 处理器函数类型定义
@@ -0,0 +1,73 @@
+//! 命令行参数解析
+
+use std::collections::{HashMap, HashSet};
+
+/// 需要取值的选项
+const VALUE_OPTIONS: &[&str] = &["input", "meta", "iterations"];
+/// 开关选项
+const FLAG_OPTIONS: &[&str] = &["pretty", "json"];
+
+/// 解析后的命令行参数
+#[derive(Debug, Default)]
+pub(crate) struct Args {
+    positionals: Vec<String>,
+    options: HashMap<String, String>,
+    flags: HashSet<String>,
+}
+
+impl Args {
+    /// 解析参数，支持 `--name value`、`--name=value` 和 `--flag`
+    pub(crate) fn parse(args: &[String]) -> Result<Self, String> {
+        let mut result = Self::default();
+        let mut iter = args.iter();
+
+        while let Some(arg) = iter.next() {
+            let Some(option) = arg.strip_prefix("--") else {
+                result.positionals.push(arg.clone());
+                continue;
+            };
+
+            let (name, inline_value) = match option.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (option, None),
+            };
+
+            if VALUE_OPTIONS.contains(&name) {
+                let value = match inline_value {
+                    Some(value) => value,
+                    None => iter
+                        .next()
+                        .cloned()
+                        .ok_or_else(|| format!("选项 --{} 需要参数值", name))?,
+                };
+                result.options.insert(name.to_string(), value);
+            } else if FLAG_OPTIONS.contains(&name) && inline_value.is_none() {
+                result.flags.insert(name.to_string());
+            } else {
+                return Err(format!("未知选项: {}", arg));
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// 位置参数
+    pub(crate) fn positionals(&self) -> &[String] {
+        &self.positionals
+    }
+
+    /// 第`index`个位置参数
+    pub(crate) fn positional(&self, index: usize) -> Option<&str> {
+        self.positionals.get(index).map(|s| s.as_str())
+    }
+
+    /// 选项值
+    pub(crate) fn option(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(|s| s.as_str())
+    }
+
+    /// 开关是否打开
+    pub(crate) fn flag(&self, name: &str) -> bool {
+        self.flags.contains(name)
+    }
+}
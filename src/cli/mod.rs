@@ -0,0 +1,432 @@
+//! 命令行工具
+//!
+//! 提供 `linkchain` 命令的实现，便于不编写Rust代码即可运行、校验和测试链配置：
+//! - `run`: 使用标准输入或文件中的JSON数据执行链，输出响应
+//! - `validate`: 按挂件的配置Schema校验链配置文件
+//! - `list`: 列出已注册的内置挂件及其配置Schema
+//! - `test`: 执行黄金文件测试用例
+//! - `bench`: 在样例输入上测量链的执行耗时
+
+mod args;
+
+use crate::builtin::get_global_registry;
+use crate::chain::executor::ChainExecutor;
+use crate::core::{ChainStatus, ChainRequest, ChainResponse};
+use crate::harness::ChainHarness;
+use args::Args;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// 退出码：执行成功
+pub const EXIT_OK: i32 = 0;
+/// 退出码：链执行失败、校验失败或测试未通过
+pub const EXIT_FAILURE: i32 = 1;
+/// 退出码：命令行参数或输入错误
+pub const EXIT_USAGE: i32 = 2;
+
+const USAGE: &str = "用法: linkchain <命令> [参数]
+
+命令:
+  run <chain.json> [--input <file>] [--meta <json|file>] [--pretty]
+                                执行链，默认从标准输入读取JSON数据
+  validate <chain.json>...      按挂件的配置Schema校验链配置
+  list [name] [--json]          列出已注册的内置挂件，指定名称时显示配置Schema和示例
+  test <dir>...                 执行黄金文件测试（目录包含chain.json和cases/）
+  bench <chain.json> [--input <file>] [--meta <json|file>] [--iterations <n>]
+                                测量链的执行耗时，输入文件可为单个JSON或每行一个JSON
+  help                          显示帮助信息
+";
+
+/// 命令执行错误：(退出码, 错误信息)
+type CliResult = Result<i32, (i32, String)>;
+
+fn usage_error(msg: impl Into<String>) -> (i32, String) {
+    (EXIT_USAGE, msg.into())
+}
+
+/// 执行命令行，返回退出码
+///
+/// `args` 不包含程序名；`stdin`、`stdout`、`stderr` 可替换为内存缓冲，便于测试
+pub fn run(args: &[String], stdin: &mut dyn Read, stdout: &mut dyn Write, stderr: &mut dyn Write) -> i32 {
+    let Some((command, rest)) = args.split_first() else {
+        let _ = write!(stderr, "{}", USAGE);
+        return EXIT_USAGE;
+    };
+
+    let result = Args::parse(rest).map_err(usage_error).and_then(|args| match command.as_str() {
+        "run" => cmd_run(&args, stdin, stdout),
+        "validate" => cmd_validate(&args, stdout),
+        "list" => cmd_list(&args, stdout),
+        "test" => cmd_test(&args, stdout),
+        "bench" => cmd_bench(&args, stdin, stdout),
+        "help" | "--help" | "-h" => {
+            let _ = write!(stdout, "{}", USAGE);
+            Ok(EXIT_OK)
+        }
+        other => Err(usage_error(format!("未知命令: {}\n\n{}", other, USAGE))),
+    });
+
+    match result {
+        Ok(code) => code,
+        Err((code, msg)) => {
+            let _ = writeln!(stderr, "错误: {}", msg);
+            code
+        }
+    }
+}
+
+/// 读取并解析JSON文件
+fn read_json_file(path: &str) -> Result<Value, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| format!("读取文件失败: {}, {}", path, e))?;
+    serde_json::from_str(&content).map_err(|e| format!("解析JSON失败: {}, {}", path, e))
+}
+
+/// 读取输入文本：`--input`指定文件（`-`表示标准输入），否则读取标准输入
+fn read_input_text(args: &Args, stdin: &mut dyn Read) -> Result<String, String> {
+    match args.option("input") {
+        Some(path) if path != "-" => {
+            std::fs::read_to_string(path).map_err(|e| format!("读取输入文件失败: {}, {}", path, e))
+        }
+        _ => {
+            let mut text = String::new();
+            stdin
+                .read_to_string(&mut text)
+                .map_err(|e| format!("读取标准输入失败: {}", e))?;
+            Ok(text)
+        }
+    }
+}
+
+/// 解析`--meta`：JSON对象文本或JSON文件路径
+fn parse_meta(args: &Args) -> Result<HashMap<String, Value>, String> {
+    let Some(meta) = args.option("meta") else {
+        return Ok(HashMap::new());
+    };
+    let value = if meta.trim_start().starts_with('{') {
+        serde_json::from_str(meta).map_err(|e| format!("解析meta失败: {}", e))?
+    } else {
+        read_json_file(meta)?
+    };
+    match value {
+        Value::Object(obj) => Ok(obj.into_iter().collect()),
+        _ => Err("meta必须是JSON对象".to_string()),
+    }
+}
+
+/// 加载链配置文件：挂件数组，或带链级on_error的链定义对象
+fn load_chain(path: &str) -> Result<ChainExecutor, String> {
+    let definition = read_json_file(path)?;
+    ChainExecutor::from_definition(definition).map_err(|e| format!("{}: {}", path, e.msg))
+}
+
+/// 响应转换为JSON输出
+fn response_to_json(response: &ChainResponse) -> Value {
+    let mut output = json!({
+        "status": response.status.as_str(),
+        "data": response.data,
+        "meta": response.meta,
+        "vars": response.vars,
+        "elapsed_ms": response.end_time.saturating_sub(response.start_time),
+    });
+    // 挂起时输出检查点，保存后可用于恢复执行
+    if let Some(checkpoint) = &response.checkpoint {
+        output["checkpoint"] = checkpoint.to_json();
+    }
+    output
+}
+
+fn cmd_run(args: &Args, stdin: &mut dyn Read, stdout: &mut dyn Write) -> CliResult {
+    let chain_file = args.positional(0).ok_or_else(|| usage_error("run命令需要链配置文件"))?;
+    let executor = load_chain(chain_file).map_err(|e| (EXIT_FAILURE, e))?;
+
+    let text = read_input_text(args, stdin).map_err(usage_error)?;
+    let input: Value = if text.trim().is_empty() {
+        Value::Null
+    } else {
+        serde_json::from_str(&text).map_err(|e| usage_error(format!("解析输入JSON失败: {}", e)))?
+    };
+    let meta = parse_meta(args).map_err(usage_error)?;
+
+    let response = executor.execute(ChainRequest::new(input, meta));
+    let output = response_to_json(&response);
+    let text = if args.flag("pretty") {
+        serde_json::to_string_pretty(&output)
+    } else {
+        serde_json::to_string(&output)
+    }
+    .unwrap_or_default();
+    let _ = writeln!(stdout, "{}", text);
+
+    Ok(if response.status == ChainStatus::Completed {
+        EXIT_OK
+    } else {
+        EXIT_FAILURE
+    })
+}
+
+fn cmd_validate(args: &Args, stdout: &mut dyn Write) -> CliResult {
+    if args.positionals().is_empty() {
+        return Err(usage_error("validate命令需要至少一个链配置文件"));
+    }
+
+    let mut failed = 0;
+    for path in args.positionals() {
+        match validate_chain_file(path) {
+            Ok(count) => {
+                let _ = writeln!(stdout, "✅ {}: {}个挂件", path, count);
+            }
+            Err(errors) => {
+                failed += 1;
+                let _ = writeln!(stdout, "❌ {}", path);
+                for error in errors {
+                    let _ = writeln!(stdout, "   - {}", error);
+                }
+            }
+        }
+    }
+    Ok(if failed == 0 { EXIT_OK } else { EXIT_FAILURE })
+}
+
+/// 校验链配置文件：挂件是否存在，配置是否符合挂件的配置Schema，返回挂件数量或所有错误
+fn validate_chain_file(path: &str) -> Result<usize, Vec<String>> {
+    let definition = read_json_file(path).map_err(|e| vec![e])?;
+    let mut errors = Vec::new();
+    let count = validate_definition(&definition, &mut errors);
+    if errors.is_empty() {
+        Ok(count)
+    } else {
+        Err(errors)
+    }
+}
+
+/// 校验链定义：挂件数组，或带链级on_error的`{"chain": [...], "on_error": ...}`，返回主链挂件数量
+fn validate_definition(definition: &Value, errors: &mut Vec<String>) -> usize {
+    let Some(obj) = definition.as_object() else {
+        return validate_nodes(definition, "", errors);
+    };
+    for key in obj.keys().filter(|key| !matches!(key.as_str(), "chain" | "on_error")) {
+        errors.push(format!("链定义中不支持的字段: {}", key));
+    }
+    let count = match obj.get("chain") {
+        Some(chain) => validate_nodes(chain, "", errors),
+        None => {
+            errors.push("链定义缺少chain字段".to_string());
+            0
+        }
+    };
+    if let Some(handler) = obj.get("on_error") {
+        validate_handler(handler, "链级on_error > ", errors);
+    }
+    count
+}
+
+/// 校验错误处理子链：挂件数组，或包含`chain`的对象
+fn validate_handler(handler: &Value, location: &str, errors: &mut Vec<String>) {
+    match handler.get("chain") {
+        Some(chain) if handler.is_object() => {
+            validate_nodes(chain, location, errors);
+        }
+        _ if handler.is_array() => {
+            validate_nodes(handler, location, errors);
+        }
+        _ => errors.push(format!("{}on_error配置必须是挂件数组或包含chain字段的对象", location)),
+    }
+}
+
+/// 校验挂件数组，`location`为子链所在位置的前缀；递归校验节点的on_error、switch分支和loop循环体
+fn validate_nodes(nodes: &Value, location: &str, errors: &mut Vec<String>) -> usize {
+    let Some(nodes) = nodes.as_array() else {
+        errors.push(format!("{}挂件配置必须是数组", location));
+        return 0;
+    };
+
+    let registry = get_global_registry();
+    for (index, node) in nodes.iter().enumerate() {
+        let Some(obj) = node.as_object() else {
+            errors.push(format!("{}第{}个挂件配置必须是对象", location, index + 1));
+            continue;
+        };
+        let Some(name) = obj.get("name").and_then(|n| n.as_str()) else {
+            errors.push(format!("{}第{}个挂件配置缺少name字段", location, index + 1));
+            continue;
+        };
+        if registry.get_type(name).is_none() {
+            errors.push(format!("{}第{}个挂件: 未找到内置挂件: {}", location, index + 1, name));
+            continue;
+        }
+        let config = obj.get("config").cloned().unwrap_or_else(|| json!({}));
+        let node_location = format!("{}第{}个挂件 '{}'", location, index + 1, name);
+        if let Err(node_errors) = registry.validate_config(name, &config) {
+            errors.extend(node_errors.into_iter().map(|e| format!("{}: {}", node_location, e)));
+        }
+
+        // 子链
+        if let Some(handler) = config.get("on_error") {
+            validate_handler(handler, &format!("{} 的on_error > ", node_location), errors);
+        }
+        match name.to_lowercase().as_str() {
+            "switch" => {
+                match config.get("cases") {
+                    Some(Value::Object(cases)) => {
+                        for (case, chain) in cases {
+                            validate_nodes(chain, &format!("{} 的分支 '{}' > ", node_location, case), errors);
+                        }
+                    }
+                    Some(Value::Array(cases)) => {
+                        for (case_index, case) in cases.iter().enumerate() {
+                            if let Some(chain) = case.get("chain") {
+                                let location = format!("{} 的第{}个分支 > ", node_location, case_index + 1);
+                                validate_nodes(chain, &location, errors);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+                if let Some(chain) = config.get("default") {
+                    validate_nodes(chain, &format!("{} 的default > ", node_location), errors);
+                }
+            }
+            "loop" => {
+                if let Some(chain) = config.get("chain") {
+                    validate_nodes(chain, &format!("{} 的循环体 > ", node_location), errors);
+                }
+            }
+            _ => {}
+        }
+    }
+    nodes.len()
+}
+
+fn cmd_list(args: &Args, stdout: &mut dyn Write) -> CliResult {
+    let registry = get_global_registry();
+
+    // 指定名称时输出该挂件的描述、配置Schema和示例
+    if let Some(name) = args.positional(0) {
+        let info = registry
+            .describe(name)
+            .ok_or_else(|| (EXIT_FAILURE, format!("未找到内置挂件: {}", name)))?;
+        if args.flag("json") {
+            let _ = writeln!(stdout, "{}", serde_json::to_string_pretty(&info.to_json()).unwrap_or_default());
+            return Ok(EXIT_OK);
+        }
+        let _ = writeln!(stdout, "{} - {}", info.name, info.description);
+        let _ = writeln!(stdout, "\n配置Schema:");
+        let _ = writeln!(stdout, "{}", serde_json::to_string_pretty(&info.config_schema).unwrap_or_default());
+        if !info.examples.is_empty() {
+            let _ = writeln!(stdout, "\n配置示例:");
+            for example in &info.examples {
+                let _ = writeln!(stdout, "{}", example);
+            }
+        }
+        return Ok(EXIT_OK);
+    }
+
+    let infos = registry.describe_all();
+    if args.flag("json") {
+        let infos: Vec<Value> = infos.iter().map(|info| info.to_json()).collect();
+        let _ = writeln!(stdout, "{}", serde_json::to_string_pretty(&infos).unwrap_or_default());
+    } else {
+        let width = infos.iter().map(|info| info.name.len()).max().unwrap_or(0);
+        for info in infos {
+            let _ = writeln!(stdout, "{:width$}  {}", info.name, info.description, width = width);
+        }
+    }
+    Ok(EXIT_OK)
+}
+
+fn cmd_test(args: &Args, stdout: &mut dyn Write) -> CliResult {
+    if args.positionals().is_empty() {
+        return Err(usage_error("test命令需要至少一个测试目录"));
+    }
+
+    let mut success = true;
+    for dir in args.positionals() {
+        let _ = writeln!(stdout, "🧪 {}", dir);
+        let harness = ChainHarness::from_dir(Path::new(dir)).map_err(|e| (EXIT_FAILURE, e))?;
+        let report = harness.run();
+        let _ = writeln!(stdout, "{}", report);
+        success &= report.is_success();
+    }
+    Ok(if success { EXIT_OK } else { EXIT_FAILURE })
+}
+
+/// bench命令的最大总执行次数（迭代次数 × 样例数）
+const MAX_BENCH_RUNS: usize = 1_000_000;
+
+fn cmd_bench(args: &Args, stdin: &mut dyn Read, stdout: &mut dyn Write) -> CliResult {
+    let chain_file = args.positional(0).ok_or_else(|| usage_error("bench命令需要链配置文件"))?;
+    let executor = load_chain(chain_file).map_err(|e| (EXIT_FAILURE, e))?;
+    let iterations: usize = match args.option("iterations") {
+        Some(n) => n
+            .parse()
+            .ok()
+            .filter(|n| *n > 0)
+            .ok_or_else(|| usage_error(format!("无效的iterations: {}", n)))?,
+        None => 1000,
+    };
+
+    let samples = parse_samples(&read_input_text(args, stdin).map_err(usage_error)?).map_err(usage_error)?;
+    let meta = parse_meta(args).map_err(usage_error)?;
+
+    // 每次执行记录一个耗时，总执行次数限制在MAX_BENCH_RUNS以内
+    let runs = iterations
+        .checked_mul(samples.len())
+        .filter(|runs| *runs <= MAX_BENCH_RUNS)
+        .ok_or_else(|| {
+            usage_error(format!("iterations过大: {}，总执行次数不能超过{}", iterations, MAX_BENCH_RUNS))
+        })?;
+    let mut durations = Vec::with_capacity(runs);
+    let mut statuses: HashMap<&'static str, usize> = HashMap::new();
+    for _ in 0..iterations {
+        for sample in &samples {
+            let request = ChainRequest::new(sample.clone(), meta.clone());
+            let start = Instant::now();
+            let response = executor.execute(request);
+            durations.push(start.elapsed());
+            *statuses.entry(response.status.as_str()).or_default() += 1;
+        }
+    }
+
+    durations.sort();
+    let total: Duration = durations.iter().sum();
+    let percentile = |p: f64| durations[((durations.len() - 1) as f64 * p).round() as usize];
+    let micros = |d: Duration| d.as_secs_f64() * 1_000_000.0;
+
+    let _ = writeln!(stdout, "链配置: {}", chain_file);
+    let _ = writeln!(stdout, "样例数: {}，迭代次数: {}，总执行次数: {}", samples.len(), iterations, durations.len());
+    let _ = writeln!(
+        stdout,
+        "平均: {:.1}µs  p50: {:.1}µs  p95: {:.1}µs  p99: {:.1}µs  最大: {:.1}µs",
+        total.as_secs_f64() * 1_000_000.0 / durations.len() as f64,
+        micros(percentile(0.5)),
+        micros(percentile(0.95)),
+        micros(percentile(0.99)),
+        micros(durations[durations.len() - 1]),
+    );
+    let _ = writeln!(stdout, "吞吐量: {:.0} 次/秒", durations.len() as f64 / total.as_secs_f64().max(f64::EPSILON));
+    let mut statuses: Vec<_> = statuses.into_iter().collect();
+    statuses.sort();
+    let statuses: Vec<String> = statuses.iter().map(|(s, n)| format!("{}={}", s, n)).collect();
+    let _ = writeln!(stdout, "状态分布: {}", statuses.join(", "));
+    Ok(EXIT_OK)
+}
+
+/// 解析样例输入：整个文本为一个JSON值，或每行一个JSON值
+fn parse_samples(text: &str) -> Result<Vec<Value>, String> {
+    if text.trim().is_empty() {
+        return Ok(vec![Value::Null]);
+    }
+    if let Ok(value) = serde_json::from_str(text) {
+        return Ok(vec![value]);
+    }
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line).map_err(|e| format!("解析第{}行输入失败: {}", index + 1, e))
+        })
+        .collect()
+}
@@ -0,0 +1,48 @@
+//! 执行检查点模块
+//!
+//! 定义链挂起时保存的执行状态，可序列化后在其他进程中恢复执行
+
+use crate::types::{error_codes, ErrorResponse};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 检查点格式版本，格式不兼容地变更时递增
+pub const CHECKPOINT_VERSION: u32 = 1;
+
+/// 执行检查点
+/// 节点返回Suspended状态时由执行器生成，恢复时从挂起节点的下一个节点继续执行
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChainCheckpoint {
+    /// 检查点格式版本
+    pub version: u32,
+    /// 链定义的哈希值，只能在相同定义的链上恢复
+    pub chain_hash: String,
+    /// 挂起节点的位置
+    pub node_index: usize,
+    /// 挂起节点的名称
+    pub node_name: String,
+    /// 挂起节点的输出数据，作为下一个节点的输入
+    pub data: serde_json::Value,
+    /// 请求参数
+    pub params: serde_json::Value,
+    /// 请求元数据
+    pub request_meta: HashMap<String, serde_json::Value>,
+    /// 响应元数据
+    pub meta: HashMap<String, serde_json::Value>,
+    /// 链变量
+    pub vars: HashMap<String, serde_json::Value>,
+}
+
+impl ChainCheckpoint {
+    /// 转换为JSON值
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+
+    /// 从JSON值解析检查点
+    pub fn from_json(value: serde_json::Value) -> Result<Self, ErrorResponse> {
+        serde_json::from_value(value).map_err(|e| {
+            ErrorResponse::new(error_codes::BAD_REQUEST, format!("解析检查点失败: {}", e), None)
+        })
+    }
+}
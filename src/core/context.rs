@@ -2,6 +2,7 @@
 //!
 //! 定义请求上下文和响应上下文
 
+use crate::core::checkpoint::ChainCheckpoint;
 use crate::core::status::ChainStatus;
 use crate::core::utils;
 use std::collections::HashMap;
@@ -62,6 +63,10 @@ pub struct ChainResponse {
     pub data: Option<serde_json::Value>,
     /// 响应元数据
     pub meta: HashMap<String, serde_json::Value>,
+    /// 链变量，节点之间共享的可写数据，挂件通过`$vars.x`读取
+    pub vars: HashMap<String, serde_json::Value>,
+    /// 执行检查点，状态为Suspended时用于恢复执行
+    pub checkpoint: Option<ChainCheckpoint>,
     /// 执行开始时间
     pub start_time: u64,
     /// 执行结束时间
@@ -75,6 +80,8 @@ impl ChainResponse {
             status: ChainStatus::Continue,
             data: None,
             meta: HashMap::new(),
+            vars: HashMap::new(),
+            checkpoint: None,
             start_time,
             end_time: utils::current_timestamp_ms(),
         }
@@ -95,6 +102,16 @@ impl ChainResponse {
         self.meta.insert(key, value);
     }
 
+    /// 设置链变量
+    pub fn set_var(&mut self, key: String, value: serde_json::Value) {
+        self.vars.insert(key, value);
+    }
+
+    /// 获取链变量
+    pub fn get_var(&self, key: &str) -> Option<&serde_json::Value> {
+        self.vars.get(key)
+    }
+
     pub fn set_start_time(&mut self, start_time: u64) {
         self.start_time = start_time;
     }
@@ -1,7 +1,9 @@
 pub mod status;
 pub mod context;
+pub mod checkpoint;
 pub mod utils;
 
 pub use status::*;
 pub use context::*;
+pub use checkpoint::*;
 pub use utils::*; 
\ No newline at end of file
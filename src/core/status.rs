@@ -14,4 +14,35 @@ pub enum ChainStatus {
     Error,
     /// 拒绝执行
     Reject,
-}
\ No newline at end of file
+    /// 挂起执行，等待人工审批或外部回调后通过检查点恢复
+    Suspended,
+}
+
+impl ChainStatus {
+    /// 状态名称（小写）
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChainStatus::Continue => "continue",
+            ChainStatus::Completed => "completed",
+            ChainStatus::Error => "error",
+            ChainStatus::Reject => "reject",
+            ChainStatus::Suspended => "suspended",
+        }
+    }
+}
+
+impl std::str::FromStr for ChainStatus {
+    type Err = String;
+
+    /// 从状态名称解析，不区分大小写
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "continue" => Ok(ChainStatus::Continue),
+            "completed" => Ok(ChainStatus::Completed),
+            "error" => Ok(ChainStatus::Error),
+            "reject" => Ok(ChainStatus::Reject),
+            "suspended" => Ok(ChainStatus::Suspended),
+            other => Err(format!("未知的执行状态: {}", other)),
+        }
+    }
+}
@@ -31,4 +31,15 @@ pub fn current_timestamp_ms() -> u64 {
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
         .as_millis() as u64
-} 
\ No newline at end of file
+}
+
+/// 计算稳定的哈希值（FNV-1a 64位，十六进制），结果不随进程和编译器版本变化
+pub fn stable_hash(text: &str) -> String {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    let hash = text
+        .bytes()
+        .fold(OFFSET_BASIS, |hash, byte| (hash ^ byte as u64).wrapping_mul(PRIME));
+    format!("{:016x}", hash)
+}
@@ -0,0 +1,357 @@
+//! 黄金文件测试
+//!
+//! 从目录加载链配置和测试用例，逐个执行并生成可读的差异报告。
+//!
+//! 目录结构：
+//! ```text
+//! pipeline/
+//!   chain.json          # 链定义：挂件配置数组，或带链级on_error的 {"chain": [...], "on_error": ...}，
+//!                       # 格式与 ChainExecutor::from_definition 相同
+//!   cases/
+//!     adult.json        # 测试用例，文件名作为用例名称
+//!     minor.json
+//! ```
+//!
+//! 用例格式：
+//! ```json
+//! {
+//!   "params": {"age": 20},
+//!   "meta": {"ip_address": "127.0.0.1"},
+//!   "expect": {
+//!     "status": "completed",
+//!     "data": {"age": 20, "adult": true},
+//!     "assertions": {"$.adult": true, "$.items.length": 2},
+//!     "errno": 403
+//!   }
+//! }
+//! ```
+
+use crate::chain::executor::ChainExecutor;
+use crate::core::{ChainStatus, ChainRequest, ChainResponse};
+use crate::utils::json_path::JsonPathTemplate;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+/// 用例期望结果，未配置的项不检查
+#[derive(Debug, Clone, Default)]
+pub struct Expectation {
+    /// 期望的执行状态
+    pub status: Option<ChainStatus>,
+    /// 期望的完整输出数据
+    pub data: Option<Value>,
+    /// JSONPath断言：路径 -> 期望值
+    pub assertions: Vec<(String, Value)>,
+    /// 期望的错误码
+    pub errno: Option<i64>,
+}
+
+/// 黄金文件测试用例
+#[derive(Debug, Clone)]
+pub struct GoldenCase {
+    /// 用例名称
+    pub name: String,
+    /// 请求参数
+    pub params: Value,
+    /// 请求元数据
+    pub meta: HashMap<String, Value>,
+    /// 期望结果
+    pub expect: Expectation,
+}
+
+impl GoldenCase {
+    /// 从JSON解析用例，JSON中的`name`优先于传入的名称
+    pub fn from_json(name: &str, value: &Value) -> Result<Self, String> {
+        let obj = value
+            .as_object()
+            .ok_or_else(|| format!("用例 '{}' 必须是对象", name))?;
+        let name = obj.get("name").and_then(|v| v.as_str()).unwrap_or(name).to_string();
+
+        let meta = match obj.get("meta") {
+            None | Some(Value::Null) => HashMap::new(),
+            Some(Value::Object(meta)) => meta.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+            Some(_) => return Err(format!("用例 '{}' 的meta必须是对象", name)),
+        };
+
+        let mut expect = Expectation::default();
+        match obj.get("expect") {
+            None => {}
+            Some(Value::Object(e)) => {
+                if let Some(status) = e.get("status") {
+                    let status = status
+                        .as_str()
+                        .ok_or_else(|| format!("用例 '{}' 的expect.status必须是字符串", name))?;
+                    expect.status = Some(status.parse().map_err(|err| format!("用例 '{}': {}", name, err))?);
+                }
+                expect.data = e.get("data").cloned();
+                match e.get("assertions") {
+                    None => {}
+                    Some(Value::Object(assertions)) => {
+                        expect.assertions = assertions.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+                    }
+                    Some(_) => return Err(format!("用例 '{}' 的expect.assertions必须是对象", name)),
+                }
+                if let Some(errno) = e.get("errno") {
+                    expect.errno =
+                        Some(errno.as_i64().ok_or_else(|| format!("用例 '{}' 的expect.errno必须是整数", name))?);
+                }
+            }
+            Some(_) => return Err(format!("用例 '{}' 的expect必须是对象", name)),
+        }
+
+        Ok(Self {
+            name,
+            params: obj.get("params").cloned().unwrap_or(Value::Null),
+            meta,
+            expect,
+        })
+    }
+
+    /// 检查响应是否符合期望，返回所有不符合项
+    fn check(&self, request: &ChainRequest, response: &ChainResponse) -> Vec<String> {
+        let mut failures = Vec::new();
+        let actual = response.data.clone().unwrap_or(Value::Null);
+
+        if let Some(status) = &self.expect.status {
+            if &response.status != status {
+                failures.push(format!("状态: 期望 {}，实际 {}", status.as_str(), response.status.as_str()));
+            }
+        }
+
+        if let Some(errno) = self.expect.errno {
+            match actual.get("errno").and_then(|v| v.as_i64()) {
+                Some(actual_errno) if actual_errno == errno => {}
+                Some(actual_errno) => failures.push(format!("错误码: 期望 {}，实际 {}", errno, actual_errno)),
+                None => failures.push(format!("错误码: 期望 {}，实际输出没有errno", errno)),
+            }
+        }
+
+        if let Some(expected) = &self.expect.data {
+            let mut diffs = Vec::new();
+            diff_values("$", expected, &actual, &mut diffs);
+            failures.extend(diffs.into_iter().map(|d| format!("数据 {}", d)));
+        }
+
+        if !self.expect.assertions.is_empty() {
+            let context = JsonPathTemplate::build_path_context(&actual, request, response);
+            for (path, expected) in &self.expect.assertions {
+                match JsonPathTemplate::get_path_value(&context, path) {
+                    Ok(value) => {
+                        let value = value.unwrap_or(Value::Null);
+                        if &value != expected {
+                            failures.push(format!("断言 {}: 期望 {}，实际 {}", path, expected, value));
+                        }
+                    }
+                    Err(err) => failures.push(format!("断言 {}: 路径错误: {}", path, err)),
+                }
+            }
+        }
+
+        failures
+    }
+}
+
+/// 递归比较两个JSON值，记录所有差异的路径
+fn diff_values(path: &str, expected: &Value, actual: &Value, diffs: &mut Vec<String>) {
+    match (expected, actual) {
+        (Value::Object(e), Value::Object(a)) => {
+            for (key, e_value) in e {
+                let child = format!("{}.{}", path, key);
+                match a.get(key) {
+                    Some(a_value) => diff_values(&child, e_value, a_value, diffs),
+                    None => diffs.push(format!("{}: 缺少字段，期望 {}", child, e_value)),
+                }
+            }
+            for (key, a_value) in a {
+                if !e.contains_key(key) {
+                    diffs.push(format!("{}.{}: 多余字段，实际 {}", path, key, a_value));
+                }
+            }
+        }
+        (Value::Array(e), Value::Array(a)) => {
+            for (index, e_value) in e.iter().enumerate() {
+                let child = format!("{}[{}]", path, index);
+                match a.get(index) {
+                    Some(a_value) => diff_values(&child, e_value, a_value, diffs),
+                    None => diffs.push(format!("{}: 缺少元素，期望 {}", child, e_value)),
+                }
+            }
+            for (index, a_value) in a.iter().enumerate().skip(e.len()) {
+                diffs.push(format!("{}[{}]: 多余元素，实际 {}", path, index, a_value));
+            }
+        }
+        _ if expected != actual => diffs.push(format!("{}: 期望 {}，实际 {}", path, expected, actual)),
+        _ => {}
+    }
+}
+
+/// 单个用例的执行结果
+#[derive(Debug, Clone)]
+pub struct CaseResult {
+    /// 用例名称
+    pub name: String,
+    /// 实际执行状态
+    pub status: ChainStatus,
+    /// 实际输出数据
+    pub data: Option<Value>,
+    /// 不符合期望的项，为空表示通过
+    pub failures: Vec<String>,
+}
+
+impl CaseResult {
+    /// 是否通过
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// 测试报告
+#[derive(Debug, Clone, Default)]
+pub struct HarnessReport {
+    /// 所有用例的执行结果
+    pub results: Vec<CaseResult>,
+}
+
+impl HarnessReport {
+    /// 通过的用例数量
+    pub fn passed(&self) -> usize {
+        self.results.iter().filter(|r| r.passed()).count()
+    }
+
+    /// 失败的用例数量
+    pub fn failed(&self) -> usize {
+        self.results.len() - self.passed()
+    }
+
+    /// 是否全部通过
+    pub fn is_success(&self) -> bool {
+        self.failed() == 0
+    }
+
+    /// 存在失败用例时panic并输出报告，便于在`#[test]`中使用
+    pub fn assert_success(&self) {
+        assert!(self.is_success(), "\n{}", self);
+    }
+}
+
+impl fmt::Display for HarnessReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for result in &self.results {
+            if result.passed() {
+                writeln!(f, "✅ {}", result.name)?;
+                continue;
+            }
+            writeln!(f, "❌ {}", result.name)?;
+            for failure in &result.failures {
+                writeln!(f, "   - {}", failure)?;
+            }
+            writeln!(
+                f,
+                "   实际输出: [{}] {}",
+                result.status.as_str(),
+                result.data.clone().unwrap_or(Value::Null)
+            )?;
+        }
+        write!(
+            f,
+            "共 {} 个用例，通过 {} 个，失败 {} 个",
+            self.results.len(),
+            self.passed(),
+            self.failed()
+        )
+    }
+}
+
+/// 黄金文件测试执行器
+pub struct ChainHarness {
+    executor: ChainExecutor,
+    cases: Vec<GoldenCase>,
+}
+
+impl ChainHarness {
+    /// 使用已构建的链执行器创建测试执行器
+    pub fn new(executor: ChainExecutor) -> Self {
+        Self {
+            executor,
+            cases: Vec::new(),
+        }
+    }
+
+    /// 从目录加载：`chain.json` 为链配置，`cases/` 下的每个JSON文件为一个用例（或用例数组）
+    pub fn from_dir<P: AsRef<Path>>(dir: P) -> Result<Self, String> {
+        let dir = dir.as_ref();
+        Self::load(dir.join("chain.json"), dir.join("cases"))
+    }
+
+    /// 加载链配置文件和用例目录
+    pub fn load<P: AsRef<Path>, Q: AsRef<Path>>(chain_file: P, cases_dir: Q) -> Result<Self, String> {
+        let chain = read_json(chain_file.as_ref())?;
+        let executor = ChainExecutor::from_definition(chain).map_err(|e| format!("链配置错误: {}", e.msg))?;
+
+        let mut harness = Self::new(executor);
+        harness.load_cases(cases_dir)?;
+        Ok(harness)
+    }
+
+    /// 加载目录下所有用例文件，按文件名排序
+    pub fn load_cases<P: AsRef<Path>>(&mut self, dir: P) -> Result<&mut Self, String> {
+        let dir = dir.as_ref();
+        let mut files: Vec<_> = std::fs::read_dir(dir)
+            .map_err(|e| format!("读取用例目录失败: {}, {}", dir.display(), e))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        files.sort();
+
+        for file in files {
+            let stem = file.file_stem().and_then(|s| s.to_str()).unwrap_or_default().to_string();
+            match read_json(&file)? {
+                Value::Array(cases) => {
+                    for (index, case) in cases.iter().enumerate() {
+                        self.cases.push(GoldenCase::from_json(&format!("{}#{}", stem, index + 1), case)?);
+                    }
+                }
+                case => self.cases.push(GoldenCase::from_json(&stem, &case)?),
+            }
+        }
+        Ok(self)
+    }
+
+    /// 添加用例
+    pub fn add_case(&mut self, case: GoldenCase) -> &mut Self {
+        self.cases.push(case);
+        self
+    }
+
+    /// 所有用例
+    pub fn cases(&self) -> &[GoldenCase] {
+        &self.cases
+    }
+
+    /// 执行所有用例并生成报告
+    pub fn run(&self) -> HarnessReport {
+        let results = self
+            .cases
+            .iter()
+            .map(|case| {
+                let request = ChainRequest::new(case.params.clone(), case.meta.clone());
+                let response = self.executor.execute(request.clone());
+                CaseResult {
+                    name: case.name.clone(),
+                    failures: case.check(&request, &response),
+                    status: response.status,
+                    data: response.data,
+                }
+            })
+            .collect();
+        HarnessReport { results }
+    }
+}
+
+/// 读取并解析JSON文件
+fn read_json(path: &Path) -> Result<Value, String> {
+    let content =
+        std::fs::read_to_string(path).map_err(|e| format!("读取文件失败: {}, {}", path.display(), e))?;
+    serde_json::from_str(&content).map_err(|e| format!("解析JSON失败: {}, {}", path.display(), e))
+}
@@ -0,0 +1,3 @@
+pub mod golden;
+
+pub use golden::*;
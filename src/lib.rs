@@ -8,6 +8,10 @@ pub mod chain;
 pub mod builtin;
 pub mod types;
 pub mod utils;
+pub mod harness;
+pub mod cli;
+#[cfg(feature = "tower")]
+pub mod service;
 
 // 只公开核心API
 pub use chain::ChainExecutor;
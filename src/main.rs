@@ -0,0 +1,11 @@
+//! linkchain 命令行工具
+//!
+//! 运行、校验和测试链配置，详见 `linkchain help`
+
+use std::io;
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let code = linkchain::cli::run(&args, &mut io::stdin(), &mut io::stdout(), &mut io::stderr());
+    std::process::exit(code);
+}
@@ -0,0 +1,236 @@
+//! tower中间件
+//!
+//! 在内部服务之前执行请求链，之后可选执行响应链
+
+use super::mapping::{self, BodyMapper, ClientAddr, StatusMapper};
+use crate::chain::executor::ChainExecutor;
+use crate::core::{ChainStatus, ChainRequest, ChainResponse};
+use bytes::Bytes;
+use http::{header, HeaderValue, Request, Response};
+use http_body::Body;
+use http_body_util::{BodyExt, Full};
+use serde_json::Value;
+use std::fmt::Display;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// 中间件配置，由层和所有服务实例共享
+#[derive(Clone)]
+struct ServiceOptions {
+    request_chain: Option<Arc<ChainExecutor>>,
+    response_chain: Option<Arc<ChainExecutor>>,
+    status_mapper: StatusMapper,
+    body_mapper: BodyMapper,
+    client_addr: ClientAddr,
+}
+
+/// 链中间件层
+///
+/// 配置格式：
+/// ```ignore
+/// let layer = ChainLayer::new()
+///     .request_chain(request_executor)     // 内部服务之前执行，Completed时以输出作为请求体转发
+///     .response_chain(response_executor)   // 内部服务之后执行，Completed时以输出作为响应体
+///     .status_mapper(|response| StatusCode::UNAUTHORIZED)
+///     .body_mapper(|response| json!({"error": response.data}))
+///     .client_addr(|parts| parts.extensions.get::<ConnectInfo<SocketAddr>>().map(|info| info.0.ip()));
+/// let service = ServiceBuilder::new().layer(layer).service(inner);
+/// ```
+///
+/// 链没有正常完成（Reject、Error、Suspended）时不再继续处理，
+/// 按`status_mapper`和`body_mapper`（默认为 [`mapping::default_status`] 和 [`mapping::default_body`]）返回JSON响应
+#[derive(Clone)]
+pub struct ChainLayer {
+    options: ServiceOptions,
+}
+
+impl Default for ChainLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChainLayer {
+    pub fn new() -> Self {
+        Self {
+            options: ServiceOptions {
+                request_chain: None,
+                response_chain: None,
+                status_mapper: Arc::new(mapping::default_status),
+                body_mapper: Arc::new(mapping::default_body),
+                client_addr: Arc::new(mapping::default_client_addr),
+            },
+        }
+    }
+
+    /// 设置请求链
+    ///
+    /// 输入为请求体（JSON，不是JSON时为文本，空请求体为null），meta为请求头、方法、路径、查询参数和客户端IP；
+    /// 正常完成时链的输出作为请求体转发给内部服务，链的响应写入请求扩展，内部服务可通过
+    /// `request.extensions().get::<ChainResponse>()`读取meta和链变量
+    pub fn request_chain(mut self, executor: impl Into<Arc<ChainExecutor>>) -> Self {
+        self.options.request_chain = Some(executor.into());
+        self
+    }
+
+    /// 设置响应链
+    ///
+    /// 输入为内部服务的响应体，meta为请求的meta加上`status`和`response_headers`；
+    /// 正常完成时链的输出作为响应体，状态码和其余响应头保持不变；响应体不是UTF-8文本时不执行
+    pub fn response_chain(mut self, executor: impl Into<Arc<ChainExecutor>>) -> Self {
+        self.options.response_chain = Some(executor.into());
+        self
+    }
+
+    /// 设置链没有正常完成时的HTTP状态码映射
+    pub fn status_mapper<F>(mut self, mapper: F) -> Self
+    where
+        F: Fn(&ChainResponse) -> http::StatusCode + Send + Sync + 'static,
+    {
+        self.options.status_mapper = Arc::new(mapper);
+        self
+    }
+
+    /// 设置链没有正常完成时的响应体映射
+    pub fn body_mapper<F>(mut self, mapper: F) -> Self
+    where
+        F: Fn(&ChainResponse) -> Value + Send + Sync + 'static,
+    {
+        self.options.body_mapper = Arc::new(mapper);
+        self
+    }
+
+    /// 设置客户端IP的提取方式，结果写入meta的`ip_address`
+    ///
+    /// 默认读取请求扩展中的`SocketAddr`（[`mapping::default_client_addr`]）；axum通过
+    /// `into_make_service_with_connect_info::<SocketAddr>()`写入的是`ConnectInfo<SocketAddr>`，需要在此读取。
+    /// 同名的`ip_address`请求头始终被丢弃，客户端无法伪造该字段
+    pub fn client_addr<F>(mut self, extractor: F) -> Self
+    where
+        F: Fn(&http::request::Parts) -> Option<std::net::IpAddr> + Send + Sync + 'static,
+    {
+        self.options.client_addr = Arc::new(extractor);
+        self
+    }
+}
+
+impl<S> Layer<S> for ChainLayer {
+    type Service = ChainService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ChainService {
+            inner,
+            options: Arc::new(self.options.clone()),
+        }
+    }
+}
+
+/// 链中间件服务，由 [`ChainLayer`] 创建
+///
+/// 内部服务接收`Request<Full<Bytes>>`，中间件的响应为`Response<Full<Bytes>>`；
+/// 链在当前任务中同步执行
+#[derive(Clone)]
+pub struct ChainService<S> {
+    inner: S,
+    options: Arc<ServiceOptions>,
+}
+
+impl ServiceOptions {
+    /// 链没有正常完成时的HTTP响应
+    fn chain_response(&self, response: &ChainResponse) -> Response<Full<Bytes>> {
+        mapping::json_response((self.status_mapper)(response), &(self.body_mapper)(response))
+    }
+
+    async fn handle<S, ReqBody, ResBody>(
+        &self,
+        request: Request<ReqBody>,
+        mut inner: S,
+    ) -> Result<Response<Full<Bytes>>, S::Error>
+    where
+        S: Service<Request<Full<Bytes>>, Response = Response<ResBody>>,
+        ReqBody: Body,
+        ReqBody::Error: Display,
+        ResBody: Body,
+        ResBody::Error: Display,
+    {
+        let (mut parts, body) = request.into_parts();
+        let mut body = match body.collect().await {
+            Ok(collected) => collected.to_bytes(),
+            Err(err) => return Ok(mapping::bad_request(format!("读取请求体失败: {}", err))),
+        };
+        let meta = mapping::request_meta(&parts, &self.client_addr);
+
+        if let Some(chain) = &self.request_chain {
+            let Some(params) = mapping::body_to_value(&body) else {
+                return Ok(mapping::bad_request("请求体不是UTF-8文本".to_string()));
+            };
+            let response = chain.execute(ChainRequest::new(params, meta.clone()));
+            if response.status != ChainStatus::Completed {
+                return Ok(self.chain_response(&response));
+            }
+            body = mapping::value_to_body(response.data.as_ref().unwrap_or(&Value::Null));
+            parts.headers.remove(header::CONTENT_LENGTH);
+            parts.extensions.insert(response);
+        }
+
+        let response = inner.call(Request::from_parts(parts, Full::new(body))).await?;
+
+        let (mut parts, body) = response.into_parts();
+        let mut body = match body.collect().await {
+            Ok(collected) => collected.to_bytes(),
+            Err(err) => return Ok(mapping::bad_gateway(format!("读取内部服务响应失败: {}", err))),
+        };
+
+        if let Some(chain) = &self.response_chain {
+            if let Some(params) = mapping::body_to_value(&body) {
+                let response = chain.execute(ChainRequest::new(params, mapping::response_meta(meta, &parts)));
+                if response.status != ChainStatus::Completed {
+                    return Ok(self.chain_response(&response));
+                }
+                let data = response.data.unwrap_or(Value::Null);
+                body = mapping::value_to_body(&data);
+                parts.headers.remove(header::CONTENT_LENGTH);
+                if !data.is_string() {
+                    parts
+                        .headers
+                        .insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+                }
+            }
+        }
+
+        Ok(Response::from_parts(parts, Full::new(body)))
+    }
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for ChainService<S>
+where
+    S: Service<Request<Full<Bytes>>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send,
+    S::Error: Send,
+    ReqBody: Body + Send + 'static,
+    ReqBody::Data: Send,
+    ReqBody::Error: Display,
+    ResBody: Body + Send + 'static,
+    ResBody::Data: Send,
+    ResBody::Error: Display,
+{
+    type Response = Response<Full<Bytes>>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<ReqBody>) -> Self::Future {
+        // 使用已就绪的服务处理本次请求，留下克隆的服务供下次调用
+        let clone = self.inner.clone();
+        let inner = std::mem::replace(&mut self.inner, clone);
+        let options = self.options.clone();
+        Box::pin(async move { options.handle(request, inner).await })
+    }
+}
@@ -0,0 +1,178 @@
+//! HTTP与链之间的转换
+//!
+//! 请求头、方法、路径、查询参数和客户端IP写入`meta`，请求体写入`params`；
+//! 链的Reject、Error等状态映射为HTTP响应。与保留字段同名的请求头被丢弃，客户端不能伪造`ip_address`等字段
+
+use crate::core::{ChainStatus, ChainResponse};
+use crate::types::{error_codes, ErrorResponse};
+use bytes::Bytes;
+use http::{header, HeaderMap, HeaderValue, Response, StatusCode};
+use http_body_util::Full;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+/// 状态映射函数类型
+pub type StatusMapper = Arc<dyn Fn(&ChainResponse) -> StatusCode + Send + Sync>;
+/// 响应体映射函数类型
+pub type BodyMapper = Arc<dyn Fn(&ChainResponse) -> Value + Send + Sync>;
+/// 客户端IP提取函数类型
+pub type ClientAddr = Arc<dyn Fn(&http::request::Parts) -> Option<IpAddr> + Send + Sync>;
+
+/// 由中间件写入的meta字段，同名的请求头不写入meta
+pub const RESERVED_META_KEYS: [&str; 6] = ["method", "path", "query", "ip_address", "status", "response_headers"];
+
+/// 默认状态映射：Reject为403，Error为500，Suspended为202，其余为200
+pub fn default_status(response: &ChainResponse) -> StatusCode {
+    match response.status {
+        ChainStatus::Reject => StatusCode::FORBIDDEN,
+        ChainStatus::Error => StatusCode::INTERNAL_SERVER_ERROR,
+        ChainStatus::Suspended => StatusCode::ACCEPTED,
+        ChainStatus::Continue | ChainStatus::Completed => StatusCode::OK,
+    }
+}
+
+/// 默认响应体映射：链的响应数据（错误或拒绝时为错误信息）
+pub fn default_body(response: &ChainResponse) -> Value {
+    response.data.clone().unwrap_or(Value::Null)
+}
+
+/// 默认客户端IP提取：请求扩展中的`SocketAddr`（由服务器在建立连接时写入）
+pub fn default_client_addr(parts: &http::request::Parts) -> Option<IpAddr> {
+    parts.extensions.get::<SocketAddr>().map(|addr| addr.ip())
+}
+
+/// 将请求体解析为JSON值：空请求体为null，不是JSON时为文本，不是UTF-8文本时返回None
+pub(crate) fn body_to_value(body: &[u8]) -> Option<Value> {
+    if body.iter().all(|b| b.is_ascii_whitespace()) {
+        return Some(Value::Null);
+    }
+    if let Ok(value) = serde_json::from_slice(body) {
+        return Some(value);
+    }
+    std::str::from_utf8(body).ok().map(|text| Value::String(text.to_string()))
+}
+
+/// 将链的输出转换为请求体或响应体：文本原样输出，其余序列化为JSON，null为空
+pub(crate) fn value_to_body(value: &Value) -> Bytes {
+    match value {
+        Value::Null => Bytes::new(),
+        Value::String(text) => Bytes::from(text.clone()),
+        other => Bytes::from(other.to_string()),
+    }
+}
+
+/// 请求头写入meta，字段名为小写的头名称，同名的多个值以`, `连接
+pub(crate) fn headers_to_meta(headers: &HeaderMap, meta: &mut HashMap<String, Value>) {
+    for name in headers.keys() {
+        let values: Vec<&str> = headers
+            .get_all(name)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .collect();
+        if !values.is_empty() {
+            meta.insert(name.as_str().to_string(), Value::String(values.join(", ")));
+        }
+    }
+}
+
+/// 解析查询字符串为对象，同名参数保留最后一个值
+pub(crate) fn parse_query(query: &str) -> Map<String, Value> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), Value::String(percent_decode(value)))
+        })
+        .collect()
+}
+
+/// 解码URL编码的查询参数，`+`解码为空格，无效的编码保留原样
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = |b: u8| (b as char).to_digit(16);
+                match (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                    (Some(high), Some(low)) => {
+                        decoded.push((high * 16 + low) as u8);
+                        i += 2;
+                    }
+                    _ => decoded.push(b'%'),
+                }
+            }
+            b => decoded.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// 由HTTP请求的各部分构建链请求的meta
+///
+/// meta包含所有请求头（小写名称）以及`method`、`path`、`query`（对象）和`ip_address`，
+/// `ip_address`由`client_addr`从连接信息中提取，与IP过滤挂件的默认配置一致。
+/// 与 [`RESERVED_META_KEYS`] 同名的请求头被丢弃，提取不到客户端IP时meta中没有`ip_address`
+pub(crate) fn request_meta(parts: &http::request::Parts, client_addr: &ClientAddr) -> HashMap<String, Value> {
+    let mut meta = HashMap::new();
+    headers_to_meta(&parts.headers, &mut meta);
+    for key in RESERVED_META_KEYS {
+        meta.remove(key);
+    }
+    meta.insert("method".to_string(), Value::String(parts.method.to_string()));
+    meta.insert("path".to_string(), Value::String(parts.uri.path().to_string()));
+    meta.insert(
+        "query".to_string(),
+        Value::Object(parts.uri.query().map(parse_query).unwrap_or_default()),
+    );
+    if let Some(ip) = client_addr(parts) {
+        meta.insert("ip_address".to_string(), Value::String(ip.to_string()));
+    }
+    meta
+}
+
+/// 由内部服务的响应构建响应链的meta：请求的meta加上`status`和`response_headers`（对象）
+pub(crate) fn response_meta(
+    request_meta: HashMap<String, Value>,
+    parts: &http::response::Parts,
+) -> HashMap<String, Value> {
+    let mut headers = HashMap::new();
+    headers_to_meta(&parts.headers, &mut headers);
+
+    let mut meta = request_meta;
+    meta.insert("status".to_string(), Value::from(parts.status.as_u16()));
+    meta.insert("response_headers".to_string(), Value::Object(headers.into_iter().collect()));
+    meta
+}
+
+/// 构建JSON响应
+pub(crate) fn json_response(status: StatusCode, body: &Value) -> Response<Full<Bytes>> {
+    let mut response = Response::new(Full::new(Bytes::from(body.to_string())));
+    *response.status_mut() = status;
+    response
+        .headers_mut()
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    response
+}
+
+/// 请求无法转换为链请求时的错误响应
+pub(crate) fn bad_request(msg: String) -> Response<Full<Bytes>> {
+    json_response(
+        StatusCode::BAD_REQUEST,
+        &ErrorResponse::new(error_codes::BAD_REQUEST, msg, None).to_json(),
+    )
+}
+
+/// 内部服务响应无法读取时的错误响应
+pub(crate) fn bad_gateway(msg: String) -> Response<Full<Bytes>> {
+    json_response(
+        StatusCode::BAD_GATEWAY,
+        &ErrorResponse::new(error_codes::INTERNAL_ERROR, msg, None).to_json(),
+    )
+}
@@ -0,0 +1,10 @@
+//! HTTP服务适配
+//!
+//! 提供tower中间件，在HTTP服务中执行链（需要启用`tower`特性）：
+//! HTTP请求转换为链请求后执行请求链，之后调用内部服务，再可选执行响应链
+
+pub mod layer;
+pub mod mapping;
+
+pub use layer::*;
+pub use mapping::{default_body, default_client_addr, default_status, BodyMapper, ClientAddr, StatusMapper, RESERVED_META_KEYS};
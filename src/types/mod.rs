@@ -46,6 +46,8 @@ pub mod error_codes {
     pub const FORBIDDEN: i32 = 403;
     /// 处理超时
     pub const TIMEOUT: i32 = 408;
+    /// 超过最大迭代次数
+    pub const ITERATION_LIMIT: i32 = 508;
     /// 内部错误
     pub const INTERNAL_ERROR: i32 = 500;
     /// 未找到挂件
@@ -0,0 +1,127 @@
+//! 日期时间工具
+//!
+//! 提供不依赖外部库的日期时间解析与格式化
+
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// 日期时间格式正则
+/// 支持：2024-01-01、2024/01/01、2024-01-01 10:00、2024-01-01T10:00:00.123Z、2024-01-01T10:00:00+08:00
+fn datetime_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r"^(\d{4})[-/](\d{1,2})[-/](\d{1,2})(?:[T ](\d{1,2}):(\d{2})(?::(\d{2})(?:\.(\d{1,9}))?)?)?\s*(Z|z|[+-]\d{2}:?\d{2})?$",
+        )
+        .unwrap()
+    })
+}
+
+/// 公历日期转换为距1970-01-01的天数
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let yoe = year - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// 距1970-01-01的天数转换为公历日期
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719468;
+    let era = if z >= 0 { z } else { z - 146096 } / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// 获取某月的天数
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        _ if (year % 4 == 0 && year % 100 != 0) || year % 400 == 0 => 29,
+        _ => 28,
+    }
+}
+
+/// 解析日期时间字符串，返回UTC毫秒时间戳
+///
+/// 未指定时区时按UTC处理
+pub fn parse_datetime_ms(text: &str) -> Result<i64, String> {
+    let caps = datetime_regex()
+        .captures(text.trim())
+        .ok_or_else(|| format!("无法识别的日期时间格式: {}", text))?;
+
+    let field = |index: usize| -> i64 {
+        caps.get(index)
+            .and_then(|m| m.as_str().parse::<i64>().ok())
+            .unwrap_or(0)
+    };
+    let (year, month, day) = (field(1), field(2), field(3));
+    let (hour, minute, second) = (field(4), field(5), field(6));
+
+    if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+        return Err(format!("无效的日期: {}", text));
+    }
+    if hour > 23 || minute > 59 || second > 60 {
+        return Err(format!("无效的时间: {}", text));
+    }
+
+    // 小数秒按毫秒截断
+    let millis = caps
+        .get(7)
+        .map(|m| {
+            let digits: String = m.as_str().chars().chain("000".chars()).take(3).collect();
+            digits.parse::<i64>().unwrap_or(0)
+        })
+        .unwrap_or(0);
+
+    // 时区偏移（分钟）
+    let offset_minutes = match caps.get(8).map(|m| m.as_str()) {
+        None | Some("Z") | Some("z") => 0,
+        Some(offset) => {
+            let sign = if offset.starts_with('-') { -1 } else { 1 };
+            let digits: String = offset[1..].chars().filter(|c| c.is_ascii_digit()).collect();
+            let hours = digits[..2].parse::<i64>().unwrap_or(0);
+            let minutes = digits[2..].parse::<i64>().unwrap_or(0);
+            sign * (hours * 60 + minutes)
+        }
+    };
+
+    let days = days_from_civil(year, month, day);
+    let seconds = days * 86400 + hour * 3600 + minute * 60 + second - offset_minutes * 60;
+    Ok(seconds * 1000 + millis)
+}
+
+/// 将毫秒时间戳格式化为ISO 8601 UTC字符串，如 2024-01-01T10:00:00Z
+pub fn format_iso_datetime(timestamp_ms: i64) -> String {
+    let seconds = timestamp_ms.div_euclid(1000);
+    let millis = timestamp_ms.rem_euclid(1000);
+    let (year, month, day) = civil_from_days(seconds.div_euclid(86400));
+    let secs_of_day = seconds.rem_euclid(86400);
+    let time = format!(
+        "{:02}:{:02}:{:02}",
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60
+    );
+    if millis == 0 {
+        format!("{:04}-{:02}-{:02}T{}Z", year, month, day, time)
+    } else {
+        format!("{:04}-{:02}-{:02}T{}.{:03}Z", year, month, day, time, millis)
+    }
+}
+
+/// 将毫秒时间戳格式化为ISO 8601 UTC日期，如 2024-01-01
+pub fn format_iso_date(timestamp_ms: i64) -> String {
+    let (year, month, day) = civil_from_days(timestamp_ms.div_euclid(1000).div_euclid(86400));
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
@@ -0,0 +1,211 @@
+//! IP集合
+//!
+//! 基于前缀树（二进制基数树）的IP集合，支持IPv4和IPv6的单个IP、CIDR网段和IP范围，
+//! 构建一次后查询复杂度只与地址位数相关，与条目数量无关，适用于大规模黑白名单
+
+use std::net::IpAddr;
+use std::path::Path;
+
+use super::ip_utils::{normalize_ip, parse_ip};
+
+/// 前缀树
+///
+/// 节点存储在数组中，`children[i]`为0表示不存在对应子节点（根节点下标为0，不会作为子节点）
+#[derive(Debug, Clone)]
+struct PrefixTrie {
+    /// 地址位数：IPv4为32，IPv6为128
+    bits: u32,
+    children: Vec<[u32; 2]>,
+    terminal: Vec<bool>,
+}
+
+impl PrefixTrie {
+    fn new(bits: u32) -> Self {
+        Self {
+            bits,
+            children: vec![[0, 0]],
+            terminal: vec![false],
+        }
+    }
+
+    /// 取地址的第`index`位（从最高位开始）
+    fn bit(&self, addr: u128, index: u32) -> usize {
+        ((addr >> (self.bits - 1 - index)) & 1) as usize
+    }
+
+    /// 插入前缀，已被更短前缀覆盖时忽略
+    fn insert(&mut self, addr: u128, prefix: u32) {
+        let mut node = 0usize;
+        for index in 0..prefix {
+            if self.terminal[node] {
+                return;
+            }
+            let bit = self.bit(addr, index);
+            if self.children[node][bit] == 0 {
+                self.children.push([0, 0]);
+                self.terminal.push(false);
+                self.children[node][bit] = (self.children.len() - 1) as u32;
+            }
+            node = self.children[node][bit] as usize;
+        }
+        self.terminal[node] = true;
+        // 更长的前缀已被覆盖，无需保留
+        self.children[node] = [0, 0];
+    }
+
+    /// 查询地址是否被任意前缀覆盖
+    fn contains(&self, addr: u128) -> bool {
+        let mut node = 0usize;
+        for index in 0..self.bits {
+            if self.terminal[node] {
+                return true;
+            }
+            match self.children[node][self.bit(addr, index)] {
+                0 => return false,
+                child => node = child as usize,
+            }
+        }
+        self.terminal[node]
+    }
+
+    /// 插入地址范围，拆分为最少数量的前缀
+    fn insert_range(&mut self, start: u128, end: u128) {
+        let mask = |size: u32| if size >= 128 { u128::MAX } else { (1u128 << size) - 1 };
+        let mut start = start;
+        loop {
+            // 以start对齐且不超过end的最大块
+            let mut size = start.trailing_zeros().min(self.bits);
+            while mask(size) > end - start {
+                size -= 1;
+            }
+            self.insert(start, self.bits - size);
+
+            let block_end = start + mask(size);
+            if block_end >= end {
+                return;
+            }
+            start = block_end + 1;
+        }
+    }
+}
+
+/// IP集合
+#[derive(Debug, Clone)]
+pub struct IpSet {
+    v4: PrefixTrie,
+    v6: PrefixTrie,
+    len: usize,
+}
+
+impl Default for IpSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IpSet {
+    /// 创建空的IP集合
+    pub fn new() -> Self {
+        Self {
+            v4: PrefixTrie::new(32),
+            v6: PrefixTrie::new(128),
+            len: 0,
+        }
+    }
+
+    /// 从条目列表构建IP集合
+    pub fn from_entries<S: AsRef<str>>(entries: &[S]) -> Result<Self, String> {
+        let mut set = Self::new();
+        for entry in entries {
+            set.insert(entry.as_ref())?;
+        }
+        Ok(set)
+    }
+
+    /// 添加条目，支持三种格式：
+    /// - 单个IP：`192.168.1.1`、`::1`
+    /// - CIDR网段：`10.0.0.0/8`、`2001:db8::/32`
+    /// - IP范围：`10.0.0.1-10.0.0.100`
+    pub fn insert(&mut self, entry: &str) -> Result<(), String> {
+        let entry = entry.trim();
+
+        if let Some((network, prefix)) = entry.split_once('/') {
+            let network = parse_ip(network).ok_or_else(|| format!("CIDR中的无效IP地址: {}", network))?;
+            let prefix: u32 = prefix
+                .trim()
+                .parse()
+                .map_err(|_| format!("CIDR中的无效前缀长度: {}", prefix))?;
+            let (trie, addr) = self.trie_for(network);
+            if prefix > trie.bits {
+                return Err(match trie.bits {
+                    32 => "IPv4前缀长度不能超过32".to_string(),
+                    _ => "IPv6前缀长度不能超过128".to_string(),
+                });
+            }
+            trie.insert(addr, prefix);
+        } else if let Some((start, end)) = entry.split_once('-') {
+            let start_ip = parse_ip(start).ok_or_else(|| format!("IP范围中的无效IP地址: {}", start))?;
+            let end_ip = parse_ip(end).ok_or_else(|| format!("IP范围中的无效IP地址: {}", end))?;
+            if start_ip.is_ipv4() != end_ip.is_ipv4() {
+                return Err(format!("IP范围的起止地址类型不一致: {}", entry));
+            }
+            let (_, end_addr) = self.trie_for(end_ip);
+            let (trie, start_addr) = self.trie_for(start_ip);
+            if start_addr > end_addr {
+                return Err(format!("IP范围的起始地址大于结束地址: {}", entry));
+            }
+            trie.insert_range(start_addr, end_addr);
+        } else {
+            let ip = parse_ip(entry).ok_or_else(|| format!("无效的IP地址: {}", entry))?;
+            let (trie, addr) = self.trie_for(ip);
+            let bits = trie.bits;
+            trie.insert(addr, bits);
+        }
+
+        self.len += 1;
+        Ok(())
+    }
+
+    /// 从文件加载条目：每行一个条目，`#`之后为注释，忽略空行
+    pub fn load_file<P: AsRef<Path>>(&mut self, path: P) -> Result<(), String> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("读取IP列表文件失败: {}, {}", path.display(), e))?;
+
+        for (line_no, line) in content.lines().enumerate() {
+            let entry = line.split('#').next().unwrap_or("").trim();
+            if entry.is_empty() {
+                continue;
+            }
+            self.insert(entry)
+                .map_err(|e| format!("{}第{}行: {}", path.display(), line_no + 1, e))?;
+        }
+        Ok(())
+    }
+
+    /// 查询IP是否在集合中，IPv4映射的IPv6地址按IPv4查询
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        match normalize_ip(*ip) {
+            IpAddr::V4(v4) => self.v4.contains(u32::from(v4) as u128),
+            IpAddr::V6(v6) => self.v6.contains(u128::from(v6)),
+        }
+    }
+
+    /// 添加的条目数量
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// 集合是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 获取IP对应的前缀树和数值形式的地址
+    fn trie_for(&mut self, ip: IpAddr) -> (&mut PrefixTrie, u128) {
+        match ip {
+            IpAddr::V4(v4) => (&mut self.v4, u32::from(v4) as u128),
+            IpAddr::V6(v6) => (&mut self.v6, u128::from(v6)),
+        }
+    }
+}
@@ -1,8 +1,13 @@
 //! IP工具函数
 //!
-//! 提供IP相关的通用处理函数
+//! 提供IP相关的通用处理函数，包括IP列表解析和基于可信代理的客户端IP解析
 
-use serde_json::Value;
+use crate::chainware::config::ChainwareConfig;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr};
+
+use super::ip_set::IpSet;
 
 /// 从配置值中提取IP列表
 /// 
@@ -46,4 +51,310 @@ pub fn extract_ip_list(config_value: Option<&Value>) -> Result<Vec<String>, Stri
             Err("缺少ip_list配置".to_string())
         }
     }
-}
\ No newline at end of file
+}
+
+/// 客户端IP解析选项
+///
+/// 位于负载均衡或反向代理之后时，真实客户端IP需要从转发头中解析
+pub struct ClientIpOptions {
+    /// 可信代理集合，为空时不解析转发头
+    pub trusted_proxies: IpSet,
+    /// 信任的转发头：`x-forwarded-for`或`forwarded`，只解析该头，不会回退到另一个头
+    pub forwarded_header: ForwardedHeader,
+    /// meta中RFC 7239 Forwarded头的字段名
+    pub forwarded_key: String,
+    /// meta中X-Forwarded-For头的字段名
+    pub forwarded_for_key: String,
+    /// 解析结果写入响应meta的字段名
+    pub client_ip_key: String,
+}
+
+impl ClientIpOptions {
+    /// 从挂件配置中读取客户端IP解析选项
+    pub fn from_config(config: Option<&ChainwareConfig>) -> Result<Self, String> {
+        let get = |key: &str| config.and_then(|cfg| cfg.config.get(key));
+        let get_str = |key: &str, default: &str| -> Result<String, String> {
+            match get(key) {
+                None => Ok(default.to_string()),
+                Some(Value::String(s)) => Ok(s.clone()),
+                Some(_) => Err(format!("{}配置必须是字符串类型", key)),
+            }
+        };
+
+        let trusted_proxies = match get("trusted_proxies") {
+            None => IpSet::new(),
+            value => {
+                let proxies = extract_ip_list(value).map_err(|e| e.replace("ip_list", "trusted_proxies"))?;
+                IpSet::from_entries(&proxies).map_err(|e| format!("trusted_proxies中{}", e))?
+            }
+        };
+
+        let forwarded_header = match get_str("forwarded_header", "x-forwarded-for")?.to_ascii_lowercase().as_str() {
+            "x-forwarded-for" => ForwardedHeader::XForwardedFor,
+            "forwarded" => ForwardedHeader::Forwarded,
+            other => return Err(format!("不支持的forwarded_header配置: {}", other)),
+        };
+
+        Ok(Self {
+            trusted_proxies,
+            forwarded_header,
+            forwarded_key: get_str("forwarded_key", "forwarded")?,
+            forwarded_for_key: get_str("forwarded_for_key", "x-forwarded-for")?,
+            client_ip_key: get_str("client_ip_key", "client_ip")?,
+        })
+    }
+
+    /// 客户端IP解析相关配置的JSON Schema属性（含`ip_key`），供使用这些配置的挂件组合进自身Schema
+    pub fn schema_properties() -> Map<String, Value> {
+        let properties = json!({
+            "ip_key": {
+                "type": "string",
+                "default": "ip_address",
+                "description": "meta中直连IP的字段名"
+            },
+            "trusted_proxies": {
+                "type": ["string", "array"],
+                "items": {"type": "string"},
+                "description": "可信代理IP或CIDR列表，配置后从转发头中解析真实客户端IP"
+            },
+            "forwarded_header": {
+                "type": "string",
+                "enum": ["x-forwarded-for", "forwarded"],
+                "default": "x-forwarded-for",
+                "description": "信任的转发头，只解析该头，应与代理实际写入的头一致"
+            },
+            "forwarded_key": {
+                "type": "string",
+                "default": "forwarded",
+                "description": "meta中RFC 7239 Forwarded头的字段名"
+            },
+            "forwarded_for_key": {
+                "type": "string",
+                "default": "x-forwarded-for",
+                "description": "meta中X-Forwarded-For头的字段名"
+            },
+            "client_ip_key": {
+                "type": "string",
+                "default": "client_ip",
+                "description": "解析出的客户端IP写入响应meta的字段名"
+            }
+        });
+        match properties {
+            Value::Object(map) => map,
+            _ => Map::new(),
+        }
+    }
+
+    /// 判断IP是否为可信代理
+    fn is_trusted(&self, ip: &IpAddr) -> bool {
+        self.trusted_proxies.contains(ip)
+    }
+}
+
+/// 信任的转发头
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForwardedHeader {
+    /// X-Forwarded-For头
+    XForwardedFor,
+    /// RFC 7239 Forwarded头
+    Forwarded,
+}
+
+/// 规范化IP地址：IPv4映射的IPv6地址（::ffff:a.b.c.d）转换为IPv4地址
+pub fn normalize_ip(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => IpAddr::V4(v4),
+            None => IpAddr::V6(v6),
+        },
+        v4 => v4,
+    }
+}
+
+/// 解析IP地址文本，支持引号、`[IPv6]:端口` 和 `IPv4:端口` 格式，结果已规范化
+pub fn parse_ip(text: &str) -> Option<IpAddr> {
+    let text = text.trim().trim_matches('"');
+    if let Some(rest) = text.strip_prefix('[') {
+        let end = rest.find(']')?;
+        return rest[..end].parse().ok().map(normalize_ip);
+    }
+    if let Ok(ip) = text.parse::<IpAddr>() {
+        return Some(normalize_ip(ip));
+    }
+    let (host, port) = text.rsplit_once(':')?;
+    if port.is_empty() || !port.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    host.parse::<Ipv4Addr>().ok().map(IpAddr::V4)
+}
+
+/// 按大小写不敏感方式获取meta中的头部值，多个值以逗号连接
+fn header_value(meta: &HashMap<String, Value>, key: &str) -> Option<String> {
+    let value = meta
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(key))
+        .map(|(_, v)| v)?;
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Array(values) => Some(
+            values
+                .iter()
+                .filter_map(|v| v.as_str())
+                .collect::<Vec<_>>()
+                .join(","),
+        ),
+        _ => None,
+    }
+}
+
+/// 解析X-Forwarded-For头，按从客户端到代理的顺序返回每一跳
+pub fn parse_x_forwarded_for(header: &str) -> Vec<String> {
+    header
+        .split(',')
+        .map(|hop| hop.trim().to_string())
+        .filter(|hop| !hop.is_empty())
+        .collect()
+}
+
+/// 解析RFC 7239 Forwarded头，按顺序返回每个元素的`for`参数
+///
+/// 例如：`for=192.0.2.60;proto=http;by=203.0.113.43, for="[2001:db8::1]:4711"`
+pub fn parse_forwarded(header: &str) -> Vec<String> {
+    let mut elements = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    for c in header.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                current.push(c);
+            }
+            ',' if !in_quotes => elements.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    elements.push(current);
+
+    elements
+        .iter()
+        .filter_map(|element| {
+            element.split(';').find_map(|pair| {
+                let (key, value) = pair.split_once('=')?;
+                key.trim()
+                    .eq_ignore_ascii_case("for")
+                    .then(|| value.trim().trim_matches('"').to_string())
+            })
+        })
+        .collect()
+}
+
+/// 解析真实客户端IP
+///
+/// 直连IP不是可信代理时直接返回直连IP；否则从配置信任的转发头中取出转发链，
+/// 自右向左跳过可信代理，返回第一个不可信的地址。转发链全部可信时返回最左侧地址。
+/// 遇到不是IP的跳（如`for=unknown`、`for=_hidden`）时停止，返回此前最后一个有效地址。
+/// 只解析信任的转发头，客户端伪造的另一个转发头不会被采用。
+pub fn resolve_client_ip(
+    remote_ip: &str,
+    meta: &HashMap<String, Value>,
+    options: &ClientIpOptions,
+) -> Result<IpAddr, String> {
+    let remote = parse_ip(remote_ip).ok_or_else(|| format!("无效的IP地址: {}", remote_ip))?;
+    if !options.is_trusted(&remote) {
+        return Ok(remote);
+    }
+
+    let hops = match options.forwarded_header {
+        ForwardedHeader::Forwarded => header_value(meta, &options.forwarded_key)
+            .map(|header| parse_forwarded(&header)),
+        ForwardedHeader::XForwardedFor => header_value(meta, &options.forwarded_for_key)
+            .map(|header| parse_x_forwarded_for(&header)),
+    }
+    .unwrap_or_default();
+
+    let mut client = remote;
+    for hop in hops.iter().rev() {
+        let Some(ip) = parse_ip(hop) else {
+            break;
+        };
+        client = ip;
+        if !options.is_trusted(&client) {
+            break;
+        }
+    }
+    Ok(client)
+}
+
+/// IP过滤规则
+///
+/// 由挂件配置编译而成，每个节点只构建一次
+pub struct IpFilterRules {
+    /// 名单集合
+    pub ip_set: IpSet,
+    /// 客户端IP解析选项
+    pub client_ip: ClientIpOptions,
+}
+
+impl IpFilterRules {
+    /// IP黑白名单挂件的配置Schema，`list_description`为名单的用途说明
+    pub fn config_schema(list_description: &str) -> Value {
+        let mut properties = Map::new();
+        properties.insert(
+            "ip_list".to_string(),
+            json!({
+                "type": ["string", "array"],
+                "items": {"type": "string"},
+                "description": format!("{}，逗号分隔的字符串或数组，支持单个IP、CIDR网段和IP范围", list_description)
+            }),
+        );
+        properties.insert(
+            "ip_file".to_string(),
+            json!({
+                "type": ["string", "array"],
+                "items": {"type": "string"},
+                "description": "名单文件路径，每行一个条目，#之后为注释"
+            }),
+        );
+        properties.extend(ClientIpOptions::schema_properties());
+
+        json!({
+            "type": "object",
+            "properties": properties,
+            "anyOf": [{"required": ["ip_list"]}, {"required": ["ip_file"]}],
+            "additionalProperties": false
+        })
+    }
+
+    /// 从挂件配置编译IP过滤规则
+    ///
+    /// 名单来自`ip_list`（字符串或数组）和`ip_file`（文件路径或路径数组），至少需要配置一项
+    pub fn from_config(config: Option<&ChainwareConfig>) -> Result<Self, String> {
+        let ip_list = config.and_then(|cfg| cfg.config.get("ip_list"));
+        let ip_file = config.and_then(|cfg| cfg.config.get("ip_file"));
+
+        let mut ip_set = IpSet::new();
+        if ip_list.is_some() || ip_file.is_none() {
+            for entry in extract_ip_list(ip_list)? {
+                ip_set.insert(&entry).map_err(|e| format!("ip_list中{}", e))?;
+            }
+        }
+        match ip_file {
+            None => {}
+            Some(Value::String(path)) => ip_set.load_file(path)?,
+            Some(Value::Array(paths)) => {
+                for path in paths {
+                    let path = path
+                        .as_str()
+                        .ok_or_else(|| "ip_file数组中的元素必须是字符串类型".to_string())?;
+                    ip_set.load_file(path)?;
+                }
+            }
+            Some(_) => return Err("ip_file配置必须是字符串或数组类型".to_string()),
+        }
+
+        Ok(Self {
+            ip_set,
+            client_ip: ClientIpOptions::from_config(config)?,
+        })
+    }
+}
@@ -2,9 +2,90 @@
 //!
 //! 提供统一的JSON路径解析和模板替换功能
 
+use jsonpath_rust::query::queryable::Queryable;
 use jsonpath_rust::JsonPath;
 use regex::Regex;
 use serde_json::Value;
+use std::collections::HashMap;
+
+/// 缺失的`$item`等数据求值为null
+static NULL: Value = Value::Null;
+
+/// 写入路径的根
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathRoot {
+    /// 当前数据（`$.`、`$input`、`$data`）
+    Input,
+    /// 链变量（`$vars`），第一个路径片段为变量名
+    Vars,
+}
+
+/// 路径求值上下文
+///
+/// 借用当前数据、请求参数、元数据和链变量，求值时只复制选取的值，不复制整个数据
+#[derive(Debug, Clone, Copy)]
+pub struct PathContext<'a> {
+    /// 当前数据（`$.`、`$input`、`$data`）
+    pub input: &'a Value,
+    /// 当前数组元素（`$item`）
+    pub item: Option<&'a Value>,
+    /// 请求参数（`$params`）
+    pub params: &'a Value,
+    /// 请求元数据（`$meta`）
+    pub meta: &'a HashMap<String, Value>,
+    /// 链变量（`$vars`）
+    pub vars: &'a HashMap<String, Value>,
+}
+
+impl<'a> PathContext<'a> {
+    /// 创建上下文
+    pub fn new(
+        input: &'a Value,
+        params: &'a Value,
+        meta: &'a HashMap<String, Value>,
+        vars: &'a HashMap<String, Value>,
+    ) -> Self {
+        Self {
+            input,
+            item: None,
+            params,
+            meta,
+            vars,
+        }
+    }
+
+    /// 数组元素的上下文：当前元素同时作为`$input`和`$item`，`$params`、`$meta`和`$vars`保持不变
+    pub fn with_item<'b>(&self, item: &'b Value) -> PathContext<'b>
+    where
+        'a: 'b,
+    {
+        PathContext {
+            input: item,
+            item: Some(item),
+            params: self.params,
+            meta: self.meta,
+            vars: self.vars,
+        }
+    }
+
+    /// 转换为JSON对象，只在路径不以已知前缀开头时使用
+    pub fn to_value(&self) -> Value {
+        serde_json::json!({
+            "__input": self.input,
+            "__item": self.item,
+            "__params": self.params,
+            "__meta": self.meta,
+            "__vars": self.vars
+        })
+    }
+}
+
+/// 路径的求值对象
+enum LookupRoot<'a> {
+    Value(&'a Value),
+    Map(&'a HashMap<String, Value>),
+    Context,
+}
 
 /// JSON路径模板工具
 pub struct JsonPathTemplate;
@@ -12,17 +93,77 @@ pub struct JsonPathTemplate;
 impl JsonPathTemplate {
     /// 构建统一的上下文对象
     ///
+    /// 复制输入数据、请求参数、元数据和链变量，配合 [`get_value`](Self::get_value) 使用；
+    /// 不需要JSON对象时使用借用数据的 [`build_path_context`](Self::build_path_context)
+    ///
     /// # 参数
     /// - `data`: 输入数据
     /// - `request`: 请求上下文
-    pub fn build_context(data: &Value, request: &crate::core::ChainRequest) -> Value {
+    /// - `response`: 响应上下文，提供链变量
+    pub fn build_context(
+        data: &Value,
+        request: &crate::core::ChainRequest,
+        response: &crate::core::ChainResponse,
+    ) -> Value {
+        Self::build_path_context(data, request, response).to_value()
+    }
+
+    /// 构建数组元素的上下文对象
+    ///
+    /// 当前元素同时作为`$input`和`$item`，`$params`、`$meta`和`$vars`保持不变
+    pub fn build_item_context(item: &Value, context: &Value) -> Value {
         serde_json::json!({
-            "__input": data,
-            "__params": request.params,
-            "__meta": request.meta
+            "__input": item,
+            "__item": item,
+            "__params": context["__params"],
+            "__meta": context["__meta"],
+            "__vars": context["__vars"]
         })
     }
 
+    /// 从上下文对象获取数据值，模板格式同 [`get_path_value`](Self::get_path_value)
+    ///
+    /// 需要复制元数据和链变量，内置挂件使用借用数据的 [`get_path_value`](Self::get_path_value)
+    pub fn get_value(context: &Value, template: &str) -> Result<Option<Value>, String> {
+        let to_map = |key: &str| -> HashMap<String, Value> {
+            match context.get(key) {
+                Some(Value::Object(object)) => object.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+                _ => HashMap::new(),
+            }
+        };
+        let meta = to_map("__meta");
+        let vars = to_map("__vars");
+        let mut path_context = PathContext::new(
+            context.get("__input").unwrap_or(&NULL),
+            context.get("__params").unwrap_or(&NULL),
+            &meta,
+            &vars,
+        );
+        path_context.item = context.get("__item").filter(|item| !item.is_null());
+        Self::get_path_value(&path_context, template)
+    }
+
+    /// 构建统一的上下文，借用数据，不复制
+    ///
+    /// # 参数
+    /// - `data`: 输入数据
+    /// - `request`: 请求上下文
+    /// - `response`: 响应上下文，提供链变量
+    pub fn build_path_context<'a>(
+        data: &'a Value,
+        request: &'a crate::core::ChainRequest,
+        response: &'a crate::core::ChainResponse,
+    ) -> PathContext<'a> {
+        PathContext::new(data, &request.params, &request.meta, &response.vars)
+    }
+
+    /// 构建数组元素的上下文
+    ///
+    /// 当前元素同时作为`$input`和`$item`，`$params`、`$meta`和`$vars`保持不变
+    pub fn build_item_path_context<'b>(item: &'b Value, context: &PathContext<'b>) -> PathContext<'b> {
+        context.with_item(item)
+    }
+
     /// 获取数据值
     ///
     /// # 参数
@@ -33,11 +174,13 @@ impl JsonPathTemplate {
     ///   - 如果是单独的`$.`路径或完整的`${}`，则直接返回获取的数据
     ///   - 如果以`$params`开头，则当作params获取数据
     ///   - 如果以`$meta`开头，则当作meta获取数据
+    ///   - 如果以`$vars`开头，则当作链变量获取数据
     ///   - 如果以`$data`开头，则当作input获取数据
     ///   - 如果以`$input`开头，则当作input获取数据
+    ///   - 如果以`$item`开头，则当作当前数组元素获取数据
     ///   - 如果模板还有其它字符内容，则转换成字符串替换对应位置
     ///   - 如果查询失败，则返回`None`
-    pub fn get_value(context: &Value, template: &str) -> Result<Option<Value>, String> {
+    pub fn get_path_value(context: &PathContext<'_>, template: &str) -> Result<Option<Value>, String> {
         let template = template.trim();
 
         let (path, data) = {
@@ -66,18 +209,18 @@ impl JsonPathTemplate {
             if processed_template.starts_with("$.[") {
                 (
                     format!("${}", &processed_template[2..]),
-                    context.get("__input").unwrap(),
+                    LookupRoot::Value(context.input),
                 )
             } else if processed_template.starts_with("$.") {
-                (processed_template, context.get("__input").unwrap())
+                (processed_template, LookupRoot::Value(context.input))
             } else if processed_template == "$" {
                 // 情况3：单独的$，返回输入数据
-                (processed_template, context.get("__input").unwrap())
+                (processed_template, LookupRoot::Value(context.input))
             } else if processed_template.starts_with("$[") {
                 // 情况4：数组索引
                 (
                     format!("${}", &processed_template[1..]),
-                    context.get("__input").unwrap(),
+                    LookupRoot::Value(context.input),
                 )
             } else if let Some(stripped) = processed_template.strip_prefix("$params") {
                 // 情况5：包含$params
@@ -86,7 +229,7 @@ impl JsonPathTemplate {
                 } else {
                     format!("${}", stripped)
                 };
-                (path, context.get("__params").unwrap())
+                (path, LookupRoot::Value(context.params))
             } else if let Some(stripped) = processed_template.strip_prefix("$meta") {
                 // 情况6：包含$meta
                 let path = if stripped.is_empty() {
@@ -94,30 +237,46 @@ impl JsonPathTemplate {
                 } else {
                     format!("${}", stripped)
                 };
-                (path, context.get("__meta").unwrap())
+                (path, LookupRoot::Map(context.meta))
+            } else if let Some(stripped) = processed_template.strip_prefix("$vars") {
+                // 情况7：包含$vars（链变量）
+                let path = if stripped.is_empty() {
+                    "$".to_string()
+                } else {
+                    format!("${}", stripped)
+                };
+                (path, LookupRoot::Map(context.vars))
             } else if let Some(stripped) = processed_template.strip_prefix("$data") {
-                // 情况7：包含$data
+                // 情况8：包含$data
                 let path = if stripped.is_empty() {
                     "$".to_string()
                 } else {
                     format!("${}", stripped)
                 };
-                (path, context.get("__input").unwrap())
+                (path, LookupRoot::Value(context.input))
             } else if let Some(stripped) = processed_template.strip_prefix("$input") {
-                // 情况8：包含$input
+                // 情况9：包含$input
+                let path = if stripped.is_empty() {
+                    "$".to_string()
+                } else {
+                    format!("${}", stripped)
+                };
+                (path, LookupRoot::Value(context.input))
+            } else if let Some(stripped) = processed_template.strip_prefix("$item") {
+                // 情况10：包含$item（数组元素上下文）
                 let path = if stripped.is_empty() {
                     "$".to_string()
                 } else {
                     format!("${}", stripped)
                 };
-                (path, context.get("__input").unwrap())
+                (path, LookupRoot::Value(context.item.unwrap_or(&NULL)))
             } else {
-                (processed_template, context)
+                (processed_template, LookupRoot::Context)
             }
         };
 
         if path.contains("${") {
-            // 情况9：包含变量的模板字符串
+            // 情况11：包含变量的模板字符串
             let result = Self::resolve_template(context, &path);
             if result.is_err() {
                 return Err(result.err().unwrap());
@@ -125,7 +284,144 @@ impl JsonPathTemplate {
             return Ok(Some(result.unwrap()));
         }
 
-        Self::resolve_jsonpath(data, &path.to_string())
+        match data {
+            LookupRoot::Value(value) => Self::resolve_jsonpath(value, &path),
+            LookupRoot::Map(map) => Self::resolve_map(map, &path),
+            LookupRoot::Context => Self::resolve_jsonpath(&context.to_value(), &path),
+        }
+    }
+
+    /// 在元数据或链变量中查询路径
+    ///
+    /// 第一个路径片段是普通字段名时直接取出对应的值再查询剩余路径，不复制整个集合
+    fn resolve_map(map: &HashMap<String, Value>, path: &str) -> Result<Option<Value>, String> {
+        let rest = path.strip_prefix('$').unwrap_or(path);
+        match Self::split_first_key(rest) {
+            Some((key, "")) => Ok(map.get(key).cloned()),
+            Some((key, remaining)) => match map.get(key) {
+                Some(value) => Self::resolve_jsonpath(value, &format!("${}", remaining)),
+                None => Ok(None),
+            },
+            None => {
+                let object: serde_json::Map<String, Value> =
+                    map.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+                Self::resolve_jsonpath(&Value::Object(object), path)
+            }
+        }
+    }
+
+    /// 拆分路径的第一个字段名：`.name`、`['name']`或`["name"]`，通配符、递归等其他形式返回None
+    fn split_first_key(path: &str) -> Option<(&str, &str)> {
+        if let Some(rest) = path.strip_prefix('.') {
+            let end = rest.find(['.', '[']).unwrap_or(rest.len());
+            let key = &rest[..end];
+            if key.is_empty() || key == "*" {
+                return None;
+            }
+            return Some((key, &rest[end..]));
+        }
+        for quote in ['\'', '"'] {
+            if let Some(rest) = path.strip_prefix('[').and_then(|rest| rest.strip_prefix(quote)) {
+                let end = rest.find(quote)?;
+                let remaining = rest[end + 1..].strip_prefix(']')?;
+                return Some((&rest[..end], remaining));
+            }
+        }
+        None
+    }
+
+    /// 查询输入数据中匹配JSONPath的所有位置
+    ///
+    /// # 参数
+    /// - `data`: 输入数据
+    /// - `path`: 相对输入数据的路径，支持`$.`、`.`、`$input`、`$data`前缀
+    ///
+    /// # 返回
+    /// 所有匹配位置的规范化路径，如 `$['items'][0]['price']`
+    pub fn query_paths(data: &Value, path: &str) -> Result<Vec<String>, String> {
+        let path = path.trim();
+        let path = if let Some(stripped) = path.strip_prefix("$input").or_else(|| path.strip_prefix("$data")) {
+            format!("${}", stripped)
+        } else if path.starts_with('.') {
+            format!("${}", path)
+        } else {
+            path.to_string()
+        };
+
+        data.query_only_path(&path)
+            .map_err(|err| format!("JSONPath解析错误 '{}': {}", path, err))
+    }
+
+    /// 获取规范化路径（由`query_paths`返回）所指位置的可变引用
+    pub fn value_at_mut<'a>(data: &'a mut Value, normalized_path: &str) -> Option<&'a mut Value> {
+        data.reference_mut(normalized_path)
+    }
+
+    /// 解析写入路径，返回根和路径片段（可直接用于`json_pointer`）
+    ///
+    /// 只支持字段和下标访问：`$.a.b[0]`、`$input.a['b c']`、`.a`、`$vars.user.name`；
+    /// `$`表示整个当前数据，`$vars`路径至少需要包含变量名
+    pub fn parse_write_path(path: &str) -> Result<(PathRoot, Vec<String>), String> {
+        let path = path.trim();
+        let (root, rest) = if let Some(rest) = path.strip_prefix("$vars") {
+            (PathRoot::Vars, rest)
+        } else if let Some(rest) = path.strip_prefix("$input").or_else(|| path.strip_prefix("$data")) {
+            (PathRoot::Input, rest)
+        } else if let Some(rest) = path.strip_prefix('$') {
+            (PathRoot::Input, rest)
+        } else if path.starts_with('.') {
+            (PathRoot::Input, path)
+        } else {
+            return Err(format!("写入路径必须以$开头: {}", path));
+        };
+
+        let invalid = || format!("不支持的写入路径: {}", path);
+        let mut tokens = Vec::new();
+        let mut chars = rest.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '.' => {
+                    let mut field = String::new();
+                    while let Some(&c) = chars.peek() {
+                        if c == '.' || c == '[' {
+                            break;
+                        }
+                        field.push(c);
+                        chars.next();
+                    }
+                    if field.is_empty() {
+                        return Err(invalid());
+                    }
+                    tokens.push(field);
+                }
+                '[' => {
+                    let mut segment = String::new();
+                    for c in chars.by_ref() {
+                        if c == ']' {
+                            break;
+                        }
+                        segment.push(c);
+                    }
+                    let segment = segment.trim();
+                    let quoted = segment.len() >= 2
+                        && ((segment.starts_with('\'') && segment.ends_with('\''))
+                            || (segment.starts_with('"') && segment.ends_with('"')));
+                    if quoted {
+                        tokens.push(segment[1..segment.len() - 1].to_string());
+                    } else if !segment.is_empty() && segment.chars().all(|c| c.is_ascii_digit()) {
+                        tokens.push(segment.to_string());
+                    } else {
+                        return Err(invalid());
+                    }
+                }
+                _ => return Err(invalid()),
+            }
+        }
+
+        if root == PathRoot::Vars && tokens.is_empty() {
+            return Err(format!("$vars写入路径缺少变量名: {}", path));
+        }
+        Ok((root, tokens))
     }
 
     /// 解析JSONPath路径
@@ -171,7 +467,7 @@ impl JsonPathTemplate {
     }
 
     /// 解析模板字符串，替换其中的变量
-    fn resolve_template(context: &Value, template: &str) -> Result<Value, String> {
+    fn resolve_template(context: &PathContext<'_>, template: &str) -> Result<Value, String> {
         let mut result = template.to_string();
 
         // 使用正则表达式找到所有${...}变量
@@ -182,7 +478,7 @@ impl JsonPathTemplate {
             let full_match = caps.get(0).unwrap().as_str();
             let path = caps.get(1).unwrap().as_str();
             // 获取值并转换为字符串
-            let value = Self::get_value(context, path)?;
+            let value = Self::get_path_value(context, path)?;
 
             let value_str = if let Some(value) = value {
                 Self::value_to_string(&value)
@@ -0,0 +1,148 @@
+//! JSON Pointer工具
+//!
+//! 提供RFC 6901 JSON Pointer的解析与读写功能
+
+use serde_json::Value;
+
+/// 解析JSON Pointer为路径片段
+///
+/// - 空字符串表示整个文档
+/// - 其它路径必须以`/`开头，片段中的`~1`和`~0`分别还原为`/`和`~`
+pub fn parse_pointer(pointer: &str) -> Result<Vec<String>, String> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    let stripped = pointer
+        .strip_prefix('/')
+        .ok_or_else(|| format!("JSON Pointer必须以'/'开头: {}", pointer))?;
+    Ok(stripped
+        .split('/')
+        .map(|token| token.replace("~1", "/").replace("~0", "~"))
+        .collect())
+}
+
+/// 按路径片段获取值
+pub fn get<'a>(target: &'a Value, tokens: &[String]) -> Option<&'a Value> {
+    let mut current = target;
+    for token in tokens {
+        current = match current {
+            Value::Object(obj) => obj.get(token)?,
+            Value::Array(arr) => arr.get(token.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+    Some(current)
+}
+
+/// 按路径片段获取可变引用
+fn get_mut<'a>(target: &'a mut Value, tokens: &[String]) -> Option<&'a mut Value> {
+    let mut current = target;
+    for token in tokens {
+        current = match current {
+            Value::Object(obj) => obj.get_mut(token)?,
+            Value::Array(arr) => arr.get_mut(token.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+    Some(current)
+}
+
+/// 解析数组下标，`-`表示数组末尾
+fn array_index(token: &str, len: usize, allow_end: bool) -> Result<usize, String> {
+    if allow_end && token == "-" {
+        return Ok(len);
+    }
+    let index = token
+        .parse::<usize>()
+        .map_err(|_| format!("无效的数组下标: {}", token))?;
+    let max = if allow_end { len } else { len.saturating_sub(1) };
+    if index > max || (!allow_end && len == 0) {
+        return Err(format!("数组下标越界: {}", index));
+    }
+    Ok(index)
+}
+
+/// 在指定位置添加值
+///
+/// - 对象：添加或覆盖字段
+/// - 数组：在下标处插入，`-`表示追加到末尾
+/// - `create_missing`为true时自动创建不存在的中间对象
+pub fn add(target: &mut Value, tokens: &[String], value: Value, create_missing: bool) -> Result<(), String> {
+    let Some((last, parents)) = tokens.split_last() else {
+        *target = value;
+        return Ok(());
+    };
+
+    let mut parent = target;
+    for token in parents {
+        parent = match parent {
+            Value::Object(obj) => {
+                if !obj.contains_key(token) {
+                    if !create_missing {
+                        return Err(format!("路径不存在: {}", token));
+                    }
+                    obj.insert(token.clone(), Value::Object(serde_json::Map::new()));
+                }
+                obj.get_mut(token).unwrap()
+            }
+            Value::Array(arr) => {
+                let index = array_index(token, arr.len(), false)?;
+                &mut arr[index]
+            }
+            Value::Null if create_missing => {
+                *parent = Value::Object(serde_json::Map::new());
+                parent
+                    .as_object_mut()
+                    .unwrap()
+                    .entry(token.clone())
+                    .or_insert_with(|| Value::Object(serde_json::Map::new()))
+            }
+            _ => return Err(format!("无法在非容器类型中访问: {}", token)),
+        };
+    }
+
+    match parent {
+        Value::Object(obj) => {
+            obj.insert(last.clone(), value);
+            Ok(())
+        }
+        Value::Array(arr) => {
+            let index = array_index(last, arr.len(), true)?;
+            arr.insert(index, value);
+            Ok(())
+        }
+        Value::Null if create_missing => {
+            let mut obj = serde_json::Map::new();
+            obj.insert(last.clone(), value);
+            *parent = Value::Object(obj);
+            Ok(())
+        }
+        _ => Err(format!("无法在非容器类型中设置: {}", last)),
+    }
+}
+
+/// 删除指定位置的值，返回被删除的值
+pub fn remove(target: &mut Value, tokens: &[String]) -> Result<Value, String> {
+    let Some((last, parents)) = tokens.split_last() else {
+        return Ok(std::mem::take(target));
+    };
+
+    let parent = get_mut(target, parents).ok_or_else(|| "路径不存在".to_string())?;
+    match parent {
+        Value::Object(obj) => obj
+            .remove(last)
+            .ok_or_else(|| format!("字段不存在: {}", last)),
+        Value::Array(arr) => {
+            let index = array_index(last, arr.len(), false)?;
+            Ok(arr.remove(index))
+        }
+        _ => Err(format!("无法在非容器类型中删除: {}", last)),
+    }
+}
+
+/// 替换指定位置已存在的值
+pub fn replace(target: &mut Value, tokens: &[String], value: Value) -> Result<(), String> {
+    let slot = get_mut(target, tokens).ok_or_else(|| "路径不存在".to_string())?;
+    *slot = value;
+    Ok(())
+}
@@ -0,0 +1,274 @@
+//! JSON修复工具
+//!
+//! 提供面向LLM输出的宽松JSON解析，修复常见的格式缺陷：
+//! - 结尾多余的逗号
+//! - 单引号字符串、未加引号的键
+//! - `//`、`/* */`、`#` 注释
+//! - Python风格的 `True`/`False`/`None`
+//! - 输出被截断导致的未闭合字符串、对象和数组
+
+use serde_json::{Map, Number, Value};
+
+/// 查找从`start`处开始的JSON对象或数组的结束位置（包含结束括号）
+///
+/// 忽略字符串中的括号，未找到匹配的结束括号时返回None
+pub fn find_json_end(text: &str, start: usize) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (i, &b) in bytes.iter().enumerate().skip(start) {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if b == b'\\' {
+                escaped = true;
+            } else if b == b'"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match b {
+            b'"' => in_string = true,
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' => {
+                depth = depth.checked_sub(1)?;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// 从`start`处宽松解析JSON值，返回解析结果和结束位置（不含）
+pub fn parse_lenient(text: &str, start: usize) -> Option<(Value, usize)> {
+    let mut parser = LenientParser { text, pos: start };
+    let value = parser.parse_value()?;
+    Some((value, parser.pos))
+}
+
+/// 宽松JSON解析器，直接在原文本上按字节位置解析，不复制文本
+struct LenientParser<'a> {
+    text: &'a str,
+    /// 当前字节位置，总是位于字符边界
+    pos: usize,
+}
+
+impl LenientParser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.text[self.pos..].chars().nth(offset)
+    }
+
+    /// 前进一个字符
+    fn bump(&mut self) {
+        if let Some(c) = self.peek() {
+            self.pos += c.len_utf8();
+        }
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos >= self.text.len()
+    }
+
+    /// 跳过空白和注释
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() {
+                self.bump();
+            } else if c == '#' || (c == '/' && self.peek_at(1) == Some('/')) {
+                while let Some(c) = self.peek() {
+                    if c == '\n' {
+                        break;
+                    }
+                    self.bump();
+                }
+            } else if c == '/' && self.peek_at(1) == Some('*') {
+                self.bump();
+                self.bump();
+                while !self.at_end() && (self.peek(), self.peek_at(1)) != (Some('*'), Some('/')) {
+                    self.bump();
+                }
+                self.bump();
+                self.bump();
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn parse_value(&mut self) -> Option<Value> {
+        self.skip_whitespace();
+        match self.peek()? {
+            '{' => self.parse_object(),
+            '[' => self.parse_array(),
+            '"' | '\'' => self.parse_string().map(Value::String),
+            c if c == '-' || c == '+' || c == '.' || c.is_ascii_digit() => self.parse_number(),
+            c if c.is_alphabetic() => self.parse_keyword(),
+            _ => None,
+        }
+    }
+
+    fn parse_object(&mut self) -> Option<Value> {
+        self.bump(); // 跳过 {
+        let mut map = Map::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                // 截断：对象未闭合
+                None => return Some(Value::Object(map)),
+                Some('}') => {
+                    self.bump();
+                    return Some(Value::Object(map));
+                }
+                Some(',') => {
+                    self.bump();
+                    continue;
+                }
+                _ => {}
+            }
+
+            let key = match self.peek()? {
+                '"' | '\'' => self.parse_string()?,
+                c if c.is_alphanumeric() || c == '_' || c == '$' => self.parse_identifier(),
+                _ => return None,
+            };
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(':') => self.bump(),
+                // 截断：键后没有值，丢弃该键
+                None => return Some(Value::Object(map)),
+                _ => return None,
+            }
+
+            self.skip_whitespace();
+            if self.at_end() {
+                return Some(Value::Object(map));
+            }
+            match self.parse_value() {
+                Some(value) => {
+                    map.insert(key, value);
+                }
+                // 截断：值不完整，丢弃该键
+                None if self.at_end() => return Some(Value::Object(map)),
+                None => return None,
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Option<Value> {
+        self.bump(); // 跳过 [
+        let mut items = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                // 截断：数组未闭合
+                None => return Some(Value::Array(items)),
+                Some(']') => {
+                    self.bump();
+                    return Some(Value::Array(items));
+                }
+                Some(',') => {
+                    self.bump();
+                    continue;
+                }
+                _ => match self.parse_value() {
+                    Some(value) => items.push(value),
+                    // 截断：元素不完整，丢弃该元素
+                    None if self.at_end() => return Some(Value::Array(items)),
+                    None => return None,
+                },
+            }
+        }
+    }
+
+    fn parse_string(&mut self) -> Option<String> {
+        let quote = self.peek()?;
+        self.bump();
+        let mut result = String::new();
+        while let Some(c) = self.peek() {
+            self.bump();
+            if c == quote {
+                return Some(result);
+            }
+            if c != '\\' {
+                result.push(c);
+                continue;
+            }
+            // 转义字符，截断时忽略末尾的反斜杠
+            let Some(escaped) = self.peek() else { break };
+            self.bump();
+            match escaped {
+                'n' => result.push('\n'),
+                't' => result.push('\t'),
+                'r' => result.push('\r'),
+                'b' => result.push('\u{8}'),
+                'f' => result.push('\u{c}'),
+                'u' => {
+                    let hex: String = (0..4).filter_map(|i| self.peek_at(i)).collect();
+                    match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                        Some(ch) if hex.len() == 4 => {
+                            result.push(ch);
+                            (0..4).for_each(|_| self.bump());
+                        }
+                        _ => result.push('u'),
+                    }
+                }
+                other => result.push(other),
+            }
+        }
+        // 截断：字符串未闭合
+        Some(result)
+    }
+
+    fn parse_identifier(&mut self) -> String {
+        let mut result = String::new();
+        while let Some(c) = self.peek() {
+            if c.is_alphanumeric() || c == '_' || c == '$' || c == '-' {
+                result.push(c);
+                self.bump();
+            } else {
+                break;
+            }
+        }
+        result
+    }
+
+    fn parse_number(&mut self) -> Option<Value> {
+        let mut text = String::new();
+        while let Some(c) = self.peek() {
+            if c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E') {
+                text.push(c);
+                self.bump();
+            } else {
+                break;
+            }
+        }
+        let text = text.trim_start_matches('+').trim_end_matches('.');
+        if let Ok(i) = text.parse::<i64>() {
+            return Some(Value::Number(i.into()));
+        }
+        text.parse::<f64>()
+            .ok()
+            .and_then(Number::from_f64)
+            .map(Value::Number)
+    }
+
+    fn parse_keyword(&mut self) -> Option<Value> {
+        let word = self.parse_identifier();
+        match word.as_str() {
+            "true" | "True" => Some(Value::Bool(true)),
+            "false" | "False" => Some(Value::Bool(false)),
+            "null" | "None" | "undefined" | "NaN" => Some(Value::Null),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,163 @@
+//! JSON Schema校验工具
+//!
+//! 实现JSON Schema的常用子集：type、enum、const、required、properties、
+//! additionalProperties、items、minItems/maxItems、minLength/maxLength、minimum/maximum、anyOf/oneOf
+
+use serde_json::Value;
+
+/// 校验数据是否符合Schema，返回所有错误（为空表示校验通过）
+///
+/// 错误格式：`路径: 错误原因`，路径以`$`表示根节点
+pub fn validate(value: &Value, schema: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    validate_at(value, schema, "$", &mut errors);
+    errors
+}
+
+/// 判断数据是否符合Schema
+pub fn is_valid(value: &Value, schema: &Value) -> bool {
+    validate(value, schema).is_empty()
+}
+
+/// 获取值的JSON Schema类型名称
+fn type_of(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// 检查值是否匹配类型名称
+fn matches_type(value: &Value, type_name: &str) -> bool {
+    let actual = type_of(value);
+    actual == type_name
+        || (type_name == "number" && actual == "integer")
+        || (type_name == "integer" && value.as_f64().is_some_and(|f| f.fract() == 0.0))
+}
+
+fn validate_at(value: &Value, schema: &Value, path: &str, errors: &mut Vec<String>) {
+    let Some(schema) = schema.as_object() else {
+        // true/空Schema接受任意值，false拒绝任意值
+        if schema == &Value::Bool(false) {
+            errors.push(format!("{}: 不允许出现该值", path));
+        }
+        return;
+    };
+
+    // type
+    if let Some(expected) = schema.get("type") {
+        let types: Vec<&str> = match expected {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(ts) => ts.iter().filter_map(|t| t.as_str()).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|t| matches_type(value, t)) {
+            errors.push(format!("{}: 类型应为 {}，实际为 {}", path, types.join("|"), type_of(value)));
+            return;
+        }
+    }
+
+    // enum / const
+    if let Some(Value::Array(options)) = schema.get("enum") {
+        if !options.contains(value) {
+            errors.push(format!("{}: 值 {} 不在可选范围 {} 中", path, value, Value::Array(options.clone())));
+        }
+    }
+    if let Some(expected) = schema.get("const") {
+        if expected != value {
+            errors.push(format!("{}: 值应为 {}", path, expected));
+        }
+    }
+
+    // anyOf / oneOf
+    if let Some(Value::Array(candidates)) = schema.get("anyOf") {
+        if !candidates.iter().any(|s| is_valid(value, s)) {
+            errors.push(format!("{}: 不符合anyOf中的任何一个Schema", path));
+        }
+    }
+    if let Some(Value::Array(candidates)) = schema.get("oneOf") {
+        let matched = candidates.iter().filter(|s| is_valid(value, s)).count();
+        if matched != 1 {
+            errors.push(format!("{}: 应恰好符合oneOf中的一个Schema，实际符合{}个", path, matched));
+        }
+    }
+
+    let number = |key: &str| schema.get(key).and_then(|v| v.as_f64());
+
+    match value {
+        Value::Object(obj) => {
+            if let Some(Value::Array(required)) = schema.get("required") {
+                for key in required.iter().filter_map(|k| k.as_str()) {
+                    if !obj.contains_key(key) {
+                        errors.push(format!("{}: 缺少必需字段 '{}'", path, key));
+                    }
+                }
+            }
+            let properties = schema.get("properties").and_then(|p| p.as_object());
+            for (key, child) in obj {
+                let child_path = format!("{}.{}", path, key);
+                match properties.and_then(|p| p.get(key)) {
+                    Some(child_schema) => validate_at(child, child_schema, &child_path, errors),
+                    None => match schema.get("additionalProperties") {
+                        Some(Value::Bool(false)) => {
+                            errors.push(format!("{}: 不允许的字段 '{}'", path, key));
+                        }
+                        Some(extra_schema @ Value::Object(_)) => {
+                            validate_at(child, extra_schema, &child_path, errors)
+                        }
+                        _ => {}
+                    },
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(min) = number("minItems") {
+                if (items.len() as f64) < min {
+                    errors.push(format!("{}: 元素数量不能少于{}", path, min));
+                }
+            }
+            if let Some(max) = number("maxItems") {
+                if (items.len() as f64) > max {
+                    errors.push(format!("{}: 元素数量不能多于{}", path, max));
+                }
+            }
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    validate_at(item, item_schema, &format!("{}[{}]", path, index), errors);
+                }
+            }
+        }
+        Value::String(s) => {
+            let len = s.chars().count() as f64;
+            if let Some(min) = number("minLength") {
+                if len < min {
+                    errors.push(format!("{}: 长度不能小于{}", path, min));
+                }
+            }
+            if let Some(max) = number("maxLength") {
+                if len > max {
+                    errors.push(format!("{}: 长度不能大于{}", path, max));
+                }
+            }
+        }
+        Value::Number(n) => {
+            let n = n.as_f64().unwrap_or(f64::NAN);
+            if let Some(min) = number("minimum") {
+                if n < min {
+                    errors.push(format!("{}: 值不能小于{}", path, min));
+                }
+            }
+            if let Some(max) = number("maximum") {
+                if n > max {
+                    errors.push(format!("{}: 值不能大于{}", path, max));
+                }
+            }
+        }
+        _ => {}
+    }
+}
@@ -1,2 +1,8 @@
+pub mod condition;
+pub mod datetime;
+pub mod ip_set;
 pub mod ip_utils;
-pub mod json_path; 
\ No newline at end of file
+pub mod json_path;
+pub mod json_pointer;
+pub mod json_repair;
+pub mod json_schema; 
\ No newline at end of file
@@ -30,8 +30,7 @@ pub fn create_context_with_ip(data: Value, ip: Option<&str>) -> ChainRequest {
     if let Some(ip_addr) = ip {
         meta.insert("ip_address".to_string(), json!(ip_addr));
     }
-    let context = ChainRequest::new(data, meta);
-    context
+    ChainRequest::new(data, meta)
 }
 
 /// 统一测试执行器
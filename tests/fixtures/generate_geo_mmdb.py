@@ -0,0 +1,129 @@
+#!/usr/bin/env python3
+"""生成geo_filter测试使用的MaxMind格式（MMDB）数据库
+
+只依赖Python标准库，输出 tests/fixtures/geo_test.mmdb：
+    python3 tests/fixtures/generate_geo_mmdb.py
+"""
+
+import ipaddress
+import os
+import struct
+
+# 测试网段：国家、大洲、ASN
+NETWORKS = [
+    ("1.0.0.0/24", "CN", "AS", 4134, "Chinanet"),
+    ("8.8.8.0/24", "US", "NA", 15169, "Google LLC"),
+    ("81.2.69.0/24", "GB", "EU", 20712, "Andrews & Arnold Ltd"),
+    ("2001:db8::/32", "DE", "EU", 3320, "Deutsche Telekom AG"),
+]
+
+
+def encode_uint(type_id, value):
+    data = value.to_bytes((value.bit_length() + 7) // 8, "big") if value else b""
+    return control(type_id, len(data)) + data
+
+
+def control(type_id, size):
+    assert size < 29 + 256
+    extra = b"" if size < 29 else bytes([size - 29])
+    size = min(size, 29)
+    if type_id <= 7:
+        return bytes([(type_id << 5) | size]) + extra
+    return bytes([size, type_id - 7]) + extra
+
+
+def encode(value):
+    if isinstance(value, bool):
+        return control(14, int(value))
+    if isinstance(value, str):
+        data = value.encode("utf-8")
+        return control(2, len(data)) + data
+    if isinstance(value, int):
+        return encode_uint(6, value) if value < 2**32 else encode_uint(9, value)
+    if isinstance(value, list):
+        return control(11, len(value)) + b"".join(encode(v) for v in value)
+    if isinstance(value, dict):
+        out = control(7, len(value))
+        for key, item in value.items():
+            out += encode(key) + encode(item)
+        return out
+    raise TypeError(value)
+
+
+def main():
+    data_section = b""
+    records = []
+    for network, country, continent, asn, org in NETWORKS:
+        record = {
+            "continent": {"code": continent},
+            "country": {"iso_code": country, "names": {"en": country}},
+            "autonomous_system_number": asn,
+            "autonomous_system_organization": org,
+        }
+        net = ipaddress.ip_network(network)
+        if net.version == 4:
+            # IPv4地址位于IPv6树的 ::/96 子树
+            addr = int(net.network_address)
+            prefix = 96 + net.prefixlen
+        else:
+            addr = int(net.network_address)
+            prefix = net.prefixlen
+        records.append((addr, prefix, len(data_section)))
+        data_section += encode(record)
+
+    # 构建二叉搜索树，节点记录：("node", 下标) / ("data", 偏移) / None
+    nodes = [[None, None]]
+    for addr, prefix, offset in records:
+        node = 0
+        for i in range(prefix):
+            bit = (addr >> (127 - i)) & 1
+            if i == prefix - 1:
+                nodes[node][bit] = ("data", offset)
+            else:
+                if nodes[node][bit] is None:
+                    nodes.append([None, None])
+                    nodes[node][bit] = ("node", len(nodes) - 1)
+                node = nodes[node][bit][1]
+
+    node_count = len(nodes)
+
+    def record_value(record):
+        if record is None:
+            return node_count
+        kind, value = record
+        return value if kind == "node" else node_count + 16 + value
+
+    tree = b""
+    for left, right in nodes:
+        tree += struct.pack(">I", record_value(left))[1:] + struct.pack(">I", record_value(right))[1:]
+
+    metadata = {
+        "binary_format_major_version": 2,
+        "binary_format_minor_version": 0,
+        "build_epoch": 1700000000,
+        "database_type": "LinkChain-Test-Geo",
+        "description": {"en": "linkchain geo_filter test fixture"},
+        "ip_version": 6,
+        "languages": ["en"],
+        "node_count": node_count,
+        "record_size": 24,
+    }
+    encoded_metadata = control(7, len(metadata))
+    for key, value in metadata.items():
+        if key in ("binary_format_major_version", "binary_format_minor_version", "ip_version", "record_size"):
+            item = encode_uint(5, value)
+        elif key == "build_epoch":
+            item = encode_uint(9, value)
+        else:
+            item = encode(value)
+        encoded_metadata += encode(key) + item
+
+    output = tree + b"\x00" * 16 + data_section + b"\xab\xcd\xefMaxMind.com" + encoded_metadata
+    path = os.path.join(os.path.dirname(os.path.abspath(__file__)), "geo_test.mmdb")
+    with open(path, "wb") as f:
+        f.write(output)
+    print("generated %s (%d bytes, %d nodes)" % (path, len(output), node_count))
+
+
+if __name__ == "__main__":
+    main()
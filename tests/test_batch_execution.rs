@@ -0,0 +1,244 @@
+//! 批量执行测试
+//!
+//! 测试线程池并行执行、响应顺序、单个请求的错误隔离、按块流式执行和统计汇总
+
+use linkchain::chain::{BatchOptions, BatchStats, ChainExecutor};
+use linkchain::chainware::config::ChainwareConfig;
+use linkchain::core::{ChainRequest, ChainResponse, ChainStatus};
+use linkchain::types::error_codes;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, ThreadId};
+use std::time::Duration;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// age大于等于18通过，并标记处理结果
+    fn adult_chain() -> ChainExecutor {
+        ChainExecutor::new()
+            .add_chainwares(json!([
+                {"name": "condition", "config": {"expression": "$.age >= 18"}},
+                {"name": "patch", "config": {"operations": [{"op": "add", "path": "/adult", "value": true}]}}
+            ]))
+            .unwrap()
+    }
+
+    fn requests(ages: impl IntoIterator<Item = i64>) -> Vec<ChainRequest> {
+        ages.into_iter()
+            .enumerate()
+            .map(|(id, age)| ChainRequest::new(json!({"id": id, "age": age}), HashMap::new()))
+            .collect()
+    }
+
+    #[test]
+    fn test_batch_keeps_order() {
+        let executor = adult_chain();
+        let ages: Vec<i64> = (0..200).map(|i| if i % 4 == 0 { 10 } else { 20 + i }).collect();
+
+        let result = executor.execute_batch(requests(ages.clone()), &BatchOptions::new().workers(4));
+        assert_eq!(result.responses.len(), 200);
+        for (id, (response, age)) in result.responses.iter().zip(&ages).enumerate() {
+            if *age < 18 {
+                assert_eq!(response.status, ChainStatus::Reject);
+            } else {
+                assert_eq!(response.status, ChainStatus::Completed);
+                assert_eq!(response.data, Some(json!({"id": id, "age": age, "adult": true})));
+            }
+        }
+
+        let stats = &result.stats;
+        assert_eq!(stats.total, 200);
+        assert_eq!(stats.count(&ChainStatus::Completed), 150);
+        assert_eq!(stats.count(&ChainStatus::Reject), 50);
+        assert_eq!(stats.count(&ChainStatus::Error), 0);
+        assert!(stats.latency_percentile(50.0) <= stats.latency_percentile(99.0));
+        assert!(stats.latency_percentile(99.0) <= stats.max_latency());
+
+        // 单线程和多线程结果一致
+        let single = executor.execute_batch(requests(ages), &BatchOptions::new().workers(1));
+        let statuses = |responses: &[ChainResponse]| responses.iter().map(|r| r.status.clone()).collect::<Vec<_>>();
+        assert_eq!(statuses(&single.responses), statuses(&result.responses));
+
+        // 空批次
+        let empty = executor.execute_batch(Vec::new(), &BatchOptions::new());
+        assert!(empty.responses.is_empty());
+        assert_eq!(empty.stats.latency_percentile(50.0), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_batch_isolates_panics() {
+        let executor = ChainExecutor::new()
+            .insert_chainware(
+                "fragile",
+                None,
+                Some(|_: &ChainRequest, _: &mut ChainResponse, data: Option<Value>, _: Option<&ChainwareConfig>| {
+                    let data = data.unwrap_or_default();
+                    if data["id"] == json!(3) {
+                        panic!("无法处理请求3");
+                    }
+                    Some(data)
+                }),
+                None,
+            )
+            .unwrap();
+
+        let result = executor.execute_batch(requests(0..8), &BatchOptions::new().workers(3));
+        assert_eq!(result.responses.len(), 8);
+        for (id, response) in result.responses.iter().enumerate() {
+            if id == 3 {
+                assert_eq!(response.status, ChainStatus::Error);
+                let data = response.data.as_ref().unwrap();
+                assert_eq!(data["errno"], json!(error_codes::INTERNAL_ERROR));
+                assert!(data["msg"].as_str().unwrap().contains("无法处理请求3"));
+            } else {
+                assert_eq!(response.status, ChainStatus::Completed);
+                assert_eq!(response.data.as_ref().unwrap()["id"], json!(id));
+            }
+        }
+        assert_eq!(result.stats.count(&ChainStatus::Error), 1);
+    }
+
+    /// 释放时panic的panic载荷，使工作线程在捕获panic后仍然异常退出
+    struct Bomb;
+
+    impl Drop for Bomb {
+        fn drop(&mut self) {
+            panic!("释放panic载荷时失败");
+        }
+    }
+
+    /// id为3的请求使工作线程异常退出，其余请求记录执行线程
+    fn crashing_chain(threads: Arc<Mutex<HashSet<ThreadId>>>) -> Arc<ChainExecutor> {
+        let executor = ChainExecutor::new()
+            .insert_chainware(
+                "crashing",
+                None,
+                Some(move |_: &ChainRequest, _: &mut ChainResponse, data: Option<Value>, _: Option<&ChainwareConfig>| {
+                    let data = data.unwrap_or_default();
+                    if data["id"] == json!(3) {
+                        std::panic::panic_any(Bomb);
+                    }
+                    threads.lock().unwrap().insert(thread::current().id());
+                    Some(data)
+                }),
+                None,
+            )
+            .unwrap();
+        Arc::new(executor)
+    }
+
+    fn assert_lost_only_third(responses: &[ChainResponse], offset: usize) {
+        for (i, response) in responses.iter().enumerate() {
+            let id = offset + i;
+            if id == 3 {
+                assert_eq!(response.status, ChainStatus::Error);
+                let data = response.data.as_ref().unwrap();
+                assert_eq!(data["errno"], json!(error_codes::INTERNAL_ERROR));
+                assert!(data["msg"].as_str().unwrap().contains("工作线程异常退出"));
+            } else {
+                assert_eq!(response.status, ChainStatus::Completed);
+                assert_eq!(response.data.as_ref().unwrap()["id"], json!(id));
+            }
+        }
+    }
+
+    #[test]
+    fn test_worker_exit_keeps_alignment() {
+        let threads = Arc::new(Mutex::new(HashSet::new()));
+        let executor = crashing_chain(Arc::clone(&threads));
+
+        // 线程异常退出时只有正在执行的请求缺失，以Error响应占位
+        for workers in [1, 3] {
+            let result = executor.execute_batch(requests(0..8), &BatchOptions::new().workers(workers));
+            assert_eq!(result.responses.len(), 8);
+            assert_lost_only_third(&result.responses, 0);
+            assert_eq!(result.stats.count(&ChainStatus::Error), 1);
+        }
+
+        // 流式执行时其余工作线程继续处理后续块，不会阻塞
+        let mut offset = 0;
+        for chunk in executor.execute_stream(requests(0..12), BatchOptions::new().workers(2).chunk_size(4)) {
+            assert_eq!(chunk.responses.len(), 4);
+            assert_lost_only_third(&chunk.responses, offset);
+            offset += chunk.responses.len();
+        }
+        assert_eq!(offset, 12);
+    }
+
+    #[test]
+    fn test_stream_reuses_workers() {
+        let threads = Arc::new(Mutex::new(HashSet::new()));
+        let executor = crashing_chain(Arc::clone(&threads));
+
+        // 跳过使线程退出的请求3
+        let input = requests(0..31).into_iter().filter(|request| request.params["id"] != json!(3));
+        let chunks = executor
+            .execute_stream(input, BatchOptions::new().workers(2).chunk_size(3))
+            .count();
+        assert_eq!(chunks, 10);
+        // 各块复用同一组工作线程
+        let threads = threads.lock().unwrap();
+        assert!(!threads.is_empty() && threads.len() <= 2);
+        assert!(!threads.contains(&thread::current().id()));
+    }
+
+    #[test]
+    fn test_merge_stats_percentiles() {
+        let executor = Arc::new(adult_chain());
+        let mut total = BatchStats::default();
+        let mut all = BatchStats::default();
+        for chunk in executor.execute_stream(requests(0..30), BatchOptions::new().workers(2).chunk_size(7)) {
+            // 合并前查询分位数，不影响后续合并结果
+            assert!(chunk.stats.latency_percentile(50.0) <= chunk.stats.max_latency());
+            total.merge(&chunk.stats);
+            all.merge(&chunk.stats.clone());
+            assert!(total.latency_percentile(50.0) <= total.latency_percentile(100.0));
+        }
+
+        assert_eq!(total.total, 30);
+        assert_eq!(total.latency_percentile(100.0), total.max_latency());
+        assert_eq!(total.latency_percentile(0.0), all.latency_percentile(0.0));
+        assert_eq!(total.latency_percentile(90.0), all.latency_percentile(90.0));
+        assert_eq!(total.to_json()["latency_ms"], all.to_json()["latency_ms"]);
+    }
+
+    #[test]
+    fn test_stream_in_chunks() {
+        let executor = Arc::new(adult_chain());
+        // 输入为惰性迭代器，按块取出
+        let input = (0..25).map(|i| ChainRequest::new(json!({"id": i, "age": 15 + i}), HashMap::new()));
+
+        let mut total = BatchStats::default();
+        let mut chunk_sizes = Vec::new();
+        let mut ids = Vec::new();
+        for chunk in executor.execute_stream(input, BatchOptions::new().workers(2).chunk_size(10)) {
+            chunk_sizes.push(chunk.responses.len());
+            total.merge(&chunk.stats);
+            ids.extend(chunk.responses.iter().filter_map(|r| r.data.as_ref()?.get("id").cloned()));
+        }
+
+        assert_eq!(chunk_sizes, vec![10, 10, 5]);
+        assert_eq!(total.total, 25);
+        assert_eq!(total.count(&ChainStatus::Reject), 3);
+        assert_eq!(total.count(&ChainStatus::Completed), 22);
+        // 被拒绝的请求响应数据为拒绝原因，其余按顺序返回
+        assert_eq!(ids, (3..25).map(|i| json!(i)).collect::<Vec<_>>());
+
+        let stats = total.to_json();
+        assert_eq!(stats["total"], json!(25));
+        assert_eq!(stats["by_status"]["completed"], json!(22));
+        assert!(stats["latency_ms"]["p90"].is_number());
+    }
+
+    #[test]
+    fn test_batch_options() {
+        let options = BatchOptions::new().workers(0).chunk_size(0);
+        assert_eq!(options.workers, 1);
+        assert_eq!(options.chunk_size, 1);
+        assert!(BatchOptions::default().workers >= 1);
+        assert_eq!(BatchOptions::default().chunk_size, 1000);
+    }
+}
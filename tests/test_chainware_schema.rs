@@ -0,0 +1,142 @@
+//! 挂件配置Schema测试
+//!
+//! 测试内置挂件的描述、配置Schema、示例，以及注册表的配置校验
+
+use linkchain::builtin::get_global_registry;
+use linkchain::chain::ChainExecutor;
+use linkchain::chainware::core::{Chainware, Closureware};
+use linkchain::core::{ChainRequest, ChainStatus};
+use linkchain::types::error_codes;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_all_builtins_are_described() {
+        let registry = get_global_registry();
+        let infos = registry.describe_all();
+        assert_eq!(infos.len(), registry.names().len());
+
+        for info in infos {
+            assert!(!info.description.is_empty(), "{} 缺少描述", info.name);
+            assert_eq!(info.config_schema["type"], json!("object"), "{}", info.name);
+            assert_eq!(
+                info.config_schema["properties"]["enabled"]["type"],
+                json!("boolean"),
+                "{} 缺少通用配置项",
+                info.name
+            );
+
+            // 示例必须符合自身的Schema
+            for example in &info.examples {
+                assert_eq!(
+                    registry.validate_config(&info.name, example),
+                    Ok(()),
+                    "{} 的示例不符合Schema: {}",
+                    info.name,
+                    example
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_all_builtin_examples_execute() {
+        let registry = get_global_registry();
+        let mut meta = HashMap::new();
+        meta.insert("ip_address".to_string(), json!("10.0.0.1"));
+        let inputs = [json!({}), json!({"content": "name=alice"}), json!([]), json!("2024-01-15")];
+
+        for info in registry.describe_all() {
+            for example in &info.examples {
+                let executor = ChainExecutor::new()
+                    .add_chainwares(json!([{"name": info.name, "config": example}]))
+                    .unwrap();
+                // 示例至少对一种输入能正常执行（通过、拒绝或达到循环上限），错误只能来自输入类型不符
+                let errors: Vec<Value> = inputs
+                    .iter()
+                    .map(|input| executor.execute(ChainRequest::new(input.clone(), meta.clone())))
+                    .filter(|response| response.status == ChainStatus::Error)
+                    .filter_map(|response| response.data)
+                    .filter(|error| error["errno"] != json!(error_codes::ITERATION_LIMIT))
+                    .collect();
+
+                // geo_filter示例引用外部数据库文件，只能在加载数据库时失败
+                if info.name == "geo_filter" {
+                    assert!(errors.iter().all(|e| e["msg"].as_str().unwrap().starts_with("加载地理数据库失败")));
+                    continue;
+                }
+                assert!(errors.len() < inputs.len(), "{} 的示例无法执行: {} {:?}", info.name, example, errors);
+            }
+        }
+    }
+
+    #[test]
+    fn test_validate_config() {
+        let registry = get_global_registry();
+
+        assert!(registry.validate_config("condition", &json!({"expression": "$.age > 18"})).is_ok());
+        assert!(registry.validate_config("condition", &json!({"condition": "$.age > 18", "enabled": false})).is_ok());
+
+        let errors = registry.validate_config("condition", &json!({})).unwrap_err();
+        assert_eq!(errors, vec!["$: 不符合anyOf中的任何一个Schema".to_string()]);
+
+        let errors = registry
+            .validate_config("map_fields", &json!({"mapping": {"a": 1}, "overwrite": "yes"}))
+            .unwrap_err();
+        assert!(errors.contains(&"$.mapping.a: 类型应为 string，实际为 integer".to_string()));
+        assert!(errors.contains(&"$.overwrite: 类型应为 boolean，实际为 string".to_string()));
+
+        let errors = registry
+            .validate_config("patch", &json!({"operations": [{"op": "rename", "path": "/a"}, {"op": "remove"}]}))
+            .unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].starts_with("$.operations[0].op: 值 \"rename\" 不在可选范围"));
+        assert_eq!(errors[1], "$.operations[1]: 缺少必需字段 'path'");
+
+        // IP名单至少配置ip_list或ip_file
+        assert!(registry.validate_config("ip_whitelist", &json!({"ip_file": "/data/ips.txt"})).is_ok());
+        assert!(registry.validate_config("ip_whitelist", &json!({"ip_key": "remote_addr"})).is_err());
+
+        let errors = registry.validate_config("no_such_chainware", &json!({})).unwrap_err();
+        assert_eq!(errors, vec!["未找到内置挂件: no_such_chainware".to_string()]);
+    }
+
+    #[test]
+    fn test_describe_schema_details() {
+        let registry = get_global_registry();
+
+        let info = registry.describe("convert").unwrap();
+        assert!(registry
+            .validate_config("convert", &json!({"fields": {"$.age": {"type": "int", "default": 0}}}))
+            .is_ok());
+        assert!(registry.validate_config("convert", &json!({"fields": {"$.age": "integer"}})).is_err());
+        assert_eq!(info.config_schema["properties"]["mode"]["default"], json!("lenient"));
+
+        // geo_filter需要启用geo特性
+        #[cfg(feature = "geo")]
+        {
+            let info = registry.describe("geo_filter").unwrap().to_json();
+            assert_eq!(info["name"], json!("geo_filter"));
+            assert_eq!(info["config_schema"]["properties"]["ip_key"]["default"], json!("ip_address"));
+            assert!(info["examples"].as_array().is_some_and(|e| !e.is_empty()));
+        }
+        #[cfg(not(feature = "geo"))]
+        assert!(registry.describe("geo_filter").is_none());
+
+        // 名称不区分大小写
+        assert_eq!(registry.describe("LOGGER").unwrap().name, "logger");
+        assert!(registry.describe("unknown").is_none());
+    }
+
+    #[test]
+    fn test_custom_chainware_defaults() {
+        let chainware = Closureware::new("custom".to_string(), |_, _, data, _| data);
+        assert_eq!(chainware.description(), "");
+        assert!(chainware.config_schema().is_none());
+        assert!(chainware.examples().is_empty());
+    }
+}
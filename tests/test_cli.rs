@@ -0,0 +1,256 @@
+//! 命令行工具测试
+//!
+//! 通过 cli::run 使用内存缓冲测试 run、validate、list、test、bench 子命令
+
+use linkchain::cli::{self, EXIT_FAILURE, EXIT_OK, EXIT_USAGE};
+use serde_json::{json, Value};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(path: &str) -> String {
+        format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), path)
+    }
+
+    fn chain_file() -> String {
+        fixture("golden/user_check/chain.json")
+    }
+
+    const META: &str = r#"{"ip_address": "192.168.1.10"}"#;
+
+    /// 执行命令，返回 (退出码, 标准输出, 标准错误)
+    fn run_cli(args: &[&str], stdin: &str) -> (i32, String, String) {
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        let code = cli::run(&args, &mut stdin.as_bytes(), &mut stdout, &mut stderr);
+        (
+            code,
+            String::from_utf8(stdout).unwrap(),
+            String::from_utf8(stderr).unwrap(),
+        )
+    }
+
+    fn write_temp(name: &str, content: &str) -> String {
+        let path = std::env::temp_dir().join(format!("linkchain_cli_{}_{}", std::process::id(), name));
+        std::fs::write(&path, content).unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn test_run_from_stdin() {
+        let chain = chain_file();
+        let (code, stdout, _) = run_cli(&["run", &chain, "--meta", META], r#"{"name": "张三", "age": 25}"#);
+        assert_eq!(code, EXIT_OK);
+
+        let output: Value = serde_json::from_str(&stdout).unwrap();
+        assert_eq!(output["status"], json!("completed"));
+        assert_eq!(output["data"]["display"], json!("张三（25岁）"));
+
+        // 被拒绝时退出码为1，输出错误信息
+        let (code, stdout, _) = run_cli(&["run", &chain, "--meta", META], r#"{"name": "李四", "age": 16}"#);
+        assert_eq!(code, EXIT_FAILURE);
+        let output: Value = serde_json::from_str(&stdout).unwrap();
+        assert_eq!(output["status"], json!("reject"));
+        assert!(output["data"]["errno"].is_number());
+    }
+
+    #[test]
+    fn test_run_with_input_file_and_meta() {
+        let chain = chain_file();
+        let input = write_temp("input.json", r#"{"name": "王五", "age": 30}"#);
+
+        let (code, stdout, _) = run_cli(
+            &["run", &chain, "--input", &input, "--meta", r#"{"ip_address": "203.0.113.5"}"#, "--pretty"],
+            "",
+        );
+        assert_eq!(code, EXIT_FAILURE);
+        assert!(stdout.contains("\n  \"status\": \"reject\""));
+
+        let (code, _, _) = run_cli(
+            &["run", &chain, &format!("--input={}", input), "--meta", r#"{"ip_address": "10.0.0.1"}"#],
+            "",
+        );
+        assert_eq!(code, EXIT_OK);
+    }
+
+    #[test]
+    fn test_usage_errors() {
+        let (code, _, stderr) = run_cli(&[], "");
+        assert_eq!(code, EXIT_USAGE);
+        assert!(stderr.contains("用法"));
+
+        let (code, _, stderr) = run_cli(&["unknown"], "");
+        assert_eq!(code, EXIT_USAGE);
+        assert!(stderr.contains("未知命令: unknown"));
+
+        let (code, _, stderr) = run_cli(&["run", &chain_file(), "--verbose"], "");
+        assert_eq!(code, EXIT_USAGE);
+        assert!(stderr.contains("未知选项: --verbose"));
+
+        let (code, _, stderr) = run_cli(&["run", &chain_file()], "{invalid");
+        assert_eq!(code, EXIT_USAGE);
+        assert!(stderr.contains("解析输入JSON失败"));
+
+        let (code, _, stderr) = run_cli(&["run"], "");
+        assert_eq!(code, EXIT_USAGE);
+        assert!(stderr.contains("run命令需要链配置文件"));
+    }
+
+    #[test]
+    fn test_validate() {
+        let (code, stdout, _) = run_cli(&["validate", &chain_file()], "");
+        assert_eq!(code, EXIT_OK);
+        assert!(stdout.contains("3个挂件"));
+
+        let invalid = write_temp(
+            "invalid_chain.json",
+            r#"[
+                {"name": "no_such_chainware"},
+                {"config": {}},
+                {"name": "condition", "config": []},
+                {"name": "merge", "config": {"data_path": "$params.extra", "strategy": "nested", "prefer_input": true}},
+                {"name": "condition", "config": {"expresion": "$.age > 18"}}
+            ]"#,
+        );
+        let (code, stdout, _) = run_cli(&["validate", &chain_file(), &invalid], "");
+        assert_eq!(code, EXIT_FAILURE);
+        assert!(stdout.contains("✅"));
+        assert!(stdout.contains("第1个挂件: 未找到内置挂件: no_such_chainware"));
+        assert!(stdout.contains("第2个挂件配置缺少name字段"));
+        assert!(stdout.contains("第3个挂件 'condition': $: 类型应为 object，实际为 array"));
+        assert!(stdout.contains("第4个挂件 'merge': $.strategy: 值 \"nested\" 不在可选范围"));
+        assert!(stdout.contains("第4个挂件 'merge': $: 不允许的字段 'prefer_input'"));
+        assert!(stdout.contains("第5个挂件 'condition': $: 不允许的字段 'expresion'"));
+
+        // 带链级on_error的链定义，子链递归校验
+        let nested = write_temp(
+            "nested_chain.json",
+            r#"{
+                "chain": [
+                    {"name": "convert", "config": {"fields": {"$.age": "int"}, "on_error": {"chain": [{"name": "logger", "config": {"levle": "info"}}]}}},
+                    {"name": "switch", "config": {"value": "$.type", "cases": {"a": [{"name": "no_such_chainware"}]}, "default": [{"config": {}}]}},
+                    {"name": "switch", "config": {"mode": "regex", "value": "$.v", "cases": [{"case": "^1", "chain": [{"name": "merge", "config": {"strategy": "nested"}}]}]}},
+                    {"name": "loop", "config": {"chain": [{"name": "condition", "config": {"expresion": "$.n > 0"}}], "until": "$.n > 3"}}
+                ],
+                "on_error": [{"name": "extract_map", "config": {"maping": {}}}],
+                "recover": true
+            }"#,
+        );
+        let (code, stdout, _) = run_cli(&["validate", &nested], "");
+        assert_eq!(code, EXIT_FAILURE);
+        for expected in [
+            "链定义中不支持的字段: recover",
+            "第1个挂件 'convert' 的on_error > 第1个挂件 'logger': $: 不允许的字段 'levle'",
+            "第2个挂件 'switch' 的分支 'a' > 第1个挂件: 未找到内置挂件: no_such_chainware",
+            "第2个挂件 'switch' 的default > 第1个挂件配置缺少name字段",
+            "第3个挂件 'switch' 的第1个分支 > 第1个挂件 'merge': $.strategy",
+            "第4个挂件 'loop' 的循环体 > 第1个挂件 'condition': $: 不允许的字段 'expresion'",
+            "链级on_error > 第1个挂件 'extract_map': $: 不允许的字段 'maping'",
+        ] {
+            assert!(stdout.contains(expected), "缺少: {}\n{}", expected, stdout);
+        }
+
+        // 对象格式的有效链定义可以校验和执行
+        let with_handler = write_temp(
+            "chain_with_handler.json",
+            r#"{"chain": [{"name": "convert", "config": {"fields": {"$.age": "int"}, "mode": "strict"}}],
+                "on_error": {"chain": [{"name": "json_extract", "config": {"pattern": "$.node.name"}}]}}"#,
+        );
+        let (code, stdout, _) = run_cli(&["validate", &with_handler], "");
+        assert_eq!(code, EXIT_OK, "{}", stdout);
+        assert!(stdout.contains("1个挂件"));
+        let (code, stdout, _) = run_cli(&["run", &with_handler], r#"{"age": "未知"}"#);
+        assert_eq!(code, EXIT_OK);
+        let output: Value = serde_json::from_str(&stdout).unwrap();
+        assert_eq!(output["data"], json!("convert"));
+    }
+
+    #[test]
+    fn test_list() {
+        let (code, stdout, _) = run_cli(&["list"], "");
+        assert_eq!(code, EXIT_OK);
+        let names: Vec<&str> = stdout.lines().filter_map(|line| line.split_whitespace().next()).collect();
+        assert!(names.contains(&"condition"));
+        assert_eq!(names.contains(&"geo_filter"), cfg!(feature = "geo"));
+        let mut sorted = names.clone();
+        sorted.sort();
+        assert_eq!(names, sorted);
+        assert!(stdout.contains("基于表达式进行条件判断"));
+
+        let (code, stdout, _) = run_cli(&["list", "--json"], "");
+        assert_eq!(code, EXIT_OK);
+        let infos: Value = serde_json::from_str(&stdout).unwrap();
+        let map_fields = infos
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|info| info["name"] == json!("map_fields"))
+            .unwrap();
+        assert_eq!(map_fields["config_schema"]["properties"]["overwrite"]["type"], json!("boolean"));
+
+        // 单个挂件显示配置Schema和示例
+        let (code, stdout, _) = run_cli(&["list", "merge"], "");
+        assert_eq!(code, EXIT_OK);
+        assert!(stdout.starts_with("merge - "));
+        assert!(stdout.contains("配置Schema"));
+        assert!(stdout.contains("\"merge_patch\""));
+        assert!(stdout.contains("配置示例"));
+
+        #[cfg(feature = "geo")]
+        {
+            let (code, stdout, _) = run_cli(&["list", "geo_filter", "--json"], "");
+            assert_eq!(code, EXIT_OK);
+            let info: Value = serde_json::from_str(&stdout).unwrap();
+            assert_eq!(info["config_schema"]["required"], json!(["database"]));
+        }
+
+        let (code, _, stderr) = run_cli(&["list", "no_such_chainware"], "");
+        assert_eq!(code, EXIT_FAILURE);
+        assert!(stderr.contains("未找到内置挂件"));
+    }
+
+    #[test]
+    fn test_golden_cases() {
+        let (code, stdout, _) = run_cli(&["test", &fixture("golden/user_check")], "");
+        assert_eq!(code, EXIT_OK, "{}", stdout);
+        assert!(stdout.contains("✅ adult"));
+        assert!(stdout.contains("失败 0 个"));
+
+        let (code, _, stderr) = run_cli(&["test", &fixture("golden/missing")], "");
+        assert_eq!(code, EXIT_FAILURE);
+        assert!(stderr.contains("读取文件失败"));
+    }
+
+    #[test]
+    fn test_bench() {
+        let samples = write_temp(
+            "samples.jsonl",
+            "{\"name\": \"张三\", \"age\": 25}\n\n{\"name\": \"李四\", \"age\": 16}\n",
+        );
+        let (code, stdout, _) = run_cli(&["bench", &chain_file(), "--input", &samples, "--meta", META, "--iterations", "5"], "");
+        assert_eq!(code, EXIT_OK);
+        assert!(stdout.contains("样例数: 2，迭代次数: 5，总执行次数: 10"));
+        assert!(stdout.contains("状态分布: completed=5, reject=5"));
+        assert!(stdout.contains("吞吐量"));
+
+        // 标准输入中的单个JSON
+        let (code, stdout, _) = run_cli(&["bench", &chain_file(), "--iterations", "3"], r#"{"age": 20}"#);
+        assert_eq!(code, EXIT_OK);
+        assert!(stdout.contains("总执行次数: 3"));
+
+        let (code, _, stderr) = run_cli(&["bench", &chain_file(), "--iterations", "0"], "");
+        assert_eq!(code, EXIT_USAGE);
+        assert!(stderr.contains("无效的iterations: 0"));
+
+        // 总执行次数溢出或超过上限
+        let max = usize::MAX.to_string();
+        let (code, _, stderr) = run_cli(&["bench", &chain_file(), "--input", &samples, "--iterations", &max], "");
+        assert_eq!(code, EXIT_USAGE);
+        assert!(stderr.contains("iterations过大"));
+        let (code, _, stderr) = run_cli(&["bench", &chain_file(), "--input", &samples, "--iterations", "500001"], "");
+        assert_eq!(code, EXIT_USAGE);
+        assert!(stderr.contains("总执行次数不能超过1000000"));
+    }
+}
@@ -158,6 +158,45 @@ mod tests {
                 None,
                 Some(json!({"username": "testuser"})),
             ),
+            (
+                6,
+                "字符串函数测试 - matches",
+                json!({"phone": "13812345678"}),
+                vec![("condition", {
+                    let mut config = HashMap::new();
+                    config.insert("expression".to_string(), json!(r#"String.matches($.phone, "^1[3-9]\d{9}$")"#));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!({"phone": "13812345678"})),
+            ),
+            (
+                7,
+                "字符串函数测试 - matches不匹配",
+                json!({"phone": "12345"}),
+                vec![("condition", {
+                    let mut config = HashMap::new();
+                    config.insert("expression".to_string(), json!(r#"String.matches($.phone, "^1[3-9]\d{9}$")"#));
+                    config
+                })],
+                ChainStatus::Reject,
+                None,
+                None,
+            ),
+            (
+                8,
+                "字符串函数测试 - matches无效正则",
+                json!({"phone": "13812345678"}),
+                vec![("condition", {
+                    let mut config = HashMap::new();
+                    config.insert("expression".to_string(), json!(r#"String.matches($.phone, "[0-9")"#));
+                    config
+                })],
+                ChainStatus::Error,
+                None,
+                None,
+            ),
         ];
 
         run_test_cases(test_cases, "条件挂件高级测试", 0.8);
@@ -1,6 +1,6 @@
 //! 数据处理类挂件测试
 //!
-//! 测试 logger, merge, map_fields 挂件
+//! 测试 logger, merge, map_fields, patch, convert, array_ops, set_var 挂件
 
 include!("common.rs");
 
@@ -131,6 +131,626 @@ mod tests {
         run_test_cases(test_cases, "merge挂件测试", 0.6); // 由于需要params数据，成功率可能较低
     }
 
+    #[test]
+    fn test_merge_strategies() {
+        let test_cases = vec![
+            (
+                1,
+                "浅合并 - 嵌套对象整体覆盖",
+                json!({"user": {"name": "张三", "age": 25}, "extra": {"user": {"age": 26}}}),
+                vec![("merge", {
+                    let mut config = HashMap::new();
+                    config.insert("data_path".to_string(), json!("$params.extra"));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!({"user": {"age": 26}, "extra": {"user": {"age": 26}}})),
+            ),
+            (
+                2,
+                "深合并 - 递归合并嵌套对象",
+                json!({"user": {"name": "张三", "age": 25}, "extra": {"user": {"age": 26}}}),
+                vec![("merge", {
+                    let mut config = HashMap::new();
+                    config.insert("data_path".to_string(), json!("$params.extra"));
+                    config.insert("strategy".to_string(), json!("deep"));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!({"user": {"name": "张三", "age": 26}, "extra": {"user": {"age": 26}}})),
+            ),
+            (
+                3,
+                "深合并 - 输入数据优先",
+                json!({"user": {"name": "张三"}, "extra": {"user": {"name": "李四", "city": "北京"}}}),
+                vec![("merge", {
+                    let mut config = HashMap::new();
+                    config.insert("data_path".to_string(), json!("$params.extra"));
+                    config.insert("strategy".to_string(), json!("deep"));
+                    config.insert("prefer".to_string(), json!("input"));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!({"user": {"name": "张三", "city": "北京"}, "extra": {"user": {"name": "李四", "city": "北京"}}})),
+            ),
+            (
+                4,
+                "JSON Merge Patch - null删除字段",
+                json!({"name": "张三", "password": "123", "patch": {"password": null, "role": "admin"}}),
+                vec![("merge", {
+                    let mut config = HashMap::new();
+                    config.insert("data_path".to_string(), json!("$params.patch"));
+                    config.insert("strategy".to_string(), json!("merge_patch"));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!({"name": "张三", "role": "admin", "patch": {"password": null, "role": "admin"}})),
+            ),
+            (
+                5,
+                "数组按key去重合并",
+                json!({"items": [{"id": 1, "qty": 1}, {"id": 2, "qty": 1}], "extra": {"items": [{"id": 2, "qty": 5}, {"id": 3, "qty": 1}]}}),
+                vec![("merge", {
+                    let mut config = HashMap::new();
+                    config.insert("data_path".to_string(), json!("$params.extra"));
+                    config.insert("strategy".to_string(), json!("deep"));
+                    config.insert("array_key".to_string(), json!("id"));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!({
+                    "items": [{"id": 1, "qty": 1}, {"id": 2, "qty": 5}, {"id": 3, "qty": 1}],
+                    "extra": {"items": [{"id": 2, "qty": 5}, {"id": 3, "qty": 1}]}
+                })),
+            ),
+            (
+                6,
+                "数组并集合并",
+                json!({"tags": ["a", "b"], "extra": {"tags": ["b", "c"]}}),
+                vec![("merge", {
+                    let mut config = HashMap::new();
+                    config.insert("data_path".to_string(), json!("$params.extra"));
+                    config.insert("strategy".to_string(), json!("deep"));
+                    config.insert("array_mode".to_string(), json!("union"));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!({"tags": ["a", "b", "c"], "extra": {"tags": ["b", "c"]}})),
+            ),
+            (
+                7,
+                "多数据源按顺序合并",
+                json!({"a": {"x": 1}, "b": {"x": 2, "y": 2}}),
+                vec![("merge", {
+                    let mut config = HashMap::new();
+                    config.insert("data_path".to_string(), json!(["$params.b", "$params.a"]));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!({"a": {"x": 1}, "b": {"x": 2, "y": 2}, "x": 1, "y": 2})),
+            ),
+            (
+                8,
+                "类型冲突报错",
+                json!({"user": {"name": "张三"}, "extra": {"user": "李四"}}),
+                vec![("merge", {
+                    let mut config = HashMap::new();
+                    config.insert("data_path".to_string(), json!("$params.extra"));
+                    config.insert("strategy".to_string(), json!("deep"));
+                    config.insert("on_conflict".to_string(), json!("error"));
+                    config
+                })],
+                ChainStatus::Error,
+                None,
+                Some(json!({"errno": 500, "msg": "合并操作失败: 字段 '$.user' 类型冲突: object 与 string"})),
+            ),
+        ];
+
+        run_test_cases(test_cases, "merge合并策略测试", 1.0);
+    }
+
+    #[test]
+    fn test_patch() {
+        let test_cases = vec![
+            (
+                1,
+                "增删改有序操作",
+                json!({"name": "张三", "password": "123456", "tags": ["a"]}),
+                vec![("patch", {
+                    let mut config = HashMap::new();
+                    config.insert("operations".to_string(), json!([
+                        {"op": "remove", "path": "/password"},
+                        {"op": "replace", "path": "/name", "value": "李四"},
+                        {"op": "add", "path": "/tags/-", "value": "b"}
+                    ]));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!({"name": "李四", "tags": ["a", "b"]})),
+            ),
+            (
+                2,
+                "深层路径自动创建",
+                json!({"name": "张三"}),
+                vec![("patch", {
+                    let mut config = HashMap::new();
+                    config.insert("operations".to_string(), json!([
+                        {"op": "add", "path": "/profile/contact/city", "value": "北京"}
+                    ]));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!({"name": "张三", "profile": {"contact": {"city": "北京"}}})),
+            ),
+            (
+                3,
+                "move和copy操作",
+                json!({"old_name": "王五", "age": 20}),
+                vec![("patch", {
+                    let mut config = HashMap::new();
+                    config.insert("operations".to_string(), json!([
+                        {"op": "move", "from": "/old_name", "path": "/name"},
+                        {"op": "copy", "from": "/age", "path": "/user/age"}
+                    ]));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!({"age": 20, "name": "王五", "user": {"age": 20}})),
+            ),
+            (
+                4,
+                "值使用模板表达式",
+                json!({"name": "赵六"}),
+                vec![("patch", {
+                    let mut config = HashMap::new();
+                    config.insert("operations".to_string(), json!([
+                        {"op": "add", "path": "/greeting", "value": "你好, ${$.name}"},
+                        {"op": "add", "path": "/copy", "value": "$.name"}
+                    ]));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!({"name": "赵六", "greeting": "你好, 赵六", "copy": "赵六"})),
+            ),
+            (
+                5,
+                "test操作失败拒绝请求",
+                json!({"version": 1}),
+                vec![("patch", {
+                    let mut config = HashMap::new();
+                    config.insert("operations".to_string(), json!([
+                        {"op": "test", "path": "/version", "value": 2},
+                        {"op": "replace", "path": "/version", "value": 3}
+                    ]));
+                    config
+                })],
+                ChainStatus::Reject,
+                None,
+                Some(json!({"errno": 402, "msg": "patch校验未通过: 路径 '/version' 的值 1 与期望值 2 不一致"})),
+            ),
+            (
+                6,
+                "删除不存在的字段报错",
+                json!({"name": "张三"}),
+                vec![("patch", {
+                    let mut config = HashMap::new();
+                    config.insert("operations".to_string(), json!([
+                        {"op": "remove", "path": "/missing"}
+                    ]));
+                    config
+                })],
+                ChainStatus::Error,
+                None,
+                None,
+            ),
+            (
+                7,
+                "literal字面量不求值",
+                json!({"name": "张三"}),
+                vec![("patch", {
+                    let mut config = HashMap::new();
+                    config.insert("operations".to_string(), json!([
+                        {"op": "add", "path": "/price", "value": {"literal": "$5.00"}},
+                        {"op": "add", "path": "/template", "value": {"literal": "${name}"}},
+                        {"op": "add", "path": "/raw", "value": {"literal": {"literal": 1}}}
+                    ]));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!({"name": "张三", "price": "$5.00", "template": "${name}", "raw": {"literal": 1}})),
+            ),
+            (
+                8,
+                "test操作按数值比较数字",
+                json!({"version": 1, "limits": {"max": [2.0, {"ratio": 0.5}]}}),
+                vec![("patch", {
+                    let mut config = HashMap::new();
+                    config.insert("operations".to_string(), json!([
+                        {"op": "test", "path": "/version", "value": 1.0},
+                        {"op": "test", "path": "/limits", "value": {"max": [2, {"ratio": 0.50}]}},
+                        {"op": "replace", "path": "/version", "value": 2}
+                    ]));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!({"version": 2, "limits": {"max": [2.0, {"ratio": 0.5}]}})),
+            ),
+            (
+                9,
+                "test操作数组长度不同时失败",
+                json!({"tags": [1, 2]}),
+                vec![("patch", {
+                    let mut config = HashMap::new();
+                    config.insert("operations".to_string(), json!([
+                        {"op": "test", "path": "/tags", "value": [1.0, 2.0, 3.0]}
+                    ]));
+                    config
+                })],
+                ChainStatus::Reject,
+                None,
+                None,
+            ),
+        ];
+
+        run_test_cases(test_cases, "patch挂件测试", 1.0);
+    }
+
+    #[test]
+    fn test_convert() {
+        let test_cases = vec![
+            (
+                1,
+                "宽松模式基础类型转换",
+                json!({"age": "25", "score": "98.5", "active": "yes", "count": 3.0, "code": 42}),
+                vec![("convert", {
+                    let mut config = HashMap::new();
+                    config.insert("fields".to_string(), json!({
+                        "$.age": "int",
+                        "$.score": "float",
+                        "$.active": "bool",
+                        "$.count": "int",
+                        "$.code": "string"
+                    }));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!({"age": 25, "score": 98.5, "active": true, "count": 3, "code": "42"})),
+            ),
+            (
+                2,
+                "数组元素批量转换",
+                json!({"items": [{"price": "10"}, {"price": "20.5"}]}),
+                vec![("convert", {
+                    let mut config = HashMap::new();
+                    config.insert("fields".to_string(), json!({"$.items[*].price": "float"}));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!({"items": [{"price": 10.0}, {"price": 20.5}]})),
+            ),
+            (
+                3,
+                "时间格式转换",
+                json!({"created": "2024-01-01T18:00:00+08:00", "updated": 1704103200, "day": "2024/03/05 12:30"}),
+                vec![("convert", {
+                    let mut config = HashMap::new();
+                    config.insert("fields".to_string(), json!({
+                        "$.created": "timestamp",
+                        "$.updated": "datetime",
+                        "$.day": "date"
+                    }));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!({"created": 1704103200, "updated": "2024-01-01T10:00:00Z", "day": "2024-03-05"})),
+            ),
+            (
+                4,
+                "转换失败使用默认值",
+                json!({"age": "unknown"}),
+                vec![("convert", {
+                    let mut config = HashMap::new();
+                    config.insert("fields".to_string(), json!({"$.age": {"type": "int", "default": 0}}));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!({"age": 0})),
+            ),
+            (
+                5,
+                "严格模式报告所有错误路径",
+                json!({"age": "25.5", "active": "yes", "name": "张三"}),
+                vec![("convert", {
+                    let mut config = HashMap::new();
+                    config.insert("fields".to_string(), json!({"$.age": "int", "$.active": "bool", "$.name": "string"}));
+                    config.insert("mode".to_string(), json!("strict"));
+                    config
+                })],
+                ChainStatus::Error,
+                None,
+                Some(json!({
+                    "errno": 402,
+                    "msg": "类型转换失败: 2个字段无法转换",
+                    "detail": [
+                        {"path": "$['active']", "value": "yes", "error": "'yes' 无法转换为布尔值"},
+                        {"path": "$['age']", "value": "25.5", "error": "'25.5' 不是整数"}
+                    ]
+                })),
+            ),
+            (
+                6,
+                "转换后条件判断生效",
+                json!({"age": "20"}),
+                vec![
+                    ("convert", {
+                        let mut config = HashMap::new();
+                        config.insert("fields".to_string(), json!({"$.age": "int"}));
+                        config
+                    }),
+                    ("condition", {
+                        let mut config = HashMap::new();
+                        config.insert("expression".to_string(), json!("$.age === 20"));
+                        config
+                    }),
+                ],
+                ChainStatus::Completed,
+                None,
+                Some(json!({"age": 20})),
+            ),
+        ];
+
+        run_test_cases(test_cases, "convert挂件测试", 1.0);
+    }
+
+    #[test]
+    fn test_array_ops() {
+        let products = json!([
+            {"id": 1, "name": "键盘", "category": "外设", "price": 199},
+            {"id": 2, "name": "鼠标", "category": "外设", "price": 99},
+            {"id": 3, "name": "显示器", "category": "显示", "price": 1299},
+            {"id": 2, "name": "鼠标", "category": "外设", "price": 99},
+            {"id": 4, "name": "线缆", "category": "配件", "price": 9}
+        ]);
+
+        let test_cases = vec![
+            (
+                1,
+                "过滤 + 排序 + 取前N个",
+                products.clone(),
+                vec![("array_ops", {
+                    let mut config = HashMap::new();
+                    config.insert("operations".to_string(), json!([
+                        {"op": "filter", "condition": "$item.price >= 99"},
+                        {"op": "dedupe", "by": "id"},
+                        {"op": "sort", "by": "$item.price", "order": "desc"},
+                        {"op": "limit", "count": 2}
+                    ]));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!([
+                    {"id": 3, "name": "显示器", "category": "显示", "price": 1299},
+                    {"id": 1, "name": "键盘", "category": "外设", "price": 199}
+                ])),
+            ),
+            (
+                2,
+                "组合条件过滤",
+                products.clone(),
+                vec![("array_ops", {
+                    let mut config = HashMap::new();
+                    config.insert("operations".to_string(), json!([
+                        {"op": "filter", "condition": "$item.category == \"外设\" && $item.price < 100"},
+                        {"op": "dedupe"}
+                    ]));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!([{"id": 2, "name": "鼠标", "category": "外设", "price": 99}])),
+            ),
+            (
+                3,
+                "整体聚合",
+                products.clone(),
+                vec![("array_ops", {
+                    let mut config = HashMap::new();
+                    config.insert("operations".to_string(), json!([
+                        {"op": "aggregate", "field": "price", "functions": ["count", "sum", "min", "max"]}
+                    ]));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!({"count": 5, "sum": 1705, "min": 9, "max": 1299})),
+            ),
+            (
+                4,
+                "分组后聚合",
+                products.clone(),
+                vec![("array_ops", {
+                    let mut config = HashMap::new();
+                    config.insert("operations".to_string(), json!([
+                        {"op": "dedupe", "by": "$item.id"},
+                        {"op": "group", "by": "category"},
+                        {"op": "aggregate", "field": "price", "functions": ["count", "avg"]}
+                    ]));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!({
+                    "外设": {"count": 2, "avg": 149},
+                    "显示": {"count": 1, "avg": 1299},
+                    "配件": {"count": 1, "avg": 9}
+                })),
+            ),
+            (
+                5,
+                "提取数组后处理",
+                json!({"order": {"items": [{"sku": "b", "qty": 2}, {"sku": "a", "qty": 5}]}}),
+                vec![
+                    ("json_extract", {
+                        let mut config = HashMap::new();
+                        config.insert("pattern".to_string(), json!("$.order.items"));
+                        config
+                    }),
+                    ("array_ops", {
+                        let mut config = HashMap::new();
+                        config.insert("operations".to_string(), json!([
+                            {"op": "sort", "by": "sku"}
+                        ]));
+                        config
+                    }),
+                ],
+                ChainStatus::Completed,
+                None,
+                Some(json!([{"sku": "a", "qty": 5}, {"sku": "b", "qty": 2}])),
+            ),
+            (
+                6,
+                "非数组输入报错",
+                json!({"name": "张三"}),
+                vec![("array_ops", {
+                    let mut config = HashMap::new();
+                    config.insert("operations".to_string(), json!([{"op": "sort"}]));
+                    config
+                })],
+                ChainStatus::Error,
+                None,
+                None,
+            ),
+        ];
+
+        run_test_cases(test_cases, "array_ops挂件测试", 1.0);
+    }
+
+    #[test]
+    fn test_set_var() {
+        let test_cases = vec![
+            (
+                1,
+                "保存中间结果供后续节点使用",
+                json!({"user": {"name": "张三", "level": 3}, "order": {"amount": 100}}),
+                vec![
+                    ("set_var", {
+                        let mut config = HashMap::new();
+                        config.insert("vars".to_string(), json!({"user": "$.user"}));
+                        config
+                    }),
+                    ("json_extract", {
+                        let mut config = HashMap::new();
+                        config.insert("pattern".to_string(), json!("$.order"));
+                        config
+                    }),
+                    ("map_fields", {
+                        let mut config = HashMap::new();
+                        config.insert("mapping".to_string(), json!({
+                            "buyer": "$vars.user.name",
+                            "note": "${$vars.user.name}的订单"
+                        }));
+                        config
+                    }),
+                ],
+                ChainStatus::Completed,
+                None,
+                Some(json!({"amount": 100, "buyer": "张三", "note": "张三的订单"})),
+            ),
+            (
+                2,
+                "条件判断读取变量",
+                json!({"user": {"level": 1}, "items": []}),
+                vec![
+                    ("set_var", {
+                        let mut config = HashMap::new();
+                        config.insert("vars".to_string(), json!({"level": "$.user.level"}));
+                        config
+                    }),
+                    ("json_extract", {
+                        let mut config = HashMap::new();
+                        config.insert("pattern".to_string(), json!("$.items"));
+                        config
+                    }),
+                    ("condition", {
+                        let mut config = HashMap::new();
+                        config.insert("expression".to_string(), json!("$vars.level >= 2"));
+                        config
+                    }),
+                ],
+                ChainStatus::Reject,
+                None,
+                None,
+            ),
+            (
+                3,
+                "缺少vars配置",
+                json!({"name": "张三"}),
+                vec![("set_var", {
+                    let mut config = HashMap::new();
+                    config.insert("vars".to_string(), json!(["user"]));
+                    config
+                })],
+                ChainStatus::Error,
+                None,
+                None,
+            ),
+        ];
+
+        run_test_cases(test_cases, "set_var挂件测试", 1.0);
+    }
+
+    #[test]
+    fn test_vars_in_response() {
+        let executor = ChainExecutor::new()
+            .add_chainwares(json!([
+                {"name": "set_var", "config": {"vars": {
+                    "name": "$.name",
+                    "ip": "$meta.ip_address",
+                    "greeting": "你好，${$.name}",
+                    "retries": 3,
+                    "label": "vip",
+                    "missing": "$.not_exists"
+                }}},
+                {"name": "map_fields", "config": {"mapping": {"name": "$params.name"}, "overwrite": false}},
+                // 后设置的同名变量覆盖之前的值，可以引用已有变量
+                {"name": "set_var", "config": {"vars": {"retries": 5, "summary": "${$vars.greeting}（${$vars.ip}）"}}}
+            ]))
+            .unwrap();
+
+        let response = executor.execute(create_context_with_ip(json!({"name": "李四"}), Some("10.0.0.1")));
+        assert_eq!(response.status, ChainStatus::Completed);
+        assert_eq!(response.data, Some(json!({"name": "李四"})));
+        assert_eq!(response.get_var("name"), Some(&json!("李四")));
+        assert_eq!(response.get_var("greeting"), Some(&json!("你好，李四")));
+        assert_eq!(response.get_var("retries"), Some(&json!(5)));
+        assert_eq!(response.get_var("label"), Some(&json!("vip")));
+        assert_eq!(response.get_var("missing"), Some(&Value::Null));
+        assert_eq!(response.get_var("summary"), Some(&json!("你好，李四（10.0.0.1）")));
+        assert_eq!(response.vars.len(), 7);
+
+        // 每次执行的变量相互独立
+        let response = executor.execute(create_context_with_ip(json!({"name": "王五"}), Some("10.0.0.2")));
+        assert_eq!(response.get_var("name"), Some(&json!("王五")));
+    }
+
     #[test]
     fn test_data_processing_chains() {
         let test_cases = vec![
@@ -0,0 +1,178 @@
+//! 节点数据路由测试
+//!
+//! 测试 ChainwareWrapper 对 input_path 和 output_path 的处理
+
+use linkchain::chain::executor::ChainExecutor;
+use linkchain::chainware::config::ChainwareConfig;
+use linkchain::core::{ChainRequest, ChainResponse, ChainStatus};
+use linkchain::types::error_codes;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn execute(configs: Value, params: Value) -> ChainResponse {
+        let executor = ChainExecutor::new().add_chainwares(configs).unwrap();
+        executor.execute(ChainRequest::new(params, HashMap::new()))
+    }
+
+    #[test]
+    fn test_process_single_field() {
+        // regexp_extract只处理text字段，结果写回order_id，其余字段保留
+        let response = execute(
+            json!([{"name": "regexp_extract", "config": {
+                "pattern": "订单号: ([A-Z]\\d+)",
+                "group": 1,
+                "input_path": "$.text",
+                "output_path": "$.order_id"
+            }}]),
+            json!({"text": "您的订单号: A123 已发货", "user": "张三"}),
+        );
+        assert_eq!(response.status, ChainStatus::Completed);
+        assert_eq!(
+            response.data,
+            Some(json!({"text": "您的订单号: A123 已发货", "user": "张三", "order_id": "A123"}))
+        );
+    }
+
+    #[test]
+    fn test_input_path_only() {
+        // 只配置input_path时结果替换整个数据
+        let response = execute(
+            json!([
+                {"name": "convert", "config": {"fields": {"$.age": "int"}, "input_path": "$.profile"}}
+            ]),
+            json!({"profile": {"age": "30"}, "other": 1}),
+        );
+        assert_eq!(response.data, Some(json!({"age": 30})));
+
+        // input_path可以选取请求参数
+        let response = execute(
+            json!([
+                {"name": "json_extract", "config": {"pattern": "$.name"}},
+                {"name": "extract_map", "config": {"mapping": {"id": "$.id"}, "input_path": "$params.user"}}
+            ]),
+            json!({"name": "张三", "user": {"id": 7}}),
+        );
+        assert_eq!(response.data, Some(json!({"id": 7})));
+
+        // 路径不存在时输入为null
+        let response = execute(
+            json!([{"name": "logger", "config": {"input_path": "$.missing", "output_path": "$.copy"}}]),
+            json!({"a": 1}),
+        );
+        assert_eq!(response.data, Some(json!({"a": 1, "copy": null})));
+    }
+
+    #[test]
+    fn test_output_path_only() {
+        // 只配置output_path时挂件处理整个数据，结果写入指定位置，中间对象自动创建
+        let response = execute(
+            json!([{"name": "extract_map", "config": {
+                "mapping": {"display": "${$.name}（${$.age}岁）"},
+                "output_path": "$.view.summary"
+            }}]),
+            json!({"name": "张三", "age": 25}),
+        );
+        assert_eq!(
+            response.data,
+            Some(json!({"name": "张三", "age": 25, "view": {"summary": {"display": "张三（25岁）"}}}))
+        );
+
+        // 数组下标和引号字段名
+        let response = execute(
+            json!([
+                {"name": "convert", "config": {"fields": {"$": "int"}, "input_path": "$.items[0].qty", "output_path": "$.items[0]['qty']"}},
+                {"name": "convert", "config": {"fields": {"$": "string"}, "input_path": "$.items[1].qty", "output_path": "$.items[1][\"qty text\"]"}}
+            ]),
+            json!({"items": [{"qty": "2"}, {"qty": 3}]}),
+        );
+        assert_eq!(response.data, Some(json!({"items": [{"qty": 2}, {"qty": 3, "qty text": "3"}]})));
+    }
+
+    #[test]
+    fn test_output_to_vars() {
+        // 结果写入链变量，数据保持不变
+        let response = execute(
+            json!([
+                {"name": "extract_json", "config": {"input_path": "$.llm_output", "output_path": "$vars.answer"}},
+                {"name": "json_extract", "config": {"pattern": "$.id", "output_path": "$vars.meta.request_id"}},
+                {"name": "condition", "config": {"expression": "$vars.answer.ok == true"}}
+            ]),
+            json!({"id": "r-1", "llm_output": "结果：{\"ok\": true, \"score\": 0.9}"}),
+        );
+        assert_eq!(response.status, ChainStatus::Completed);
+        assert_eq!(response.data, Some(json!({"id": "r-1", "llm_output": "结果：{\"ok\": true, \"score\": 0.9}"})));
+        assert_eq!(response.get_var("answer"), Some(&json!({"ok": true, "score": 0.9})));
+        assert_eq!(response.get_var("meta"), Some(&json!({"request_id": "r-1"})));
+    }
+
+    #[test]
+    fn test_closure_routing() {
+        let mut config = HashMap::new();
+        config.insert("input_path".to_string(), json!("$.name"));
+        config.insert("output_path".to_string(), json!("$.upper"));
+
+        let executor = ChainExecutor::new()
+            .insert_chainware(
+                "upper",
+                None,
+                Some(|_: &ChainRequest, _: &mut ChainResponse, data: Option<Value>, _: Option<&ChainwareConfig>| {
+                    data.and_then(|v| v.as_str().map(|s| json!(s.to_uppercase())))
+                }),
+                Some(ChainwareConfig::new(config)),
+            )
+            .unwrap();
+
+        let response = executor.execute(ChainRequest::new(json!({"name": "alice"}), HashMap::new()));
+        assert_eq!(response.data, Some(json!({"name": "alice", "upper": "ALICE"})));
+    }
+
+    #[test]
+    fn test_routing_with_reject_and_disabled() {
+        // 节点拒绝时不写回数据，保留错误信息
+        let response = execute(
+            json!([{"name": "condition", "config": {
+                "expression": "$.age >= 18",
+                "input_path": "$.user",
+                "output_path": "$.checked"
+            }}]),
+            json!({"user": {"age": 16}}),
+        );
+        assert_eq!(response.status, ChainStatus::Reject);
+        assert!(response.data.unwrap().get("checked").is_none());
+
+        // 禁用的节点忽略路由配置
+        let response = execute(
+            json!([{"name": "json_extract", "config": {
+                "pattern": "$.a",
+                "enabled": false,
+                "input_path": "$.x",
+                "output_path": "$.y"
+            }}]),
+            json!({"x": {"a": 1}}),
+        );
+        assert_eq!(response.data, Some(json!({"x": {"a": 1}})));
+    }
+
+    #[test]
+    fn test_routing_errors() {
+        let cases = vec![
+            (json!({"output_path": "result"}), json!({}), "写入路径必须以$开头"),
+            (json!({"output_path": "$.a[?(@.b)]"}), json!({}), "不支持的写入路径"),
+            (json!({"output_path": "$vars"}), json!({}), "缺少变量名"),
+            (json!({"output_path": "$.name.first"}), json!({"name": "张三"}), "output_path '$.name.first' 写入失败"),
+            (json!({"input_path": "$.items[?(@.price >"}), json!({"items": []}), "input_path '$.items[?(@.price >' 解析失败"),
+        ];
+
+        for (config, params, expected) in cases {
+            let response = execute(json!([{"name": "logger", "config": config}]), params);
+            assert_eq!(response.status, ChainStatus::Error, "{}", config);
+            let data = response.data.unwrap();
+            assert_eq!(data["errno"], json!(error_codes::CONFIG_ERROR));
+            assert!(data["msg"].as_str().unwrap().contains(expected), "{}: {}", config, data["msg"]);
+        }
+    }
+}
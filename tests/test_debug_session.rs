@@ -0,0 +1,126 @@
+//! 调试会话测试
+//!
+//! 测试 ChainExecutor::debug_session 的单步执行、断点、数据修改和回退
+
+use linkchain::chain::executor::ChainExecutor;
+use linkchain::chain::debug::{Breakpoint, DebugStop};
+use linkchain::core::{ChainRequest, ChainStatus};
+use serde_json::json;
+use std::collections::HashMap;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 创建测试链：map_fields -> condition -> patch
+    fn create_executor() -> ChainExecutor {
+        ChainExecutor::new()
+            .add_chainwares(json!([
+                {"name": "map_fields", "config": {"mapping": {"years": "$.age"}}},
+                {"name": "condition", "config": {"expression": "$.years >= 18"}},
+                {"name": "patch", "config": {"operations": [{"op": "add", "path": "/verified", "value": true}]}}
+            ]))
+            .unwrap()
+    }
+
+    fn create_request(age: i64) -> ChainRequest {
+        ChainRequest::new(json!({"name": "张三", "age": age}), HashMap::new())
+    }
+
+    #[test]
+    fn test_step_through_matches_execute() {
+        let executor = create_executor();
+        let expected = executor.execute(create_request(25));
+
+        let mut session = executor.debug_session(create_request(25));
+        assert_eq!(session.next_node(), Some("map_fields"));
+
+        let step = session.step().unwrap().clone();
+        assert_eq!(step.index, 0);
+        assert_eq!(step.node_name, "map_fields");
+        assert_eq!(step.input, json!({"name": "张三", "age": 25}));
+        assert_eq!(session.data(), &step.output);
+        assert_eq!(session.next_node(), Some("condition"));
+
+        while session.step().is_some() {}
+        assert!(session.is_finished());
+        assert_eq!(session.history().len(), 3);
+
+        let response = session.finish();
+        assert_eq!(response.status, expected.status);
+        assert_eq!(response.data, expected.data);
+    }
+
+    #[test]
+    fn test_breakpoint_and_modify_data() {
+        let executor = create_executor();
+        let mut session = executor.debug_session(create_request(16));
+        session.add_breakpoint(Breakpoint::Node("condition".to_string()));
+
+        // 停在condition节点之前
+        let stop = session.run();
+        assert_eq!(stop, DebugStop::Breakpoint { index: 1, node_name: "condition".to_string() });
+        assert_eq!(session.data()["years"], json!(16));
+
+        // 修改数据后继续执行
+        session.data_mut()["years"] = json!(20);
+        assert_eq!(session.run(), DebugStop::Finished(ChainStatus::Completed));
+        assert_eq!(session.data()["verified"], json!(true));
+    }
+
+    #[test]
+    fn test_status_change_breakpoint() {
+        let executor = create_executor();
+        let mut session = executor.debug_session(create_request(16));
+        session.add_breakpoint(Breakpoint::StatusChange);
+
+        // condition拒绝时暂停，并可查看拒绝原因
+        let stop = session.run();
+        assert_eq!(
+            stop,
+            DebugStop::StatusChanged { index: 1, from: ChainStatus::Continue, to: ChainStatus::Reject }
+        );
+        let step = session.history().last().unwrap();
+        assert_eq!(step.node_name, "condition");
+        assert_eq!(step.input["years"], json!(16));
+        assert!(step.response_data.as_ref().unwrap()["msg"].is_string());
+        assert!(session.is_finished());
+        assert!(session.step().is_none());
+    }
+
+    #[test]
+    fn test_rewind_and_replay() {
+        let executor = create_executor();
+        let mut session = executor.debug_session(create_request(16));
+        assert_eq!(session.run(), DebugStop::Finished(ChainStatus::Reject));
+        assert_eq!(session.history().len(), 2);
+
+        // 回退到condition执行之前，修改数据后重新执行
+        assert!(session.rewind(1));
+        assert_eq!(session.position(), 1);
+        assert_eq!(session.history().len(), 1);
+        assert_eq!(session.response().status, ChainStatus::Continue);
+        session.data_mut()["years"] = json!(30);
+        assert_eq!(session.run(), DebugStop::Finished(ChainStatus::Completed));
+
+        // 回退到初始状态，修改请求参数
+        assert!(session.rewind(0));
+        assert_eq!(session.data(), &json!({"name": "张三", "age": 16}));
+        session.add_breakpoint(Breakpoint::Index(2));
+        session.set_data(json!({"name": "李四", "age": 40}));
+        assert_eq!(session.run(), DebugStop::Breakpoint { index: 2, node_name: "patch".to_string() });
+
+        // 回退同时恢复请求上下文的修改
+        session.request_mut().meta.insert("trace".to_string(), json!("edited"));
+        assert!(session.rewind(1));
+        assert!(!session.request().meta.contains_key("trace"));
+        assert_eq!(session.data()["name"], json!("李四"));
+
+        // 超出范围的回退
+        assert!(!session.rewind(5));
+
+        let response = session.finish();
+        assert_eq!(response.status, ChainStatus::Completed);
+        assert_eq!(response.data.unwrap()["verified"], json!(true));
+    }
+}
@@ -0,0 +1,228 @@
+//! 错误处理测试
+//!
+//! 测试节点级、链级on_error处理子链和continue_on_error
+
+use linkchain::chain::executor::ChainExecutor;
+use linkchain::chainware::config::ChainwareConfig;
+use linkchain::core::{ChainRequest, ChainResponse, ChainStatus};
+use linkchain::types::{error_codes, ErrorResponse};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn execute(executor: &ChainExecutor, params: Value) -> ChainResponse {
+        executor.execute(ChainRequest::new(params, HashMap::new()))
+    }
+
+    /// 严格转换age字段的节点，age不是数字时出错
+    fn convert_node(extra: Value) -> Value {
+        let mut config = json!({"fields": {"$.age": "int"}, "mode": "strict"});
+        config.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+        json!({"name": "convert", "config": config})
+    }
+
+    #[test]
+    fn test_continue_on_error() {
+        let executor = ChainExecutor::new()
+            .add_chainwares(json!([
+                convert_node(json!({"continue_on_error": true})),
+                {"name": "patch", "config": {"operations": [{"op": "add", "path": "/checked", "value": true}]}}
+            ]))
+            .unwrap();
+
+        let response = execute(&executor, json!({"id": 1, "age": "未知"}));
+        assert_eq!(response.status, ChainStatus::Completed);
+        assert_eq!(response.data, Some(json!({"id": 1, "age": "未知", "checked": true})));
+
+        let recovered = response.meta["recovered_errors"].as_array().unwrap();
+        assert_eq!(recovered.len(), 1);
+        assert_eq!(recovered[0]["node_index"], json!(0));
+        assert_eq!(recovered[0]["node_name"], json!("convert"));
+        assert_eq!(recovered[0]["error"]["errno"], json!(error_codes::VALIDATION_FAILED));
+        assert!(!response.meta.contains_key("error_details"));
+
+        // 未出错时不记录
+        let response = execute(&executor, json!({"id": 1, "age": "30"}));
+        assert_eq!(response.data.unwrap()["age"], json!(30));
+        assert!(!response.meta.contains_key("recovered_errors"));
+    }
+
+    #[test]
+    fn test_node_on_error_recover() {
+        // 处理子链收到错误、节点信息和节点输入，返回替代数据后链继续执行
+        let executor = ChainExecutor::new()
+            .add_chainwares(json!([
+                convert_node(json!({"on_error": [
+                    {"name": "extract_map", "config": {"mapping": {
+                        "id": "$.data.id",
+                        "failed_node": "$.node.name",
+                        "failed_index": "$.node.index",
+                        "reason": "$.error.errno"
+                    }}}
+                ]})),
+                {"name": "patch", "config": {"operations": [{"op": "add", "path": "/fallback", "value": true}]}}
+            ]))
+            .unwrap();
+
+        let response = execute(&executor, json!({"id": 7, "age": "未知"}));
+        assert_eq!(response.status, ChainStatus::Completed);
+        assert_eq!(
+            response.data,
+            Some(json!({
+                "id": 7,
+                "failed_node": "convert",
+                "failed_index": 0,
+                "reason": error_codes::VALIDATION_FAILED,
+                "fallback": true
+            }))
+        );
+        assert_eq!(response.meta["recovered_errors"][0]["node_name"], json!("convert"));
+    }
+
+    #[test]
+    fn test_node_on_error_custom_response() {
+        // recover为false时子链输出作为错误响应
+        let executor = ChainExecutor::new()
+            .add_chainwares(json!([
+                convert_node(json!({"on_error": {
+                    "chain": [{"name": "extract_map", "config": {"mapping": {
+                        "code": "$.error.errno",
+                        "message": "年龄格式错误：${$.error.msg}"
+                    }}}],
+                    "recover": false
+                }})),
+                {"name": "patch", "config": {"operations": [{"op": "add", "path": "/unreachable", "value": true}]}}
+            ]))
+            .unwrap();
+
+        let response = execute(&executor, json!({"age": "未知"}));
+        assert_eq!(response.status, ChainStatus::Error);
+        let data = response.data.unwrap();
+        assert_eq!(data["code"], json!(error_codes::VALIDATION_FAILED));
+        assert!(data["message"].as_str().unwrap().starts_with("年龄格式错误：类型转换失败"));
+        assert_eq!(response.meta["error_details"]["node_name"], json!("convert"));
+        assert!(!response.meta.contains_key("recovered_errors"));
+    }
+
+    #[test]
+    fn test_chain_on_error() {
+        let executor = ChainExecutor::new()
+            .add_chainwares(json!([
+                convert_node(json!({})),
+                convert_node(json!({"on_error": [{"name": "extract_map", "config": {"mapping": {"raw_age": "$.data.age"}}}]}))
+            ]))
+            .unwrap()
+            .on_error(json!([
+                {"name": "set_var", "config": {"vars": {"error_code": "$.error.errno"}}},
+                {"name": "json_extract", "config": {"pattern": "$.data"}}
+            ]))
+            .unwrap();
+
+        // 第一个节点没有配置on_error，由链级处理；第二个节点使用自己的处理子链
+        let response = execute(&executor, json!({"age": "未知"}));
+        assert_eq!(response.status, ChainStatus::Completed);
+        assert_eq!(response.data, Some(json!({"raw_age": "未知"})));
+        assert_eq!(response.get_var("error_code"), Some(&json!(error_codes::VALIDATION_FAILED)));
+        let recovered = response.meta["recovered_errors"].as_array().unwrap();
+        assert_eq!(recovered.len(), 2);
+        assert_eq!(recovered[0]["node_index"], json!(0));
+        assert_eq!(recovered[1]["node_index"], json!(1));
+    }
+
+    #[test]
+    fn test_handler_terminates_chain() {
+        // 处理子链拒绝时，链以子链的状态结束
+        let executor = ChainExecutor::new()
+            .add_chainwares(json!([
+                convert_node(json!({"on_error": [{"name": "condition", "config": {"expression": "$.data.retry == true"}}]}))
+            ]))
+            .unwrap();
+        let response = execute(&executor, json!({"age": "未知", "retry": false}));
+        assert_eq!(response.status, ChainStatus::Reject);
+        assert_eq!(response.data.unwrap()["errno"], json!(error_codes::CONDITION_NOT_MET));
+
+        // 链级处理子链同样处理自定义回调的错误
+        let executor = ChainExecutor::new()
+            .insert_chainware(
+                "fail",
+                None,
+                Some(|_: &ChainRequest, response: &mut ChainResponse, _: Option<Value>, _: Option<&ChainwareConfig>| {
+                    response.status = ChainStatus::Error;
+                    response.data = Some(ErrorResponse::new(error_codes::INTERNAL_ERROR, "失败".to_string(), None).to_json());
+                    None
+                }),
+                None,
+            )
+            .unwrap()
+            .on_error(json!([{"name": "json_extract", "config": {"pattern": "$.error.msg"}}]))
+            .unwrap();
+        let response = execute(&executor, json!({}));
+        assert_eq!(response.status, ChainStatus::Completed);
+        assert_eq!(response.data, Some(json!("失败")));
+
+        // 处理子链本身出错时返回子链的错误
+        let executor = ChainExecutor::new()
+            .add_chainwares(json!([
+                convert_node(json!({"on_error": [{"name": "convert", "config": {"fields": {"$.data.age": "int"}, "mode": "strict"}}]}))
+            ]))
+            .unwrap();
+        let response = execute(&executor, json!({"age": "未知"}));
+        assert_eq!(response.status, ChainStatus::Error);
+        assert_eq!(response.meta["error_details"]["node_index"], json!(0));
+    }
+
+    #[test]
+    fn test_on_error_config_errors() {
+        let cases = vec![
+            (json!("fallback"), "on_error配置必须是挂件数组或对象"),
+            (json!({"recover": true}), "on_error配置缺少chain字段"),
+            (json!({"chain": [], "recover": "yes"}), "on_error的recover必须是布尔类型"),
+            (json!([{"name": "no_such_chainware"}]), "on_error子链配置错误"),
+        ];
+
+        for (on_error, expected) in cases {
+            let err = ChainExecutor::new()
+                .add_chainwares(json!([{"name": "logger", "config": {"on_error": on_error}}]))
+                .err()
+                .unwrap();
+            assert_eq!(err.errno, error_codes::CONFIG_ERROR);
+            assert!(err.msg.contains(expected), "{}", err.msg);
+
+            let err = ChainExecutor::new().on_error(on_error).err().unwrap();
+            assert!(err.msg.contains(expected), "{}", err.msg);
+        }
+    }
+
+    #[test]
+    fn test_from_definition() {
+        let executor = ChainExecutor::from_definition(json!({
+            "chain": [convert_node(json!({}))],
+            "on_error": {"chain": [{"name": "json_extract", "config": {"pattern": "$.data"}}], "recover": true}
+        }))
+        .unwrap();
+        let response = execute(&executor, json!({"age": "未知"}));
+        assert_eq!(response.status, ChainStatus::Completed);
+        assert_eq!(response.data, Some(json!({"age": "未知"})));
+
+        // 链定义可以重新加载为相同的链
+        let reloaded = ChainExecutor::from_definition(executor.definition()).unwrap();
+        assert_eq!(reloaded.chain_hash(), executor.chain_hash());
+        let array_form = ChainExecutor::from_definition(json!([convert_node(json!({}))])).unwrap();
+        assert_eq!(array_form.definition(), json!([convert_node(json!({}))]));
+
+        let cases = vec![
+            (json!("logger"), "链定义必须是挂件数组或包含chain字段的对象"),
+            (json!({"on_error": []}), "链定义缺少chain字段"),
+            (json!({"chain": [], "onerror": []}), "链定义中不支持的字段: onerror"),
+            (json!({"chain": [], "on_error": "fallback"}), "on_error配置必须是挂件数组或对象"),
+        ];
+        for (definition, expected) in cases {
+            let err = ChainExecutor::from_definition(definition).err().unwrap();
+            assert_eq!(err.errno, error_codes::CONFIG_ERROR);
+            assert!(err.msg.contains(expected), "{}", err.msg);
+        }
+    }
+}
@@ -1,6 +1,6 @@
 //! 提取类挂件测试
 //!
-//! 测试 extract_map, extract_json, extract_sql, json_extract, regexp_extract 挂件
+//! 测试 extract_map, extract_json, extract_sql, extract_code, json_extract, regexp_extract 挂件
 
 include!("common.rs");
 
@@ -172,6 +172,92 @@ mod tests {
         run_test_cases(test_cases, "extract_json挂件测试", 0.8);
     }
 
+    #[test]
+    fn test_extract_json_repair() {
+        let test_cases = vec![
+            (
+                1,
+                "字符串中包含括号",
+                json!("结果: {\"msg\": \"使用 } 和 { 符号\", \"ok\": true}"),
+                vec![("extract_json", HashMap::new())],
+                ChainStatus::Completed,
+                None,
+                Some(json!({"msg": "使用 } 和 { 符号", "ok": true})),
+            ),
+            (
+                2,
+                "修复常见LLM格式缺陷",
+                json!("```json\n{\n  // 用户信息\n  name: '张三',\n  \"active\": True,\n  \"tags\": [\"a\", \"b\",],\n  \"extra\": None,\n}\n```"),
+                vec![("extract_json", {
+                    let mut config = HashMap::new();
+                    config.insert("repair".to_string(), json!(true));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!({"name": "张三", "active": true, "tags": ["a", "b"], "extra": null})),
+            ),
+            (
+                3,
+                "修复被截断的输出",
+                json!("{\"items\": [{\"id\": 1}, {\"id\": 2, \"name\": \"商"),
+                vec![("extract_json", {
+                    let mut config = HashMap::new();
+                    config.insert("repair".to_string(), json!(true));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!({"items": [{"id": 1}, {"id": 2, "name": "商"}]})),
+            ),
+            (
+                4,
+                "返回所有JSON值",
+                json!("第一个 {\"a\": 1} 第二个 [1, 2] 第三个 {\"b\": {\"c\": 2}}"),
+                vec![("extract_json", {
+                    let mut config = HashMap::new();
+                    config.insert("mode".to_string(), json!("all"));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!([{"a": 1}, [1, 2], {"b": {"c": 2}}])),
+            ),
+            (
+                5,
+                "返回最大的JSON值",
+                json!("示例 {\"a\": 1} 完整结果 {\"a\": 1, \"b\": [1, 2, 3]}"),
+                vec![("extract_json", {
+                    let mut config = HashMap::new();
+                    config.insert("mode".to_string(), json!("largest"));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!({"a": 1, "b": [1, 2, 3]})),
+            ),
+            (
+                6,
+                "返回匹配Schema的JSON值",
+                json!("思考过程 {\"step\": 1} 最终答案 {\"sql\": \"SELECT 1\", \"confidence\": 0.9}"),
+                vec![("extract_json", {
+                    let mut config = HashMap::new();
+                    config.insert("schema".to_string(), json!({
+                        "type": "object",
+                        "required": ["sql"],
+                        "properties": {"sql": {"type": "string"}}
+                    }));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!({"sql": "SELECT 1", "confidence": 0.9})),
+            ),
+        ];
+
+        run_test_cases(test_cases, "extract_json修复模式测试", 1.0);
+    }
+
     #[test]
     fn test_extract_sql() {
         let test_cases = vec![
@@ -225,6 +311,147 @@ mod tests {
         run_test_cases(test_cases, "extract_sql挂件测试", 0.8);
     }
 
+    #[test]
+    fn test_extract_code() {
+        let llm_output = "先安装依赖：\n```bash\npip install requests\n```\n然后运行：\n```py\nimport requests\nprint(requests.get(url).status_code)\n```\n配置文件：\n```yaml\nname: demo\n```\n```\nplain text\n```";
+
+        let test_cases = vec![
+            (
+                1,
+                "提取第一个指定语言代码块",
+                json!(llm_output),
+                vec![("extract_code", {
+                    let mut config = HashMap::new();
+                    config.insert("language".to_string(), json!("python"));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!({"language": "python", "code": "import requests\nprint(requests.get(url).status_code)"})),
+            ),
+            (
+                2,
+                "提取多种语言的所有代码块",
+                json!(llm_output),
+                vec![("extract_code", {
+                    let mut config = HashMap::new();
+                    config.insert("language".to_string(), json!(["shell", "yml"]));
+                    config.insert("mode".to_string(), json!("all"));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!([
+                    {"language": "shell", "code": "pip install requests"},
+                    {"language": "yaml", "code": "name: demo"}
+                ])),
+            ),
+            (
+                3,
+                "包含无标签代码块",
+                json!(llm_output),
+                vec![("extract_code", {
+                    let mut config = HashMap::new();
+                    config.insert("language".to_string(), json!("yaml"));
+                    config.insert("include_unlabeled".to_string(), json!(true));
+                    config.insert("mode".to_string(), json!("all"));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!([
+                    {"language": "yaml", "code": "name: demo"},
+                    {"language": null, "code": "plain text"}
+                ])),
+            ),
+            (
+                4,
+                "SQL代码块拆分所有语句",
+                json!("```sql\nINSERT INTO logs VALUES ('a;b');\n-- 注释; 不拆分\nUPDATE users SET n = 1;\nSELECT 1\n```"),
+                vec![("extract_code", {
+                    let mut config = HashMap::new();
+                    config.insert("language".to_string(), json!("sql"));
+                    config.insert("split_statements".to_string(), json!(true));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!({
+                    "language": "sql",
+                    "code": "INSERT INTO logs VALUES ('a;b');\n-- 注释; 不拆分\nUPDATE users SET n = 1;\nSELECT 1",
+                    "statements": ["INSERT INTO logs VALUES ('a;b');", "UPDATE users SET n = 1;", "SELECT 1"]
+                })),
+            ),
+            (
+                5,
+                "无代码块时直接匹配SQL语句",
+                json!("先执行 DELETE FROM t WHERE id = 1; 再执行 SELECT * FROM t;"),
+                vec![("extract_code", {
+                    let mut config = HashMap::new();
+                    config.insert("language".to_string(), json!("sql"));
+                    config.insert("split_statements".to_string(), json!(true));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!({
+                    "language": "sql",
+                    "code": "DELETE FROM t WHERE id = 1;\nSELECT * FROM t;",
+                    "statements": ["DELETE FROM t WHERE id = 1;", "SELECT * FROM t;"]
+                })),
+            ),
+            (
+                6,
+                "未找到代码块返回null",
+                json!("没有代码"),
+                vec![("extract_code", {
+                    let mut config = HashMap::new();
+                    config.insert("language".to_string(), json!("json"));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!(Value::Null)),
+            ),
+            (
+                7,
+                "块注释中的分号不拆分",
+                json!("```sql\nSELECT 1 /* 注释; 不拆分 */ FROM t;\n/* 多行\n注释; */UPDATE t SET a = 1;\n```"),
+                vec![("extract_code", {
+                    let mut config = HashMap::new();
+                    config.insert("language".to_string(), json!("sql"));
+                    config.insert("split_statements".to_string(), json!(true));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!({
+                    "language": "sql",
+                    "code": "SELECT 1 /* 注释; 不拆分 */ FROM t;\n/* 多行\n注释; */UPDATE t SET a = 1;",
+                    "statements": ["SELECT 1   FROM t;", "UPDATE t SET a = 1;"]
+                })),
+            ),
+            (
+                8,
+                "WITH只匹配公用表表达式",
+                json!("Start with the following; 然后执行 WITH recent AS (SELECT * FROM t) SELECT * FROM recent;"),
+                vec![("extract_code", {
+                    let mut config = HashMap::new();
+                    config.insert("language".to_string(), json!("sql"));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!({
+                    "language": "sql",
+                    "code": "WITH recent AS (SELECT * FROM t) SELECT * FROM recent;"
+                })),
+            ),
+        ];
+
+        run_test_cases(test_cases, "extract_code挂件测试", 1.0);
+    }
+
     #[test]
     fn test_json_extract() {
         let test_cases = vec![
@@ -371,6 +598,158 @@ mod tests {
         run_test_cases(test_cases, "regexp_extract挂件测试", 0.8);
     }
 
+    #[test]
+    fn test_regexp_extract_advanced() {
+        let test_cases = vec![
+            (
+                1,
+                "提取所有匹配",
+                json!("订单: 1001, 1002, 1003"),
+                vec![("regexp_extract", {
+                    let mut config = HashMap::new();
+                    config.insert("pattern".to_string(), json!(r"\d+"));
+                    config.insert("mode".to_string(), json!("all"));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!(["1001", "1002", "1003"])),
+            ),
+            (
+                2,
+                "命名分组按output返回对象",
+                json!("name=alice age=30"),
+                vec![("regexp_extract", {
+                    let mut config = HashMap::new();
+                    config.insert("pattern".to_string(), json!(r"(?P<key>\w+)=(?P<value>\w+)"));
+                    config.insert("mode".to_string(), json!("all"));
+                    config.insert("output".to_string(), json!("object"));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!([{"key": "name", "value": "alice"}, {"key": "age", "value": "30"}])),
+            ),
+            (
+                3,
+                "返回指定分组",
+                json!("版本: v2.15.3"),
+                vec![("regexp_extract", {
+                    let mut config = HashMap::new();
+                    config.insert("pattern".to_string(), json!(r"v(\d+)\.(\d+)\.(\d+)"));
+                    config.insert("group".to_string(), json!(2));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!("15")),
+            ),
+            (
+                4,
+                "替换模式",
+                json!("2024-01-15 和 2024-02-20"),
+                vec![("regexp_extract", {
+                    let mut config = HashMap::new();
+                    config.insert("pattern".to_string(), json!(r"(?P<y>\d{4})-(\d{2})-(\d{2})"));
+                    config.insert("mode".to_string(), json!("replace"));
+                    config.insert("replacement".to_string(), json!("$3/$2/${y}"));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!("15/01/2024 和 20/02/2024")),
+            ),
+            (
+                5,
+                "忽略大小写和多行标志",
+                json!("status: OK\nSTATUS: failed"),
+                vec![("regexp_extract", {
+                    let mut config = HashMap::new();
+                    config.insert("pattern".to_string(), json!(r"^status: (\w+)$"));
+                    config.insert("mode".to_string(), json!("all"));
+                    config.insert("group".to_string(), json!(1));
+                    config.insert("flags".to_string(), json!("im"));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!(["OK", "failed"])),
+            ),
+            (
+                6,
+                "从指定字段提取",
+                json!({"id": 1, "content": "回复编号 #A-778"}),
+                vec![("regexp_extract", {
+                    let mut config = HashMap::new();
+                    config.insert("pattern".to_string(), json!(r"#([A-Z]-\d+)"));
+                    config.insert("group".to_string(), json!(1));
+                    config.insert("source".to_string(), json!("$.content"));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!("A-778")),
+            ),
+            (
+                7,
+                "所有匹配无结果返回空数组",
+                json!("没有数字"),
+                vec![("regexp_extract", {
+                    let mut config = HashMap::new();
+                    config.insert("pattern".to_string(), json!(r"\d+"));
+                    config.insert("mode".to_string(), json!("all"));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!([])),
+            ),
+            (
+                8,
+                "不支持的标志",
+                json!("abc"),
+                vec![("regexp_extract", {
+                    let mut config = HashMap::new();
+                    config.insert("pattern".to_string(), json!(r"\w+"));
+                    config.insert("flags".to_string(), json!("q"));
+                    config
+                })],
+                ChainStatus::Error,
+                None,
+                None,
+            ),
+            (
+                9,
+                "命名分组默认返回数组，未参与匹配的分组为null",
+                json!("name=alice"),
+                vec![("regexp_extract", {
+                    let mut config = HashMap::new();
+                    config.insert("pattern".to_string(), json!(r"(?P<key>\w+)=(?P<value>\w+)(?: #(\d+))?"));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!(["name", "alice", null])),
+            ),
+            (
+                10,
+                "object返回未命名分组以序号为键",
+                json!("name=alice"),
+                vec![("regexp_extract", {
+                    let mut config = HashMap::new();
+                    config.insert("pattern".to_string(), json!(r"(?P<key>\w+)=(\w+)"));
+                    config.insert("output".to_string(), json!("object"));
+                    config
+                })],
+                ChainStatus::Completed,
+                None,
+                Some(json!({"key": "name", "2": "alice"})),
+            ),
+        ];
+
+        run_test_cases(test_cases, "regexp_extract高级功能测试", 1.0);
+    }
+
     #[test]
     fn test_extract_chains() {
         let test_cases = vec![
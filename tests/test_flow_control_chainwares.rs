@@ -0,0 +1,305 @@
+//! 流程控制挂件测试
+//!
+//! 测试 switch 挂件的分支选择和子链执行，以及 loop 挂件的循环条件和迭代限制
+
+use linkchain::chain::executor::ChainExecutor;
+use linkchain::core::{ChainRequest, ChainResponse, ChainStatus};
+use linkchain::types::error_codes;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn execute(configs: Value, params: Value, meta: Value) -> ChainResponse {
+        let executor = ChainExecutor::new().add_chainwares(configs).unwrap();
+        let meta: HashMap<String, Value> = serde_json::from_value(meta).unwrap();
+        executor.execute(ChainRequest::new(params, meta))
+    }
+
+    fn tool_switch() -> Value {
+        json!([
+            {"name": "switch", "config": {
+                "value": "$meta.tool_name",
+                "cases": {
+                    "search": [{"name": "extract_map", "config": {"mapping": {"query": "$.q"}}}],
+                    "weather": [
+                        {"name": "condition", "config": {"expression": "Chain.isString($.city)"}},
+                        {"name": "extract_map", "config": {"mapping": {"city": "$.city", "unit": "$.unit"}}}
+                    ]
+                },
+                "default": [{"name": "patch", "config": {"operations": [{"op": "add", "path": "/unknown", "value": true}]}}]
+            }},
+            {"name": "patch", "config": {"operations": [{"op": "add", "path": "/dispatched", "value": true}]}}
+        ])
+    }
+
+    #[test]
+    fn test_switch_exact() {
+        let params = json!({"q": "rust", "city": "北京", "unit": "c"});
+
+        let response = execute(tool_switch(), params.clone(), json!({"tool_name": "search"}));
+        assert_eq!(response.status, ChainStatus::Completed);
+        assert_eq!(response.data, Some(json!({"query": "rust", "dispatched": true})));
+        assert_eq!(response.meta["switch_case"], json!("search"));
+
+        let response = execute(tool_switch(), params.clone(), json!({"tool_name": "weather"}));
+        assert_eq!(response.data, Some(json!({"city": "北京", "unit": "c", "dispatched": true})));
+        assert_eq!(response.meta["switch_case"], json!("weather"));
+
+        // 没有匹配的分支时执行默认分支，值不存在时同样使用默认分支
+        for meta in [json!({"tool_name": "translate"}), json!({})] {
+            let response = execute(tool_switch(), params.clone(), meta);
+            assert_eq!(response.data.unwrap()["unknown"], json!(true));
+            assert_eq!(response.meta["switch_case"], json!("default"));
+        }
+
+        // 分支子链拒绝时整个链以拒绝结束
+        let response = execute(tool_switch(), json!({"city": 1}), json!({"tool_name": "weather"}));
+        assert_eq!(response.status, ChainStatus::Reject);
+        assert_eq!(response.data.unwrap()["errno"], json!(error_codes::CONDITION_NOT_MET));
+    }
+
+    #[test]
+    fn test_switch_regex_in_order() {
+        let configs = json!([{"name": "switch", "config": {
+            "value": "$params.version",
+            "match": "regex",
+            "case_key": "api_version",
+            "cases": [
+                {"case": "^v2\\.1", "name": "v2.1", "chain": [{"name": "json_extract", "config": {"pattern": "$.new"}}]},
+                {"case": "^v2\\.", "name": "v2", "chain": [{"name": "json_extract", "config": {"pattern": "$.mid"}}]},
+                {"case": ".*", "chain": [{"name": "json_extract", "config": {"pattern": "$.old"}}]}
+            ]
+        }}]);
+        let data = |version: &str| json!({"version": version, "new": 3, "mid": 2, "old": 1});
+
+        let response = execute(configs.clone(), data("v2.1.0"), json!({}));
+        assert_eq!(response.data, Some(json!(3)));
+        assert_eq!(response.meta["api_version"], json!("v2.1"));
+
+        let response = execute(configs.clone(), data("v2.0.3"), json!({}));
+        assert_eq!(response.data, Some(json!(2)));
+
+        // 未配置名称时使用case作为分支名称
+        let response = execute(configs, data("v1"), json!({}));
+        assert_eq!(response.data, Some(json!(1)));
+        assert_eq!(response.meta["api_version"], json!(".*"));
+    }
+
+    #[test]
+    fn test_switch_condition() {
+        let configs = json!([
+            {"name": "set_var", "config": {"vars": {"tier": "$.tier"}}},
+            {"name": "switch", "config": {
+                "match": "condition",
+                "cases": [
+                    {"case": "$.amount >= 1000 && $vars.tier == \"vip\"", "name": "manual_review", "chain": [
+                        {"name": "set_var", "config": {"vars": {"review": true}}}
+                    ]},
+                    {"case": "$.amount >= 1000", "name": "reject", "chain": [
+                        {"name": "condition", "config": {"expression": "$.amount < 1000"}}
+                    ]}
+                ]
+            }}
+        ]);
+
+        let response = execute(configs.clone(), json!({"amount": 5000, "tier": "vip"}), json!({}));
+        assert_eq!(response.status, ChainStatus::Completed);
+        assert_eq!(response.meta["switch_case"], json!("manual_review"));
+        // 分支子链共享链变量
+        assert_eq!(response.get_var("review"), Some(&json!(true)));
+
+        let response = execute(configs.clone(), json!({"amount": 5000, "tier": "normal"}), json!({}));
+        assert_eq!(response.status, ChainStatus::Reject);
+        assert_eq!(response.meta["switch_case"], json!("reject"));
+
+        // 没有匹配的分支且没有默认分支时数据透传，不记录分支
+        let response = execute(configs, json!({"amount": 10, "tier": "vip"}), json!({}));
+        assert_eq!(response.status, ChainStatus::Completed);
+        assert_eq!(response.data, Some(json!({"amount": 10, "tier": "vip"})));
+        assert!(!response.meta.contains_key("switch_case"));
+    }
+
+    #[test]
+    fn test_switch_non_string_value() {
+        // 数字和布尔值转换为字符串后匹配
+        let configs = json!([{"name": "switch", "config": {
+            "value": "$.code",
+            "cases": {"200": [{"name": "json_extract", "config": {"pattern": "$.body"}}]}
+        }}]);
+        let response = execute(configs, json!({"code": 200, "body": "ok"}), json!({}));
+        assert_eq!(response.data, Some(json!("ok")));
+    }
+
+    #[test]
+    fn test_switch_config_errors() {
+        let cases = vec![
+            (json!({"value": "$.a"}), "switch挂件需要cases配置"),
+            (json!({"cases": {}}), "exact匹配方式需要value配置"),
+            (json!({"value": "$.a", "match": "glob", "cases": {}}), "不支持的匹配方式"),
+            (json!({"value": "$.a", "match": "regex", "cases": {"(": []}}), "分支 '(' 的正则表达式无效"),
+            (json!({"value": "$.a", "cases": {"x": [{"name": "nope"}]}}), "分支 'x' 的子链配置错误"),
+            (json!({"value": "$.a", "cases": [{"chain": []}]}), "第1个分支缺少字符串类型的case字段"),
+            (json!({"value": "$.a", "cases": {}, "default": {}}), "default子链配置错误"),
+        ];
+
+        for (config, expected) in cases {
+            let response = execute(json!([{"name": "switch", "config": config}]), json!({"a": "x"}), json!({}));
+            assert_eq!(response.status, ChainStatus::Error, "{}", config);
+            let data = response.data.unwrap();
+            assert_eq!(data["errno"], json!(error_codes::CONFIG_ERROR));
+            assert!(data["msg"].as_str().unwrap().contains(expected), "{}: {}", config, data["msg"]);
+        }
+
+        // 条件表达式求值失败
+        let response = execute(
+            json!([{"name": "switch", "config": {"match": "condition", "cases": [{"case": "$.a ~~ 1", "chain": []}]}}]),
+            json!({"a": 1}),
+            json!({}),
+        );
+        assert_eq!(response.status, ChainStatus::Error);
+        assert_eq!(response.data.unwrap()["errno"], json!(error_codes::INTERNAL_ERROR));
+    }
+
+    /// 每次迭代输出当前迭代序号的循环体
+    fn counter_body() -> Value {
+        json!([{"name": "extract_map", "config": {"mapping": {"n": "$vars.loop_index", "id": "$.id"}}}])
+    }
+
+    #[test]
+    fn test_loop_until() {
+        let configs = json!([
+            {"name": "loop", "config": {
+                "chain": counter_body(),
+                "until": "$.n >= 2",
+                "accumulate": "$.n"
+            }},
+            {"name": "patch", "config": {"operations": [{"op": "add", "path": "/done", "value": true}]}}
+        ]);
+
+        let response = execute(configs, json!({"id": 1}), json!({}));
+        assert_eq!(response.status, ChainStatus::Completed);
+        assert_eq!(response.data, Some(json!({"n": 2, "id": 1, "done": true})));
+        assert_eq!(response.get_var("loop_index"), Some(&json!(2)));
+        assert_eq!(response.get_var("loop_results"), Some(&json!([0, 1, 2])));
+    }
+
+    #[test]
+    fn test_loop_while() {
+        // while在每次迭代前检查，初始不满足时不执行循环体
+        let configs = json!([{"name": "loop", "config": {
+            "chain": [{"name": "extract_map", "config": {"mapping": {"n": "$vars.attempt", "id": "$.id"}}}],
+            "while": "$vars.attempt < 3",
+            "index_var": "attempt",
+            "accumulate": "第${$vars.attempt}次",
+            "accumulator_var": "log"
+        }}]);
+
+        let response = execute(configs, json!({"id": 1}), json!({}));
+        assert_eq!(response.status, ChainStatus::Completed);
+        assert_eq!(response.data, Some(json!({"n": 2, "id": 1})));
+        assert_eq!(response.get_var("attempt"), Some(&json!(3)));
+        assert_eq!(response.get_var("log"), Some(&json!(["第0次", "第1次", "第2次"])));
+
+        let response = execute(
+            json!([{"name": "loop", "config": {"chain": counter_body(), "while": "$.skip != true"}}]),
+            json!({"skip": true}),
+            json!({}),
+        );
+        assert_eq!(response.data, Some(json!({"skip": true})));
+
+        // 未配置条件时按最大迭代次数执行
+        let response = execute(
+            json!([{"name": "loop", "config": {"chain": counter_body(), "max_iterations": 4}}]),
+            json!({"id": 1}),
+            json!({}),
+        );
+        assert_eq!(response.status, ChainStatus::Completed);
+        assert_eq!(response.data, Some(json!({"n": 3, "id": 1})));
+    }
+
+    #[test]
+    fn test_loop_iteration_limit() {
+        let response = execute(
+            json!([{"name": "loop", "config": {"chain": counter_body(), "until": "$.n > 100", "max_iterations": 3}}]),
+            json!({"id": 1}),
+            json!({}),
+        );
+        assert_eq!(response.status, ChainStatus::Error);
+        let data = response.data.unwrap();
+        assert_eq!(data["errno"], json!(error_codes::ITERATION_LIMIT));
+        assert_eq!(data["msg"], json!("循环达到最大迭代次数3，until条件 '$.n > 100' 仍未满足"));
+        assert_eq!(data["detail"], json!({"iterations": 3, "data": {"n": 2, "id": 1}}));
+        assert_eq!(response.meta["error_details"]["node_name"], json!("loop"));
+
+        let response = execute(
+            json!([{"name": "loop", "config": {"chain": counter_body(), "while": "$.id == 1", "max_iterations": 2}}]),
+            json!({"id": 1}),
+            json!({}),
+        );
+        assert_eq!(response.status, ChainStatus::Error);
+        assert_eq!(
+            response.data.unwrap()["msg"],
+            json!("循环达到最大迭代次数2，while条件 '$.id == 1' 仍然满足")
+        );
+    }
+
+    #[test]
+    fn test_loop_body_status() {
+        // 循环体被拒绝时整个链以拒绝结束
+        let response = execute(
+            json!([{"name": "loop", "config": {
+                "chain": [{"name": "condition", "config": {"expression": "$vars.loop_index < 1"}}],
+                "until": "$.ok == true"
+            }}]),
+            json!({"ok": false}),
+            json!({}),
+        );
+        assert_eq!(response.status, ChainStatus::Reject);
+
+        // 循环体内的on_error可以在出错时修复数据并继续迭代
+        let response = execute(
+            json!([{"name": "loop", "config": {
+                "chain": [{"name": "convert", "config": {
+                    "fields": {"$.age": "int"}, "mode": "strict",
+                    "on_error": [{"name": "extract_map", "config": {"mapping": {"age": "$.data.fallback", "fallback": "$.data.fallback"}}}]
+                }}],
+                "until": "Chain.isNumber($.age)",
+                "max_iterations": 3
+            }}]),
+            json!({"age": "未知", "fallback": "18"}),
+            json!({}),
+        );
+        assert_eq!(response.status, ChainStatus::Completed);
+        assert_eq!(response.data, Some(json!({"age": 18, "fallback": "18"})));
+        assert_eq!(response.meta["recovered_errors"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_loop_config_errors() {
+        let cases = vec![
+            (json!({"until": "$.a == 1"}), "loop挂件需要chain配置"),
+            (json!({"chain": {}}), "循环子链配置错误"),
+            (json!({"chain": [], "max_iterations": 0}), "max_iterations配置必须是正整数"),
+            (json!({"chain": [], "until": true}), "until配置必须是字符串类型"),
+        ];
+
+        for (config, expected) in cases {
+            let response = execute(json!([{"name": "loop", "config": config}]), json!({}), json!({}));
+            assert_eq!(response.status, ChainStatus::Error, "{}", config);
+            let data = response.data.unwrap();
+            assert_eq!(data["errno"], json!(error_codes::CONFIG_ERROR));
+            assert!(data["msg"].as_str().unwrap().contains(expected), "{}: {}", config, data["msg"]);
+        }
+
+        let response = execute(
+            json!([{"name": "loop", "config": {"chain": [], "until": "$.a ~~ 1"}}]),
+            json!({"a": 1}),
+            json!({}),
+        );
+        assert_eq!(response.status, ChainStatus::Error);
+        assert!(response.data.unwrap()["msg"].as_str().unwrap().starts_with("until条件 '$.a ~~ 1' 求值失败"));
+    }
+}
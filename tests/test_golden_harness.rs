@@ -0,0 +1,91 @@
+//! 黄金文件测试执行器测试
+//!
+//! 测试 harness::ChainHarness 从目录加载链配置和用例，以及差异报告
+
+use linkchain::chain::executor::ChainExecutor;
+use linkchain::core::ChainStatus;
+use linkchain::harness::{ChainHarness, GoldenCase};
+use serde_json::json;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture_dir(name: &str) -> String {
+        format!("{}/tests/fixtures/golden/{}", env!("CARGO_MANIFEST_DIR"), name)
+    }
+
+    #[test]
+    fn test_golden_dir() {
+        let harness = ChainHarness::from_dir(fixture_dir("user_check")).unwrap();
+        let names: Vec<&str> = harness.cases().iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["adult", "minor", "黑名单IP", "断言部分字段"]);
+
+        let report = harness.run();
+        println!("{}", report);
+        report.assert_success();
+        assert_eq!(report.passed(), 4);
+    }
+
+    #[test]
+    fn test_golden_dir_with_chain_on_error() {
+        // chain.json为带链级on_error的对象格式
+        let harness = ChainHarness::from_dir(fixture_dir("with_on_error")).unwrap();
+        let report = harness.run();
+        println!("{}", report);
+        report.assert_success();
+        assert_eq!(report.passed(), 2);
+    }
+
+    #[test]
+    fn test_failure_report() {
+        let executor = ChainExecutor::new()
+            .add_chainwares(json!([{"name": "map_fields", "config": {"mapping": {"full": "${$.first} ${$.last}"}}}]))
+            .unwrap();
+        let mut harness = ChainHarness::new(executor);
+        harness.add_case(
+            GoldenCase::from_json(
+                "数据不一致",
+                &json!({
+                    "params": {"first": "Ada", "last": "Lovelace", "extra": [1, 2]},
+                    "expect": {
+                        "status": "reject",
+                        "data": {"first": "Ada", "last": "Byron", "full": "Ada Lovelace", "extra": [1], "age": 36},
+                        "assertions": {"$.full": "Ada Byron"},
+                        "errno": 403
+                    }
+                }),
+            )
+            .unwrap(),
+        );
+
+        let report = harness.run();
+        assert!(!report.is_success());
+        let result = &report.results[0];
+        assert_eq!(result.status, ChainStatus::Completed);
+        assert_eq!(
+            result.failures,
+            vec![
+                "状态: 期望 reject，实际 completed",
+                "错误码: 期望 403，实际输出没有errno",
+                "数据 $.age: 缺少字段，期望 36",
+                "数据 $.extra[1]: 多余元素，实际 2",
+                "数据 $.last: 期望 \"Byron\"，实际 \"Lovelace\"",
+                "断言 $.full: 期望 \"Ada Byron\"，实际 \"Ada Lovelace\"",
+            ]
+        );
+
+        let text = report.to_string();
+        assert!(text.contains("❌ 数据不一致"));
+        assert!(text.ends_with("共 1 个用例，通过 0 个，失败 1 个"));
+    }
+
+    #[test]
+    fn test_invalid_case() {
+        let err = GoldenCase::from_json("bad", &json!({"expect": {"status": "done"}})).unwrap_err();
+        assert_eq!(err, "用例 'bad': 未知的执行状态: done");
+
+        let err = ChainHarness::from_dir(fixture_dir("not_exists")).err().unwrap();
+        assert!(err.starts_with("读取文件失败"), "{}", err);
+    }
+}
@@ -1,6 +1,6 @@
 //! IP过滤类挂件测试
 //!
-//! 测试 ip_blacklist 和 ip_whitelist 挂件
+//! 测试 ip_blacklist、ip_whitelist 和 geo_filter 挂件，包括IP范围、名单文件、可信代理和转发头解析
 
 include!("common.rs");
 
@@ -268,4 +268,415 @@ mod tests {
 
         run_test_cases(test_cases, "IP过滤边界测试", 0.6);
     }
+
+    #[test]
+    fn test_ip_ranges_and_ipv6() {
+        let test_cases = vec![
+            (
+                1,
+                "IP范围黑名单命中",
+                json!({"user": "test"}),
+                vec![("ip_blacklist", {
+                    let mut config = HashMap::new();
+                    config.insert("ip_list".to_string(), json!(["10.0.0.10-10.0.1.20"]));
+                    config
+                })],
+                ChainStatus::Reject,
+                Some("10.0.0.255"),
+                Some(json!({"errno": 403, "msg": "IP地址 10.0.0.255 在黑名单中"})),
+            ),
+            (
+                2,
+                "IP范围边界外通过",
+                json!({"user": "test"}),
+                vec![("ip_blacklist", {
+                    let mut config = HashMap::new();
+                    config.insert("ip_list".to_string(), json!(["10.0.0.10-10.0.1.20"]));
+                    config
+                })],
+                ChainStatus::Completed,
+                Some("10.0.1.21"),
+                Some(json!({"user": "test"})),
+            ),
+            (
+                3,
+                "IPv6网段白名单",
+                json!({"user": "test"}),
+                vec![("ip_whitelist", {
+                    let mut config = HashMap::new();
+                    config.insert("ip_list".to_string(), json!(["2001:db8::/32", "fe80::1-fe80::ff"]));
+                    config
+                })],
+                ChainStatus::Completed,
+                Some("2001:db8:1234::5"),
+                Some(json!({"user": "test"})),
+            ),
+            (
+                4,
+                "IPv6范围白名单",
+                json!({"user": "test"}),
+                vec![("ip_whitelist", {
+                    let mut config = HashMap::new();
+                    config.insert("ip_list".to_string(), json!(["2001:db8::/32", "fe80::1-fe80::ff"]));
+                    config
+                })],
+                ChainStatus::Reject,
+                Some("fe80::100"),
+                Some(json!({"errno": 403, "msg": "IP地址 fe80::100 不在白名单中"})),
+            ),
+            (
+                5,
+                "无效的IP范围",
+                json!({"user": "test"}),
+                vec![("ip_blacklist", {
+                    let mut config = HashMap::new();
+                    config.insert("ip_list".to_string(), json!(["10.0.0.9-10.0.0.1"]));
+                    config
+                })],
+                ChainStatus::Error,
+                Some("10.0.0.5"),
+                Some(json!({"errno": 400, "msg": "ip_list中IP范围的起始地址大于结束地址: 10.0.0.9-10.0.0.1"})),
+            ),
+        ];
+
+        run_test_cases(test_cases, "IP范围和IPv6测试", 1.0);
+    }
+
+    #[test]
+    fn test_ip_list_file() {
+        let path = std::env::temp_dir().join(format!("linkchain_ip_list_{}.txt", std::process::id()));
+        std::fs::write(
+            &path,
+            "# 威胁情报列表\n198.51.100.0/24\n\n203.0.113.5  # 单个IP\n2001:db8:bad::/48\n192.0.2.10-192.0.2.20\n",
+        )
+        .unwrap();
+
+        let config = json!({"ip_file": path.to_str().unwrap(), "ip_list": "8.8.8.8"});
+        for (ip, status) in [
+            ("198.51.100.77", ChainStatus::Reject),
+            ("203.0.113.5", ChainStatus::Reject),
+            ("2001:db8:bad:1::1", ChainStatus::Reject),
+            ("192.0.2.15", ChainStatus::Reject),
+            ("8.8.8.8", ChainStatus::Reject),
+            ("192.0.2.21", ChainStatus::Completed),
+            ("203.0.113.6", ChainStatus::Completed),
+        ] {
+            let response = execute_with_meta("ip_blacklist", config.clone(), json!({"ip_address": ip}));
+            assert_eq!(response.status, status, "IP {}", ip);
+        }
+
+        // 文件中的无效条目报告行号
+        std::fs::write(&path, "10.0.0.0/8\nnot-an-ip\n").unwrap();
+        let response = execute_with_meta(
+            "ip_blacklist",
+            json!({"ip_file": path.to_str().unwrap()}),
+            json!({"ip_address": "10.0.0.1"}),
+        );
+        assert_eq!(response.status, ChainStatus::Error);
+        let msg = response.data.unwrap()["msg"].as_str().unwrap().to_string();
+        assert!(msg.contains("第2行"), "{}", msg);
+        std::fs::remove_file(&path).unwrap();
+
+        // 名单文件暂不存在时构建失败不被缓存，文件就绪后同一节点重新构建
+        let executor = ChainExecutor::new()
+            .add_chainwares(json!([{"name": "ip_whitelist", "config": {"ip_file": path.to_str().unwrap()}}]))
+            .unwrap();
+        let execute = || {
+            let meta: HashMap<String, Value> = serde_json::from_value(json!({"ip_address": "10.0.0.1"})).unwrap();
+            executor.execute(ChainRequest::new(json!({"user": "test"}), meta))
+        };
+        let response = execute();
+        assert_eq!(response.status, ChainStatus::Error);
+        assert_eq!(response.data.unwrap()["errno"], 400);
+
+        std::fs::write(&path, "10.0.0.0/8\n").unwrap();
+        assert_eq!(execute().status, ChainStatus::Completed);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(execute().status, ChainStatus::Completed);
+    }
+
+    /// 测试用地理数据库路径（由 tests/fixtures/generate_geo_mmdb.py 生成）
+    #[cfg(feature = "geo")]
+    fn geo_database() -> String {
+        format!("{}/tests/fixtures/geo_test.mmdb", env!("CARGO_MANIFEST_DIR"))
+    }
+
+    #[test]
+    #[cfg(feature = "geo")]
+    fn test_geo_filter() {
+        let geo_config = |key: &str, value: Value| {
+            let mut config = HashMap::new();
+            config.insert("database".to_string(), json!(geo_database()));
+            config.insert(key.to_string(), value);
+            config
+        };
+
+        let test_cases = vec![
+            (
+                1,
+                "允许国家通过",
+                json!({"user": "test"}),
+                vec![("geo_filter", geo_config("allow_countries", json!(["cn", "GB"])))],
+                ChainStatus::Completed,
+                Some("1.0.0.8"),
+                Some(json!({"user": "test"})),
+            ),
+            (
+                2,
+                "不在允许国家中被拒绝",
+                json!({"user": "test"}),
+                vec![("geo_filter", geo_config("allow_countries", json!(["CN", "GB"])))],
+                ChainStatus::Reject,
+                Some("8.8.8.8"),
+                Some(json!({
+                    "errno": 403,
+                    "msg": "IP地址 8.8.8.8 所属国家 US 不在允许名单中",
+                    "detail": {"ip": "8.8.8.8", "country": "US", "continent": "NA", "asn": 15169, "as_org": "Google LLC"}
+                })),
+            ),
+            (
+                3,
+                "拒绝ASN",
+                json!({"user": "test"}),
+                vec![("geo_filter", geo_config("deny_asns", json!(["AS15169"])))],
+                ChainStatus::Reject,
+                Some("8.8.8.8"),
+                None,
+            ),
+            (
+                4,
+                "拒绝国家（IPv6）",
+                json!({"user": "test"}),
+                vec![("geo_filter", geo_config("deny_countries", json!("DE,KP")))],
+                ChainStatus::Reject,
+                Some("2001:db8::1"),
+                None,
+            ),
+            (
+                5,
+                "附加地理信息",
+                json!({"user": "test"}),
+                vec![("geo_filter", geo_config("attach", json!(true)))],
+                ChainStatus::Completed,
+                Some("81.2.69.160"),
+                Some(json!({
+                    "user": "test",
+                    "geo": {"ip": "81.2.69.160", "country": "GB", "continent": "EU", "asn": 20712, "as_org": "Andrews & Arnold Ltd"}
+                })),
+            ),
+            (
+                6,
+                "未知IP在配置允许名单时被拒绝",
+                json!({"user": "test"}),
+                vec![("geo_filter", geo_config("allow_asns", json!([4134])))],
+                ChainStatus::Reject,
+                Some("192.0.2.1"),
+                Some(json!({
+                    "errno": 403,
+                    "msg": "IP地址 192.0.2.1 无法确定地理位置",
+                    "detail": {"ip": "192.0.2.1", "country": null, "continent": null, "asn": null, "as_org": null}
+                })),
+            ),
+            (
+                7,
+                "未知IP在仅配置拒绝名单时放行",
+                json!({"user": "test"}),
+                vec![("geo_filter", geo_config("deny_countries", json!(["US"])))],
+                ChainStatus::Completed,
+                Some("192.0.2.1"),
+                Some(json!({"user": "test"})),
+            ),
+            (
+                8,
+                "数据库不存在",
+                json!({"user": "test"}),
+                vec![("geo_filter", {
+                    let mut config = HashMap::new();
+                    config.insert("database".to_string(), json!("/nonexistent/geo.mmdb"));
+                    config
+                })],
+                ChainStatus::Error,
+                Some("1.0.0.8"),
+                None,
+            ),
+        ];
+
+        run_test_cases(test_cases, "geo_filter挂件测试", 1.0);
+
+        // 与ip_whitelist相同的客户端IP解析
+        let response = execute_with_meta(
+            "geo_filter",
+            json!({"database": geo_database(), "allow_countries": ["CN"], "trusted_proxies": ["10.0.0.0/8"]}),
+            json!({"ip_address": "10.0.0.2", "x-forwarded-for": "1.0.0.99, 10.0.0.5"}),
+        );
+        assert_eq!(response.status, ChainStatus::Completed);
+        assert_eq!(response.meta["client_ip"], "1.0.0.99");
+
+        // ASN前缀不区分大小写且只能有一个，超出32位范围的ASN和非字符串的国家代码为配置错误
+        let config = |key: &str, value: Value| json!({"database": geo_database(), key: value});
+        let response = execute_with_meta("geo_filter", config("deny_asns", json!(["as15169"])), json!({"ip_address": "8.8.8.8"}));
+        assert_eq!(response.status, ChainStatus::Reject);
+        for (key, value, msg) in [
+            ("deny_asns", json!(["ASAS1"]), "deny_asns中的无效ASN: \"ASAS1\""),
+            ("allow_asns", json!([4294982465u64]), "allow_asns中的无效ASN: 4294982465"),
+            ("deny_asns", json!("AS-1"), "deny_asns中的无效ASN: \"AS-1\""),
+            ("allow_countries", json!(["CN", 86]), "allow_countries数组中的元素必须是字符串类型"),
+            ("deny_countries", json!(true), "deny_countries配置必须是字符串或数组类型"),
+        ] {
+            let response = execute_with_meta("geo_filter", config(key, value), json!({"ip_address": "8.8.8.8"}));
+            assert_eq!(response.status, ChainStatus::Error);
+            assert_eq!(response.data.unwrap()["msg"], json!(msg));
+        }
+    }
+
+    #[test]
+    fn test_large_ip_set() {
+        use linkchain::utils::ip_set::IpSet;
+        use std::net::IpAddr;
+
+        // 10万条/24网段：10.0.0.0/24 ~ 11.134.159.0/24 中每隔一个网段
+        let entries: Vec<String> = (0..100_000u32)
+            .map(|i| {
+                let net = (10u32 << 24) + (i * 2) * 256;
+                format!("{}/24", std::net::Ipv4Addr::from(net))
+            })
+            .collect();
+        let set = IpSet::from_entries(&entries).unwrap();
+        assert_eq!(set.len(), 100_000);
+
+        let contains = |ip: &str| set.contains(&ip.parse::<IpAddr>().unwrap());
+        assert!(contains("10.0.0.1"));
+        assert!(!contains("10.0.1.1"));
+        assert!(contains("10.0.2.255"));
+        assert!(!contains("9.255.255.255"));
+        assert!(contains("::ffff:10.0.4.8"));
+        assert!(!contains("2001:db8::1"));
+    }
+
+    /// 使用自定义meta执行单个IP过滤挂件
+    fn execute_with_meta(name: &str, config: Value, meta: Value) -> linkchain::core::ChainResponse {
+        let executor = ChainExecutor::new()
+            .add_chainwares(json!([{"name": name, "config": config}]))
+            .unwrap();
+        let meta: HashMap<String, Value> = serde_json::from_value(meta).unwrap();
+        executor.execute(ChainRequest::new(json!({"user": "test"}), meta))
+    }
+
+    #[test]
+    fn test_ip_trusted_proxies() {
+        let proxy_config = |ip_list: &str| {
+            json!({
+                "ip_list": ip_list,
+                "trusted_proxies": ["10.0.0.0/8", "172.16.0.1"]
+            })
+        };
+
+        // X-Forwarded-For：自右向左跳过可信代理
+        let response = execute_with_meta(
+            "ip_blacklist",
+            proxy_config("203.0.113.7"),
+            json!({"ip_address": "10.0.0.2", "X-Forwarded-For": "198.51.100.1, 203.0.113.7, 172.16.0.1"}),
+        );
+        assert_eq!(response.status, ChainStatus::Reject);
+        assert_eq!(response.data.unwrap()["msg"], "IP地址 203.0.113.7 在黑名单中");
+        assert_eq!(response.meta["client_ip"], "203.0.113.7");
+
+        // 直连IP不可信时忽略转发头，防止伪造
+        let response = execute_with_meta(
+            "ip_blacklist",
+            proxy_config("203.0.113.7"),
+            json!({"ip_address": "192.0.2.10", "x-forwarded-for": "10.1.1.1"}),
+        );
+        assert_eq!(response.status, ChainStatus::Completed);
+        assert_eq!(response.meta["client_ip"], "192.0.2.10");
+
+        // RFC 7239 Forwarded头，支持带引号的IPv6和端口
+        let mut forwarded_config = proxy_config("2001:db8::/32");
+        forwarded_config["forwarded_header"] = json!("forwarded");
+        let response = execute_with_meta(
+            "ip_whitelist",
+            forwarded_config.clone(),
+            json!({
+                "ip_address": "10.0.0.2",
+                "forwarded": "for=\"[2001:db8:cafe::17]:4711\";proto=https, for=10.2.3.4;by=10.0.0.2"
+            }),
+        );
+        assert_eq!(response.status, ChainStatus::Completed);
+        assert_eq!(response.meta["client_ip"], "2001:db8:cafe::17");
+
+        // IPv4映射的IPv6地址规范化为IPv4
+        let response = execute_with_meta(
+            "ip_whitelist",
+            json!({"ip_list": "192.168.1.0/24"}),
+            json!({"ip_address": "::ffff:192.168.1.20"}),
+        );
+        assert_eq!(response.status, ChainStatus::Completed);
+        assert_eq!(response.meta["client_ip"], "192.168.1.20");
+
+        // 转发链全部可信时返回最左侧地址
+        let response = execute_with_meta(
+            "ip_whitelist",
+            proxy_config("10.9.9.9"),
+            json!({"ip_address": "10.0.0.2", "x-forwarded-for": "10.9.9.9, 10.0.0.3"}),
+        );
+        assert_eq!(response.status, ChainStatus::Completed);
+        assert_eq!(response.meta["client_ip"], "10.9.9.9");
+
+        // 只解析信任的转发头：默认信任X-Forwarded-For，客户端伪造的Forwarded头被忽略
+        let response = execute_with_meta(
+            "ip_whitelist",
+            proxy_config("203.0.113.0/24"),
+            json!({
+                "ip_address": "10.0.0.2",
+                "forwarded": "for=203.0.113.7",
+                "x-forwarded-for": "198.51.100.1"
+            }),
+        );
+        assert_eq!(response.status, ChainStatus::Reject);
+        assert_eq!(response.meta["client_ip"], "198.51.100.1");
+
+        // 信任的转发头缺失时不回退到另一个头
+        let response = execute_with_meta(
+            "ip_whitelist",
+            forwarded_config,
+            json!({"ip_address": "10.0.0.2", "x-forwarded-for": "2001:db8::1"}),
+        );
+        assert_eq!(response.status, ChainStatus::Reject);
+        assert_eq!(response.meta["client_ip"], "10.0.0.2");
+
+        // 转发链中不是IP的跳：停止解析，使用此前最后一个有效地址
+        let response = execute_with_meta(
+            "ip_blacklist",
+            proxy_config("203.0.113.7"),
+            json!({"ip_address": "10.0.0.2", "x-forwarded-for": "203.0.113.7, unknown, 10.0.0.3"}),
+        );
+        assert_eq!(response.status, ChainStatus::Completed);
+        assert_eq!(response.meta["client_ip"], "10.0.0.3");
+
+        let mut forwarded_config = proxy_config("203.0.113.7");
+        forwarded_config["forwarded_header"] = json!("forwarded");
+        let response = execute_with_meta(
+            "ip_blacklist",
+            forwarded_config,
+            json!({"ip_address": "10.0.0.2", "forwarded": "for=_hidden, for=198.51.100.1"}),
+        );
+        assert_eq!(response.status, ChainStatus::Completed);
+        assert_eq!(response.meta["client_ip"], "198.51.100.1");
+
+        // 不支持的转发头配置
+        let mut invalid_config = proxy_config("203.0.113.7");
+        invalid_config["forwarded_header"] = json!("x-real-ip");
+        let response = execute_with_meta("ip_blacklist", invalid_config, json!({"ip_address": "10.0.0.2"}));
+        assert_eq!(response.status, ChainStatus::Error);
+        assert_eq!(response.data.unwrap()["errno"], 400);
+
+        // 无效的可信代理配置
+        let response = execute_with_meta(
+            "ip_blacklist",
+            json!({"ip_list": "203.0.113.7", "trusted_proxies": ["10.0.0.0/40"]}),
+            json!({"ip_address": "10.0.0.2"}),
+        );
+        assert_eq!(response.status, ChainStatus::Error);
+        assert_eq!(response.data.unwrap()["errno"], 400);
+    }
 }
@@ -0,0 +1,136 @@
+//! 节点执行条件测试
+//!
+//! 测试 ChainwareWrapper 对 when 表达式的处理
+
+use linkchain::chain::executor::ChainExecutor;
+use linkchain::chainware::config::ChainwareConfig;
+use linkchain::core::{ChainRequest, ChainResponse, ChainStatus};
+use linkchain::types::error_codes;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn execute_with_ip(configs: Value, params: Value, ip: &str) -> ChainResponse {
+        let executor = ChainExecutor::new().add_chainwares(configs).unwrap();
+        let mut meta = HashMap::new();
+        meta.insert("ip_address".to_string(), json!(ip));
+        executor.execute(ChainRequest::new(params, meta))
+    }
+
+    #[test]
+    fn test_when_skips_node() {
+        let configs = json!([
+            // 只对非管理员隐藏手机号
+            {"name": "patch", "config": {
+                "when": "$.role != \"admin\"",
+                "operations": [{"op": "replace", "path": "/phone", "value": "***"}]
+            }},
+            {"name": "patch", "config": {
+                "when": "$.phone == \"***\"",
+                "operations": [{"op": "add", "path": "/masked", "value": true}]
+            }}
+        ]);
+
+        let response = execute_with_ip(configs.clone(), json!({"role": "user", "phone": "13800000000"}), "10.0.0.1");
+        assert_eq!(response.status, ChainStatus::Completed);
+        assert_eq!(response.data, Some(json!({"role": "user", "phone": "***", "masked": true})));
+
+        let response = execute_with_ip(configs, json!({"role": "admin", "phone": "13800000000"}), "10.0.0.1");
+        assert_eq!(response.data, Some(json!({"role": "admin", "phone": "13800000000"})));
+    }
+
+    #[test]
+    fn test_when_with_context() {
+        // 按路由参数决定是否检查IP，条件可以使用$params、$meta、$vars
+        let configs = json!([
+            {"name": "set_var", "config": {"vars": {"internal": "$params.route"}}},
+            {"name": "ip_whitelist", "config": {
+                "ip_list": "10.0.0.0/8",
+                "when": "$vars.internal == \"/admin\" && String.startsWith($meta.ip_address, \"192.\")"
+            }}
+        ]);
+
+        let response = execute_with_ip(configs.clone(), json!({"route": "/admin"}), "192.168.1.1");
+        assert_eq!(response.status, ChainStatus::Reject);
+
+        let response = execute_with_ip(configs.clone(), json!({"route": "/public"}), "192.168.1.1");
+        assert_eq!(response.status, ChainStatus::Completed);
+
+        let response = execute_with_ip(configs, json!({"route": "/admin"}), "10.1.1.1");
+        assert_eq!(response.status, ChainStatus::Completed);
+    }
+
+    #[test]
+    fn test_when_on_closure() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+
+        let mut config = HashMap::new();
+        config.insert("when".to_string(), json!("$.count > 1"));
+        let executor = ChainExecutor::new()
+            .insert_chainware(
+                "counter",
+                None,
+                Some(move |_: &ChainRequest, _: &mut ChainResponse, data: Option<Value>, _: Option<&ChainwareConfig>| {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    data
+                }),
+                Some(ChainwareConfig::new(config)),
+            )
+            .unwrap();
+
+        executor.execute(ChainRequest::new(json!({"count": 1}), HashMap::new()));
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+        executor.execute(ChainRequest::new(json!({"count": 2}), HashMap::new()));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_when_combined_with_routing() {
+        // when基于整个当前数据求值，不受input_path影响
+        let configs = json!([
+            {"name": "convert", "config": {
+                "when": "Chain.isString($.profile.age)",
+                "fields": {"$": "int"},
+                "input_path": "$.profile.age",
+                "output_path": "$.profile.age"
+            }}
+        ]);
+        let response = execute_with_ip(configs.clone(), json!({"profile": {"age": "30"}}), "10.0.0.1");
+        assert_eq!(response.data, Some(json!({"profile": {"age": 30}})));
+
+        let response = execute_with_ip(configs, json!({"profile": {"age": null}}), "10.0.0.1");
+        assert_eq!(response.data, Some(json!({"profile": {"age": null}})));
+
+        // enabled为false时不求值when
+        let response = execute_with_ip(
+            json!([{"name": "logger", "config": {"enabled": false, "when": "$.a ~~ 1"}}]),
+            json!({"a": 1}),
+            "10.0.0.1",
+        );
+        assert_eq!(response.status, ChainStatus::Completed);
+    }
+
+    #[test]
+    fn test_when_errors() {
+        for (when, expected) in [
+            (json!(true), "when配置必须是字符串类型"),
+            (json!("$.a ~~ 1"), "when表达式 '$.a ~~ 1' 求值失败"),
+        ] {
+            let response = execute_with_ip(
+                json!([{"name": "logger", "config": {"when": when}}]),
+                json!({"a": 1}),
+                "10.0.0.1",
+            );
+            assert_eq!(response.status, ChainStatus::Error);
+            let data = response.data.unwrap();
+            assert_eq!(data["errno"], json!(error_codes::CONFIG_ERROR));
+            assert!(data["msg"].as_str().unwrap().contains(expected), "{}", data["msg"]);
+        }
+    }
+}
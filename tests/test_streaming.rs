@@ -0,0 +1,168 @@
+//! 流式输入测试
+//!
+//! 测试逐块接收文本时流式挂件提前给出结果，以及输入结束后以完整文本执行整条链
+
+use linkchain::chain::ChainExecutor;
+use linkchain::core::{ChainRequest, ChainStatus};
+use linkchain::types::error_codes;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(configs: Value) -> ChainExecutor {
+        ChainExecutor::new().add_chainwares(configs).unwrap()
+    }
+
+    /// 逐块输入，返回每块之后给出结果的节点名称和值
+    fn feed(session: &mut linkchain::chain::StreamSession<'_>, chunks: &[&str]) -> Vec<(usize, String, Value)> {
+        chunks
+            .iter()
+            .enumerate()
+            .flat_map(|(i, chunk)| {
+                session
+                    .push(chunk)
+                    .into_iter()
+                    .map(move |event| (i, event.node_name, event.value))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_extract_json_emits_first_complete_object() {
+        let executor = chain(json!([
+            {"name": "extract_json"},
+            {"name": "patch", "config": {"operations": [{"op": "add", "path": "/checked", "value": true}]}}
+        ]));
+        let mut session = executor.stream_session(ChainRequest::new(Value::Null, HashMap::new()));
+
+        // 外层对象闭合之前，内层对象不能作为结果
+        let events = feed(
+            &mut session,
+            &["结果如下：{\"user\": {\"na", "me\": \"张三\"}", ", \"age\": 3", "0} 以上", "。"],
+        );
+        assert_eq!(
+            events,
+            vec![(3, "extract_json".to_string(), json!({"user": {"name": "张三"}, "age": 30}))]
+        );
+        assert_eq!(session.text(), "结果如下：{\"user\": {\"name\": \"张三\"}, \"age\": 30} 以上。");
+
+        // 输入结束后以完整文本执行整条链，非流式节点此时执行
+        let response = session.finish();
+        assert_eq!(response.status, ChainStatus::Completed);
+        assert_eq!(
+            response.data,
+            Some(json!({"user": {"name": "张三"}, "age": 30, "checked": true}))
+        );
+    }
+
+    #[test]
+    fn test_multiple_streaming_nodes_on_text_path() {
+        // 多个节点通过input_path读取同一段流式文本，结果写入其他字段
+        let executor = chain(json!([
+            {"name": "extract_json", "config": {"input_path": "$.llm_output", "output_path": "$.plan"}},
+            {"name": "extract_json", "config": {"input_path": "$.llm_output", "output_path": "$.first_array", "schema": {"type": "array"}}},
+            {"name": "regexp_extract", "config": {"pattern": "置信度[:：](\\d+)", "group": 1, "input_path": "$.llm_output", "output_path": "$.confidence"}},
+            {"name": "extract_json", "config": {"input_path": "$.llm_output", "output_path": "$.json", "mode": "all"}}
+        ]));
+        let request = ChainRequest::new(json!({"question": "查询用户"}), HashMap::new());
+        let mut session = executor.stream_session_at(request, "$.llm_output").unwrap();
+
+        let events = feed(
+            &mut session,
+            &["{\"step\": 1", "} 置信度：9", "5 [1, ", "2]"],
+        );
+        // regexp_extract不支持流式输入，之后的节点等待输入结束
+        assert_eq!(
+            events,
+            vec![
+                (1, "extract_json".to_string(), json!({"step": 1})),
+                (3, "extract_json".to_string(), json!([1, 2])),
+            ]
+        );
+
+        let response = session.finish();
+        assert_eq!(response.status, ChainStatus::Completed);
+        let data = response.data.unwrap();
+        assert_eq!(data["question"], json!("查询用户"));
+        assert_eq!(data["plan"], json!({"step": 1}));
+        assert_eq!(data["first_array"], json!([1, 2]));
+        assert_eq!(data["confidence"], json!("95"));
+        assert_eq!(data["json"], json!([{"step": 1}, [1, 2]]));
+    }
+
+    #[test]
+    fn test_early_result_matches_final() {
+        // 之前的匹配在更多输入到达后可能被更早开始的匹配取代，regexp_extract不提前给出结果
+        let executor = chain(json!([{"name": "regexp_extract", "config": {"pattern": "BEGIN.*END|\\d+"}}]));
+        let mut session = executor.stream_session(ChainRequest::new(Value::Null, HashMap::new()));
+        assert!(feed(&mut session, &["BEGIN step 1", " then", " END"]).is_empty());
+        assert_eq!(session.finish().data, Some(json!("BEGIN step 1 then END")));
+
+        // 不满足schema的候选被跳过，逐字符输入时从尚未闭合的候选继续查找
+        let executor = chain(json!([
+            {"name": "extract_json", "config": {"schema": {"type": "object", "required": ["sql"]}}}
+        ]));
+        let text = "先给出 {\"step\": [1, {\"n\": 2}]}，再给出 [{\"x\": 1}] 和 {\"sql\": \"SELECT 1\", \"args\": [\"{\"]} 结束";
+        let chunks: Vec<String> = text.chars().map(String::from).collect();
+        let chunks: Vec<&str> = chunks.iter().map(String::as_str).collect();
+        let mut session = executor.stream_session(ChainRequest::new(Value::Null, HashMap::new()));
+        let events = feed(&mut session, &chunks);
+        let closed_at = text[..text.rfind('}').unwrap()].chars().count();
+        assert_eq!(
+            events,
+            vec![(closed_at, "extract_json".to_string(), json!({"sql": "SELECT 1", "args": ["{"]}))]
+        );
+        let response = session.finish();
+        assert_eq!(response.data, Some(json!({"sql": "SELECT 1", "args": ["{"]})));
+
+        // extract_sql不支持流式输入：之后到达的SQL代码块优先于已收到的语句
+        let executor = chain(json!([{"name": "extract_sql"}]));
+        let mut session = executor.stream_session(ChainRequest::new(Value::Null, HashMap::new()));
+        assert!(feed(&mut session, &["先执行 DELETE FROM t;", " 然后\n```sql\nSELECT 'a;b' FROM t\n```"]).is_empty());
+        assert_eq!(session.finish().data, Some(json!("SELECT 'a;b' FROM t")));
+
+        // 修复模式下单引号字符串中的括号无法提前判断是否闭合，不提前给出结果
+        let executor = chain(json!([{"name": "extract_json", "config": {"repair": true}}]));
+        let mut session = executor.stream_session(ChainRequest::new(Value::Null, HashMap::new()));
+        assert!(feed(&mut session, &["{'a': '}", "', 'b': 2}"]).is_empty());
+        assert_eq!(session.finish().data, Some(json!({"a": "}", "b": 2})));
+    }
+
+    #[test]
+    fn test_non_streaming_node_buffers_rest() {
+        // 非流式节点之后的流式节点等待输入结束
+        let executor = chain(json!([
+            {"name": "logger", "config": {"input_path": "$.llm_output", "output_path": "$vars.log"}},
+            {"name": "extract_json", "config": {"input_path": "$.llm_output"}}
+        ]));
+        let request = ChainRequest::new(json!({}), HashMap::new());
+        let mut session = executor.stream_session_at(request, "$.llm_output").unwrap();
+        assert!(feed(&mut session, &["{\"a\": 1}", " 结束"]).is_empty());
+        assert_eq!(session.finish().data, Some(json!({"a": 1})));
+
+        // 节点输入不是流式文本时不提前给出结果
+        let executor = chain(json!([{"name": "extract_json"}]));
+        let request = ChainRequest::new(json!({}), HashMap::new());
+        let mut session = executor.stream_session_at(request, "$.llm_output").unwrap();
+        assert!(feed(&mut session, &["{\"a\": 1}"]).is_empty());
+
+        // 禁用的节点被跳过，when条件依赖数据，不提前执行
+        let executor = chain(json!([
+            {"name": "logger", "config": {"enabled": false}},
+            {"name": "extract_json", "config": {"when": "Chain.isString($)"}}
+        ]));
+        let mut session = executor.stream_session(ChainRequest::new(Value::Null, HashMap::new()));
+        assert!(feed(&mut session, &["{\"a\": 1}"]).is_empty());
+        let response = session.finish();
+        assert_eq!(response.status, ChainStatus::Completed);
+        assert_eq!(response.data, Some(json!({"a": 1})));
+
+        // 流式文本路径不能指向链变量
+        let request = ChainRequest::new(json!({}), HashMap::new());
+        let err = executor.stream_session_at(request, "$vars.text").err().unwrap();
+        assert_eq!(err.errno, error_codes::BAD_REQUEST);
+    }
+}
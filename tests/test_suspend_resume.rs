@@ -0,0 +1,188 @@
+//! 挂起与恢复测试
+//!
+//! 测试节点返回Suspended状态时生成检查点，以及从检查点恢复执行
+
+use linkchain::chain::executor::ChainExecutor;
+use linkchain::chainware::config::ChainwareConfig;
+use linkchain::core::{ChainCheckpoint, ChainRequest, ChainResponse, ChainStatus, CHECKPOINT_VERSION};
+use linkchain::types::error_codes;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 审批挂件：记录审批请求后挂起，数据原样传给下一个节点
+    fn approval(
+        _: &ChainRequest,
+        response: &mut ChainResponse,
+        data: Option<Value>,
+        _: Option<&ChainwareConfig>,
+    ) -> Option<Value> {
+        response.set_meta("approval_requested".to_string(), json!(true));
+        response.status = ChainStatus::Suspended;
+        response.data = Some(json!({"pending": "等待审批"}));
+        data
+    }
+
+    /// 设置变量 -> 审批（挂起）-> 检查审批结果 -> 记录审批人
+    fn approval_chain() -> ChainExecutor {
+        ChainExecutor::new()
+            .add_chainwares(json!([{"name": "set_var", "config": {"vars": {"user": "$.user"}}}]))
+            .unwrap()
+            .insert_chainware("approval", None, Some(approval), None)
+            .unwrap()
+            .add_chainwares(json!([
+                {"name": "condition", "config": {"expression": "$vars.resume_input.approved == true"}},
+                {"name": "map_fields", "config": {"mapping": {"approver": "$vars.resume_input.by", "user": "$vars.user"}}}
+            ]))
+            .unwrap()
+    }
+
+    fn suspend() -> ChainResponse {
+        let mut meta = HashMap::new();
+        meta.insert("trace_id".to_string(), json!("t-1"));
+        approval_chain().execute(ChainRequest::new(json!({"user": "张三", "amount": 5000}), meta))
+    }
+
+    #[test]
+    fn test_suspend_creates_checkpoint() {
+        let response = suspend();
+        assert_eq!(response.status, ChainStatus::Suspended);
+        assert_eq!(response.data, Some(json!({"pending": "等待审批"})));
+
+        let checkpoint = response.checkpoint.unwrap();
+        assert_eq!(checkpoint.version, CHECKPOINT_VERSION);
+        assert_eq!(checkpoint.chain_hash, approval_chain().chain_hash());
+        assert_eq!(checkpoint.node_index, 1);
+        assert_eq!(checkpoint.node_name, "approval");
+        assert_eq!(checkpoint.data, json!({"user": "张三", "amount": 5000}));
+        assert_eq!(checkpoint.request_meta["trace_id"], json!("t-1"));
+        assert_eq!(checkpoint.meta["approval_requested"], json!(true));
+        assert_eq!(checkpoint.vars["user"], json!("张三"));
+
+        // 未挂起时没有检查点
+        let response = ChainExecutor::new().execute(ChainRequest::new(json!({}), HashMap::new()));
+        assert_eq!(response.status, ChainStatus::Completed);
+        assert!(response.checkpoint.is_none());
+    }
+
+    #[test]
+    fn test_resume_in_new_executor() {
+        // 检查点序列化后在新构建的链上恢复，模拟在其他进程中恢复执行
+        let saved = suspend().checkpoint.unwrap().to_json().to_string();
+        let checkpoint = ChainCheckpoint::from_json(serde_json::from_str(&saved).unwrap()).unwrap();
+
+        let response = approval_chain().resume(checkpoint.clone(), json!({"approved": true, "by": "李四"}));
+        assert_eq!(response.status, ChainStatus::Completed);
+        assert_eq!(
+            response.data,
+            Some(json!({"user": "张三", "amount": 5000, "approver": "李四"}))
+        );
+        assert_eq!(response.meta["approval_requested"], json!(true));
+        assert_eq!(response.get_var("resume_input"), Some(&json!({"approved": true, "by": "李四"})));
+        assert!(response.checkpoint.is_none());
+
+        // 审批不通过时被拒绝
+        let response = approval_chain().resume(checkpoint, json!({"approved": false}));
+        assert_eq!(response.status, ChainStatus::Reject);
+    }
+
+    #[test]
+    fn test_suspend_with_data_routing() {
+        // 挂件只处理doc字段，挂起时结果同样写回doc_status，检查点保留其余数据
+        let mut config = HashMap::new();
+        config.insert("input_path".to_string(), json!("$.doc"));
+        config.insert("output_path".to_string(), json!("$.doc_status"));
+        let executor = ChainExecutor::new()
+            .insert_chainware(
+                "review",
+                None,
+                Some(|_: &ChainRequest, response: &mut ChainResponse, data: Option<Value>, _: Option<&ChainwareConfig>| {
+                    response.status = ChainStatus::Suspended;
+                    Some(json!({"seen": data}))
+                }),
+                Some(ChainwareConfig::new(config)),
+            )
+            .unwrap()
+            .add_chainwares(json!([{"name": "map_fields", "config": {"mapping": {"approved": "$vars.resume_input.approved"}}}]))
+            .unwrap();
+
+        let response = executor.execute(ChainRequest::new(json!({"doc": "hello", "other": 42}), HashMap::new()));
+        assert_eq!(response.status, ChainStatus::Suspended);
+        let checkpoint = response.checkpoint.unwrap();
+        assert_eq!(checkpoint.data, json!({"doc": "hello", "other": 42, "doc_status": {"seen": "hello"}}));
+
+        let response = executor.resume(checkpoint, json!({"approved": true}));
+        assert_eq!(response.status, ChainStatus::Completed);
+        assert_eq!(
+            response.data,
+            Some(json!({"doc": "hello", "other": 42, "doc_status": {"seen": "hello"}, "approved": true}))
+        );
+    }
+
+    #[test]
+    fn test_resume_validation() {
+        let checkpoint = suspend().checkpoint.unwrap();
+
+        // 链定义变化
+        let changed = ChainExecutor::new()
+            .add_chainwares(json!([{"name": "set_var", "config": {"vars": {"user": "$.name"}}}]))
+            .unwrap()
+            .insert_chainware("approval", None, Some(approval), None)
+            .unwrap();
+        let response = changed.resume(checkpoint.clone(), Value::Null);
+        assert_eq!(response.status, ChainStatus::Error);
+        let data = response.data.unwrap();
+        assert_eq!(data["errno"], json!(error_codes::BAD_REQUEST));
+        assert_eq!(data["msg"], json!("检查点与当前链定义不匹配"));
+
+        // 版本不兼容
+        let mut old = checkpoint.clone();
+        old.version = CHECKPOINT_VERSION + 1;
+        let response = approval_chain().resume(old, Value::Null);
+        assert_eq!(response.status, ChainStatus::Error);
+        assert!(response.data.unwrap()["msg"].as_str().unwrap().contains("不兼容"));
+
+        // 挂件位置与检查点不一致
+        let mut moved = checkpoint;
+        moved.node_index = 5;
+        let response = approval_chain().resume(moved, Value::Null);
+        assert_eq!(response.status, ChainStatus::Error);
+
+        // 检查点格式错误
+        let err = ChainCheckpoint::from_json(json!({"version": 1})).unwrap_err();
+        assert_eq!(err.errno, error_codes::BAD_REQUEST);
+        assert!(err.msg.starts_with("解析检查点失败"));
+    }
+
+    #[test]
+    fn test_chain_hash() {
+        let build = |configs: Value| ChainExecutor::new().add_chainwares(configs).unwrap().chain_hash();
+
+        // 相同定义的哈希值相同，与配置项顺序无关
+        let a = build(json!([{"name": "merge", "config": {"strategy": "deep", "enabled": true}}]));
+        let b = build(json!([{"name": "merge", "config": {"enabled": true, "strategy": "deep"}}]));
+        assert_eq!(a, b);
+        assert_eq!(a.len(), 16);
+
+        assert_ne!(a, build(json!([{"name": "merge", "config": {"strategy": "shallow"}}])));
+        assert_ne!(a, build(json!([{"name": "merge", "config": {"strategy": "deep", "enabled": true}}, {"name": "logger"}])));
+
+        // 链级错误处理子链也是链定义的一部分
+        let with_handler = ChainExecutor::new()
+            .add_chainwares(json!([{"name": "merge", "config": {"strategy": "deep", "enabled": true}}]))
+            .unwrap()
+            .on_error(json!([{"name": "logger"}]))
+            .unwrap();
+        assert_ne!(a, with_handler.chain_hash());
+    }
+
+    #[test]
+    fn test_suspended_status_name() {
+        assert_eq!(ChainStatus::Suspended.as_str(), "suspended");
+        assert_eq!(ChainStatus::from_str("Suspended"), Ok(ChainStatus::Suspended));
+    }
+}
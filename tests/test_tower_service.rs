@@ -0,0 +1,183 @@
+//! tower中间件测试
+//!
+//! 测试HTTP请求到链请求的转换、请求链和响应链的执行以及状态映射
+
+#![cfg(feature = "tower")]
+
+use bytes::Bytes;
+use http::{Request, Response, StatusCode};
+use http_body_util::{BodyExt, Full};
+use linkchain::chain::ChainExecutor;
+use linkchain::core::ChainResponse;
+use linkchain::service::ChainLayer;
+use linkchain::types::error_codes;
+use serde_json::{json, Value};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use tower::{service_fn, ServiceBuilder, ServiceExt};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 内部服务：返回收到的请求体，并在响应头中带上请求链写入的链变量
+    async fn echo(request: Request<Full<Bytes>>) -> Result<Response<Full<Bytes>>, Infallible> {
+        let user = request
+            .extensions()
+            .get::<ChainResponse>()
+            .and_then(|response| response.get_var("user").cloned())
+            .unwrap_or(Value::Null);
+        let body = request.into_body().collect().await.unwrap().to_bytes();
+        Ok(Response::builder()
+            .status(StatusCode::CREATED)
+            .header("x-user", user.to_string())
+            .body(Full::new(body))
+            .unwrap())
+    }
+
+    fn chain(configs: Value) -> ChainExecutor {
+        ChainExecutor::new().add_chainwares(configs).unwrap()
+    }
+
+    async fn read_body(response: Response<Full<Bytes>>) -> (StatusCode, Value) {
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+    }
+
+    fn post(uri: &str, body: &str) -> Request<Full<Bytes>> {
+        Request::post(uri)
+            .header("x-api-key", "secret")
+            .header("content-type", "application/json")
+            .body(Full::new(Bytes::from(body.to_string())))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_request_chain_transforms_request() {
+        let layer = ChainLayer::new().request_chain(chain(json!([
+            {"name": "condition", "config": {"expression": "$meta['x-api-key'] == 'secret' && $meta.method == 'POST'"}},
+            {"name": "set_var", "config": {"vars": {"user": "$.name"}}},
+            {"name": "map_fields", "config": {"mapping": {
+                "path": "$meta.path",
+                "page": "$meta.query.page",
+                "keyword": "$meta.query.q",
+                "ip": "$meta.ip_address"
+            }}}
+        ])));
+        let service = ServiceBuilder::new().layer(layer).service(service_fn(echo));
+
+        let mut request = post("/users/search?page=2&q=%E5%BC%A0+%E4%B8%89", r#"{"name": "张三"}"#);
+        request.extensions_mut().insert("10.0.0.8:5000".parse::<SocketAddr>().unwrap());
+        let response = service.clone().oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(response.headers()["x-user"], "\"张三\"");
+        let (_, body) = read_body(response).await;
+        assert_eq!(
+            body,
+            json!({"name": "张三", "path": "/users/search", "page": "2", "keyword": "张 三", "ip": "10.0.0.8"})
+        );
+
+        // 请求链拒绝时不调用内部服务
+        let request = Request::post("/users").body(Full::new(Bytes::from("{}"))).unwrap();
+        let (status, body) = read_body(service.oneshot(request).await.unwrap()).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["errno"], json!(error_codes::CONDITION_NOT_MET));
+    }
+
+    #[tokio::test]
+    async fn test_response_chain_and_mapping() {
+        let layer = ChainLayer::new()
+            .request_chain(chain(json!([{"name": "convert", "config": {"fields": {"$.age": "int"}, "mode": "strict"}}])))
+            .response_chain(chain(json!([
+                {"name": "condition", "config": {"expression": "$meta.status == 201"}},
+                {"name": "extract_map", "config": {"mapping": {"data": "$", "user": "$meta.response_headers['x-user']"}}}
+            ])))
+            .status_mapper(|response: &ChainResponse| match response.data.as_ref().map(|data| &data["errno"]) {
+                Some(errno) if errno == &json!(error_codes::VALIDATION_FAILED) => StatusCode::UNPROCESSABLE_ENTITY,
+                _ => linkchain::service::default_status(response),
+            })
+            .body_mapper(|response: &ChainResponse| json!({"error": response.data.as_ref().map(|data| &data["msg"])}));
+        let service = ServiceBuilder::new().layer(layer).service(service_fn(echo));
+
+        // 响应链包装内部服务的响应体，状态码保持不变
+        let response = service.clone().oneshot(post("/", r#"{"age": "30"}"#)).await.unwrap();
+        assert_eq!(response.headers()["content-type"], "application/json");
+        let (status, body) = read_body(response).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(body, json!({"data": {"age": 30}, "user": "null"}));
+
+        // 自定义状态和响应体映射
+        let (status, body) = read_body(service.clone().oneshot(post("/", r#"{"age": "未知"}"#)).await.unwrap()).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(body["error"].as_str().unwrap().starts_with("类型转换失败"));
+
+        // 请求体不是UTF-8文本
+        let request = Request::post("/").body(Full::new(Bytes::from_static(&[0xff, 0xfe]))).unwrap();
+        let (status, body) = read_body(service.oneshot(request).await.unwrap()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["errno"], json!(error_codes::BAD_REQUEST));
+    }
+
+    /// 模拟框架写入的连接信息，例如axum的`ConnectInfo<SocketAddr>`
+    #[derive(Clone)]
+    struct PeerAddr(SocketAddr);
+
+    #[tokio::test]
+    async fn test_headers_cannot_spoof_reserved_meta() {
+        let whitelist = || {
+            chain(json!([
+                {"name": "ip_whitelist", "config": {"ip_list": ["10.0.0.0/8"]}},
+                {"name": "map_fields", "config": {"mapping": {"method": "$meta.method", "ip": "$meta.ip_address"}}}
+            ]))
+        };
+        let spoofed = |peer: Option<&str>| {
+            let mut request = Request::put("/")
+                .header("ip_address", "10.0.0.1")
+                .header("method", "GET")
+                .body(Full::new(Bytes::from("{}")))
+                .unwrap();
+            if let Some(peer) = peer {
+                request.extensions_mut().insert(peer.parse::<SocketAddr>().unwrap());
+                request.extensions_mut().insert(PeerAddr(peer.parse().unwrap()));
+            }
+            request
+        };
+
+        // 请求头中的ip_address不能绕过白名单，无论是否有连接地址
+        let service = ServiceBuilder::new()
+            .layer(ChainLayer::new().request_chain(whitelist()))
+            .service(service_fn(echo));
+        for peer in [Some("203.0.113.5:4000"), None] {
+            let (status, body) = read_body(service.clone().oneshot(spoofed(peer)).await.unwrap()).await;
+            assert_eq!(status, StatusCode::FORBIDDEN);
+            assert_eq!(body["errno"], json!(error_codes::FORBIDDEN));
+        }
+
+        // 保留字段始终由中间件写入
+        let (status, body) = read_body(service.oneshot(spoofed(Some("10.1.2.3:4000"))).await.unwrap()).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(body, json!({"method": "PUT", "ip": "10.1.2.3"}));
+
+        // 自定义客户端IP提取
+        let layer = ChainLayer::new()
+            .request_chain(whitelist())
+            .client_addr(|parts| parts.extensions.get::<PeerAddr>().map(|peer| peer.0.ip()));
+        let service = ServiceBuilder::new().layer(layer).service(service_fn(echo));
+        let mut request = Request::put("/").body(Full::new(Bytes::from("{}"))).unwrap();
+        request.extensions_mut().insert(PeerAddr("10.9.9.9:80".parse().unwrap()));
+        let (status, body) = read_body(service.oneshot(request).await.unwrap()).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(body["ip"], json!("10.9.9.9"));
+    }
+
+    #[tokio::test]
+    async fn test_without_chains_passes_through() {
+        let service = ServiceBuilder::new().layer(ChainLayer::new()).service(service_fn(echo));
+        let response = service.oneshot(post("/", "纯文本")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, Bytes::from("纯文本"));
+    }
+}
@@ -0,0 +1,124 @@
+//! 数据流转测试
+//!
+//! 测试执行过程中的响应数据、借用上下文的路径求值，以及JSON对象上下文的兼容接口
+
+use linkchain::chain::executor::ChainExecutor;
+use linkchain::chainware::config::ChainwareConfig;
+use linkchain::core::{ChainRequest, ChainResponse, ChainStatus};
+use linkchain::utils::json_path::{JsonPathTemplate, PathContext};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 记录执行过程中的响应数据
+    fn observe(
+        _: &ChainRequest,
+        response: &mut ChainResponse,
+        data: Option<Value>,
+        _: Option<&ChainwareConfig>,
+    ) -> Option<Value> {
+        let current = response.data.clone().unwrap_or_default();
+        response.set_meta("data_during_execution".to_string(), current);
+        data
+    }
+
+    #[test]
+    fn test_response_data_during_execution() {
+        let executor = ChainExecutor::new()
+            .add_chainwares(json!([
+                {"name": "condition", "config": {"expression": "$.size > 0"}},
+                {"name": "logger"}
+            ]))
+            .unwrap()
+            .insert_chainware("observe", None, Some(observe), None)
+            .unwrap();
+
+        let payload = json!({"size": 3, "text": "x".repeat(1024)});
+        let response = executor.execute(ChainRequest::new(payload.clone(), HashMap::new()));
+        assert_eq!(response.status, ChainStatus::Completed);
+        assert_eq!(response.meta["data_during_execution"], payload);
+        assert_eq!(response.data, Some(payload));
+
+        // 拒绝时响应数据为拒绝原因，而非当前数据
+        let response = executor.execute(ChainRequest::new(json!({"size": 0}), HashMap::new()));
+        assert_eq!(response.status, ChainStatus::Reject);
+        assert!(response.data.unwrap().get("errno").is_some());
+        assert!(!response.meta.contains_key("data_during_execution"));
+    }
+
+    #[test]
+    fn test_callback_without_response_data() {
+        // 回调停止执行但未设置响应数据时，响应数据为回调的输入数据
+        let executor = ChainExecutor::new()
+            .insert_chainware(
+                "stop",
+                None,
+                Some(|_: &ChainRequest, response: &mut ChainResponse, _: Option<Value>, _: Option<&ChainwareConfig>| {
+                    response.status = ChainStatus::Completed;
+                    Some(json!({"cached": true}))
+                }),
+                None,
+            )
+            .unwrap()
+            .add_chainwares(json!([{"name": "patch", "config": {"operations": [{"op": "add", "path": "/unreachable", "value": true}]}}]))
+            .unwrap();
+        let response = executor.execute(ChainRequest::new(json!({}), HashMap::new()));
+        assert_eq!(response.status, ChainStatus::Completed);
+        assert_eq!(response.data, Some(json!({})));
+    }
+
+    #[test]
+    fn test_borrowed_context_lookup() {
+        let input = json!({"items": [1, 2, 3]});
+        let params = json!({"user": {"name": "张三"}});
+        let mut meta = HashMap::new();
+        meta.insert("client.ip".to_string(), json!("10.0.0.1"));
+        let mut vars = HashMap::new();
+        vars.insert("tags".to_string(), json!(["a", "b"]));
+        let context = PathContext::new(&input, &params, &meta, &vars);
+
+        let get = |template: &str| JsonPathTemplate::get_path_value(&context, template).unwrap();
+        assert_eq!(get("$.items.length"), Some(json!(3)));
+        assert_eq!(get("$params.user.name"), Some(json!("张三")));
+        assert_eq!(get("$meta['client.ip']"), Some(json!("10.0.0.1")));
+        assert_eq!(get("$vars.tags[1]"), Some(json!("b")));
+        assert_eq!(get("$vars.missing"), None);
+        assert_eq!(get("用户${$params.user.name}"), Some(json!("用户张三")));
+
+        // 数组元素上下文共享其余部分
+        let item = json!({"id": 9});
+        let item_context = context.with_item(&item);
+        let get = |template: &str| JsonPathTemplate::get_path_value(&item_context, template).unwrap();
+        assert_eq!(get("$item.id"), Some(json!(9)));
+        assert_eq!(get("$.id"), Some(json!(9)));
+        assert_eq!(get("$vars.tags.length"), Some(json!(2)));
+    }
+
+    #[test]
+    fn test_value_context_lookup() {
+        let mut request = ChainRequest::new(json!({"user": {"name": "张三"}}), HashMap::new());
+        request.meta.insert("client.ip".to_string(), json!("10.0.0.1"));
+        let mut response = ChainResponse::new(request.start_time);
+        response.set_var("tags".to_string(), json!(["a", "b"]));
+        let input = json!({"items": [1, 2, 3]});
+
+        // JSON对象上下文与借用上下文的求值结果一致
+        let context = JsonPathTemplate::build_context(&input, &request, &response);
+        let get = |template: &str| JsonPathTemplate::get_value(&context, template).unwrap();
+        assert_eq!(get("$.items.length"), Some(json!(3)));
+        assert_eq!(get("$params.user.name"), Some(json!("张三")));
+        assert_eq!(get("$meta['client.ip']"), Some(json!("10.0.0.1")));
+        assert_eq!(get("$vars.tags[1]"), Some(json!("b")));
+        assert_eq!(get("$item"), Some(Value::Null));
+
+        let item = json!({"id": 9});
+        let item_context = JsonPathTemplate::build_item_context(&item, &context);
+        let get = |template: &str| JsonPathTemplate::get_value(&item_context, template).unwrap();
+        assert_eq!(get("$item.id"), Some(json!(9)));
+        assert_eq!(get("$.id"), Some(json!(9)));
+        assert_eq!(get("$vars.tags.length"), Some(json!(2)));
+    }
+}